@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+
+use crate::geometry::angular_shape::{AngularShape, ShapeError};
+use crate::geometry::point::Point;
+
+// Algorithms that work on plain lists of corner points, so that they can be
+// used without building a shape first.
+
+// A point as the bits of its coordinates, for finding exactly equal points.
+type PointKey = (u64, u64);
+
+// Get the winding number of a point with respect to the polygon formed by the
+// corners: how many times the boundary goes around the point counterclockwise.
+// Clockwise turns count as negative. The number is 0 for points outside the
+// polygon. Points exactly on the boundary may count either way.
+// http://geomalgorithms.com/a03-_inclusion.html
+pub fn winding_number( p: &Point, corners: &[Point] ) -> i32 {
+    let mut winding: i32 = 0;
+    for i in 0..corners.len() {
+        let start: Point = corners[i];
+        let end: Point = corners[ (i + 1) % corners.len() ];
+
+        // Which side of the edge the point is on, positive for left.
+        let side: f64 = (end.x - start.x) * (p.y - start.y) - (p.x - start.x) * (end.y - start.y);
+
+        // Count upward crossings with the point on the left and downward
+        // crossings with the point on the right.
+        if start.y <= p.y {
+            if end.y > p.y && side > 0.0 {
+                winding += 1;
+            }
+        }
+        else if end.y <= p.y && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    return winding;
+}
+
+
+// Split the polygon formed by the corners into triangles by ear clipping.
+// The polygon must be simple, that is, its edges must not cross. The triangles
+// are counterclockwise whatever the direction of the corners, and corners on a
+// straight line between their neighbours are left out.
+// https://www.geometrictools.com/Documentation/TriangulationByEarClipping.pdf
+pub fn triangulate( corners: &[Point] ) -> Vec<[Point; 3]> {
+
+    // Work on the corners in counterclockwise order.
+    let mut remaining: Vec<Point> = corners.to_vec();
+    if signed_area( &remaining ) < 0.0 {
+        remaining.reverse();
+    }
+
+    // Cut off ears until only a triangle is left.
+    let mut triangles: Vec<[Point; 3]> = Vec::new();
+    while remaining.len() > 3 {
+        let count: usize = remaining.len();
+        let mut clipped: bool = false;
+        for i in 0..count {
+            let previous: Point = remaining[ (i + count - 1) % count ];
+            let current: Point = remaining[i];
+            let next: Point = remaining[ (i + 1) % count ];
+
+            // A corner on a straight line is dropped without a triangle.
+            let turn: f64 = cross( previous, current, next );
+            if turn == 0.0 {
+                remaining.remove( i );
+                clipped = true;
+                break;
+            }
+
+            // An ear is a convex corner with no other corner inside its triangle.
+            if turn < 0.0 {
+                continue;
+            }
+            let is_ear: bool = remaining.iter()
+                .filter( |p| **p != previous && **p != current && **p != next )
+                .all( |p| !in_triangle( *p, previous, current, next ) );
+            if is_ear {
+                triangles.push( [previous, current, next] );
+                remaining.remove( i );
+                clipped = true;
+                break;
+            }
+        }
+
+        // A polygon with crossing edges may have no ears left.
+        if !clipped {
+            return triangles;
+        }
+    }
+    if remaining.len() == 3 && cross( remaining[0], remaining[1], remaining[2] ) != 0.0 {
+        triangles.push( [remaining[0], remaining[1], remaining[2]] );
+    }
+    return triangles;
+}
+
+// Rebuild the outline of a polygon from the triangles it was split into,
+// for example by triangulate. The outline is made of the edges that belong to
+// only one triangle, and the corners start from an arbitrary corner.
+// Returns an error if the outline edges do not form a single closed polygon.
+pub fn triangles_to_polygon( triangles: &[[Point; 3]] ) -> Result<AngularShape, ShapeError> {
+
+    // Points are compared exactly, so the edges are keyed by the bits of the coordinates.
+    let key = |p: Point| -> PointKey { (p.x.to_bits(), p.y.to_bits()) };
+
+    // Count how many triangles share each edge, in either direction.
+    // The triangles are turned counterclockwise so that the outline has a single direction.
+    let mut edges: Vec<(Point, Point)> = Vec::new();
+    let mut edge_counts: HashMap<(PointKey, PointKey), usize> = HashMap::new();
+    for triangle in triangles {
+        let mut triangle: [Point; 3] = *triangle;
+        if cross( triangle[0], triangle[1], triangle[2] ) < 0.0 {
+            triangle.reverse();
+        }
+        for i in 0..3 {
+            let (start, end) = (triangle[i], triangle[ (i + 1) % 3 ]);
+            let (a, b) = (key( start ), key( end ));
+            *edge_counts.entry( if a < b { (a, b) } else { (b, a) } ).or_insert( 0 ) += 1;
+            edges.push( (start, end) );
+        }
+    }
+
+    // Link the outline edges from their start to their end.
+    let mut next: HashMap<PointKey, Point> = HashMap::new();
+    for (start, end) in edges {
+        let (a, b) = (key( start ), key( end ));
+        if edge_counts[ &if a < b { (a, b) } else { (b, a) } ] != 1 {
+            continue;
+        }
+        if next.insert( a, end ).is_some() {
+            return Err( ShapeError::NotValidShape( format!("The outline touches itself at {}.", start).into() ) );
+        }
+    }
+
+    // Follow the outline from any corner until it closes.
+    let Some( first ) = triangles.iter().flatten().find( |p| next.contains_key( &key( **p ) ) ) else {
+        return Err( ShapeError::NotValidShape( "There are no triangles with an outline.".into() ) );
+    };
+    let mut corners: Vec<Point> = vec![ *first ];
+    let mut current: Point = next[ &key( *first ) ];
+    while current != *first {
+        if corners.len() >= next.len() {
+            return Err( ShapeError::NotValidShape( "The outline does not close.".into() ) );
+        }
+        corners.push( current );
+        let Some( following ) = next.get( &key( current ) ) else {
+            return Err( ShapeError::NotValidShape( format!("The outline ends at {}.", current).into() ) );
+        };
+        current = *following;
+    }
+    if corners.len() != next.len() {
+        return Err( ShapeError::NotValidShape( "The triangles have more than one outline.".into() ) );
+    }
+    return Ok( AngularShape { corners } );
+}
+
+// Get the area filled by the polygon formed by the corners with the even-odd
+// rule: a point is inside if a ray from it crosses the edges an odd number of
+// times. The edges may cross each other. The plane is cut into vertical slabs
+// at the corners and the crossings, so that inside a slab the edges do not
+// cross, and the filled length changes linearly with x. The filled length in
+// the middle of the slab times its width is then exactly the area in the slab.
+pub fn even_odd_area( corners: &[Point] ) -> f64 {
+    let count: usize = corners.len();
+    let edge = |i: usize| -> (Point, Point) { ( corners[i], corners[ (i + 1) % count ] ) };
+
+    // The slabs start and end at the corners and where the edges cross.
+    let mut xs: Vec<f64> = corners.iter().map( |p| p.x ).collect();
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let ( a, b ) = edge( i );
+            let ( c, d ) = edge( j );
+            let denominator: f64 = (b.x - a.x) * (d.y - c.y) - (b.y - a.y) * (d.x - c.x);
+            if denominator == 0.0 {
+                continue;
+            }
+            let t: f64 = ((c.x - a.x) * (d.y - c.y) - (c.y - a.y) * (d.x - c.x)) / denominator;
+            let u: f64 = ((c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)) / denominator;
+            if (0.0..=1.0).contains( &t ) && (0.0..=1.0).contains( &u ) {
+                xs.push( a.x + t * (b.x - a.x) );
+            }
+        }
+    }
+    xs.sort_by( f64::total_cmp );
+    xs.dedup();
+
+    // Sum the filled lengths in the middle of the slabs.
+    let mut area: f64 = 0.0;
+    for slab in xs.windows( 2 ) {
+        let middle: f64 = (slab[0] + slab[1]) / 2.0;
+        let mut ys: Vec<f64> = (0..count)
+            .map( edge )
+            .filter( |(a, b)| a.x.min( b.x ) < middle && middle < a.x.max( b.x ) )
+            .map( |(a, b)| a.y + (middle - a.x) / (b.x - a.x) * (b.y - a.y) )
+            .collect();
+        ys.sort_by( f64::total_cmp );
+        let length: f64 = ys.chunks_exact( 2 ).map( |pair| pair[1] - pair[0] ).sum();
+        area += length * (slab[1] - slab[0]);
+    }
+    return area;
+}
+
+// Get the sign of (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y), the
+// orientation of the points p, q and r: 1 if they turn clockwise, -1 if they
+// turn counterclockwise and 0 if they are on a straight line. Unlike the same
+// formula in floating point, the sign is exact for all finite coordinates that
+// do not overflow, so nearly collinear points are never misclassified and
+// moving all the points by the same exact offset does not change the result.
+// Every difference and product is split into its rounded value and the exact
+// rounding error, and the parts are summed into an expansion, a sum of floats
+// that do not overlap, whose largest part has the sign of the whole sum.
+// https://www.cs.cmu.edu/~quake/robust.html
+pub fn orientation_sign_exact( p: &Point, q: &Point, r: &Point ) -> i32 {
+    let dy_pq: [f64; 2] = two_diff( q.y, p.y );
+    let dx_qr: [f64; 2] = two_diff( r.x, q.x );
+    let dx_pq: [f64; 2] = two_diff( q.x, p.x );
+    let dy_qr: [f64; 2] = two_diff( r.y, q.y );
+
+    // Sum the exact products of the parts of the differences.
+    let mut expansion: Vec<f64> = Vec::with_capacity( 17 );
+    for a in dy_pq {
+        for b in dx_qr {
+            let ( product, error ) = two_product( a, b );
+            grow_expansion( &mut expansion, product );
+            grow_expansion( &mut expansion, error );
+        }
+    }
+    for a in dx_pq {
+        for b in dy_qr {
+            let ( product, error ) = two_product( a, b );
+            grow_expansion( &mut expansion, -product );
+            grow_expansion( &mut expansion, -error );
+        }
+    }
+
+    // The parts grow in magnitude, so the last one that is not zero gives the sign.
+    return match expansion.iter().rev().find( |part| **part != 0.0 ) {
+        Some( part ) if *part > 0.0 => 1,
+        Some( _ ) => -1,
+        None => 0,
+    };
+}
+
+// Get a - b as the rounded difference and its rounding error.
+fn two_diff( a: f64, b: f64 ) -> [f64; 2] {
+    let difference: f64 = a - b;
+    let b_virtual: f64 = a - difference;
+    let a_virtual: f64 = difference + b_virtual;
+    let error: f64 = (a - a_virtual) + (b_virtual - b);
+    return [ difference, error ];
+}
+
+// Get a + b as the rounded sum and its rounding error.
+fn two_sum( a: f64, b: f64 ) -> ( f64, f64 ) {
+    let sum: f64 = a + b;
+    let b_virtual: f64 = sum - a;
+    let a_virtual: f64 = sum - b_virtual;
+    let error: f64 = (a - a_virtual) + (b - b_virtual);
+    return ( sum, error );
+}
+
+// Get a * b as the rounded product and its rounding error.
+fn two_product( a: f64, b: f64 ) -> ( f64, f64 ) {
+    let product: f64 = a * b;
+    return ( product, a.mul_add( b, -product ) );
+}
+
+// Add a value to an expansion, keeping the parts in order of magnitude and
+// leaving out the zero rounding errors.
+fn grow_expansion( expansion: &mut Vec<f64>, value: f64 ) {
+    let mut sum: f64 = value;
+    let mut grown: Vec<f64> = Vec::with_capacity( expansion.len() + 1 );
+    for part in expansion.iter() {
+        let ( new_sum, error ) = two_sum( sum, *part );
+        if error != 0.0 {
+            grown.push( error );
+        }
+        sum = new_sum;
+    }
+    grown.push( sum );
+    *expansion = grown;
+}
+
+// Get the convex hull of the points, the smallest convex polygon that contains
+// all of them, with the corners counterclockwise. Points on the edges of the
+// hull are not corners. The hull of less than 3 points, or of points on one
+// line, has less than 3 corners.
+// https://en.wikibooks.org/wiki/Algorithm_Implementation/Geometry/Convex_hull/Monotone_chain
+pub fn convex_hull( points: &[Point] ) -> Vec<Point> {
+    let mut sorted: Vec<Point> = points.iter().copied().filter( |p| p.x.is_finite() && p.y.is_finite() ).collect();
+    sorted.sort_by( |a, b| a.x.total_cmp( &b.x ).then( a.y.total_cmp( &b.y ) ) );
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // Build the lower hull from left to right and the upper hull from right
+    // to left, dropping corners that do not turn left.
+    let mut hull: Vec<Point> = Vec::with_capacity( sorted.len() + 1 );
+    for pass in [ sorted.clone(), sorted.iter().rev().copied().collect() ] {
+        let start: usize = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross( hull[hull.len() - 2], hull[hull.len() - 1], p ) <= 0.0 {
+                hull.pop();
+            }
+            hull.push( p );
+        }
+
+        // The last point is the first point of the other half.
+        hull.pop();
+    }
+    return hull;
+}
+
+// Check if the polygon formed by the corners is convex: it turns the same way
+// at every corner. Corners on a straight line do not turn either way. The
+// polygon is expected not to intersect itself.
+pub fn is_convex( corners: &[Point] ) -> bool {
+    let mut left: bool = false;
+    let mut right: bool = false;
+    for i in 0..corners.len() {
+        let turn: f64 = cross( corners[i], corners[ (i + 1) % corners.len() ], corners[ (i + 2) % corners.len() ] );
+        left |= turn > 0.0;
+        right |= turn < 0.0;
+    }
+    return corners.len() >= 3 && !(left && right);
+}
+
+// Get the intersection of two convex polygons, the polygon covered by both,
+// with the corners counterclockwise. The polygons can be in either direction.
+// The intersection is empty if the polygons do not overlap, and has less than
+// 3 corners if they only touch.
+// https://en.wikipedia.org/wiki/Sutherland%E2%80%93Hodgman_algorithm
+pub fn convex_intersection( subject: &[Point], clip: &[Point] ) -> Vec<Point> {
+    let counterclockwise = |corners: &[Point]| -> Vec<Point> {
+        if signed_area( corners ) < 0.0 { corners.iter().rev().copied().collect() } else { corners.to_vec() }
+    };
+    let clip: Vec<Point> = counterclockwise( clip );
+    let mut output: Vec<Point> = counterclockwise( subject );
+
+    // Clip the subject by each edge of the clip polygon in turn, keeping
+    // the part on the left side of the edge.
+    for i in 0..clip.len() {
+        if output.is_empty() {
+            break;
+        }
+        let a: Point = clip[i];
+        let b: Point = clip[ (i + 1) % clip.len() ];
+        let side = |p: Point| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+        let input: Vec<Point> = std::mem::take( &mut output );
+        for j in 0..input.len() {
+            let current: Point = input[j];
+            let previous: Point = input[ (j + input.len() - 1) % input.len() ];
+            let (current_side, previous_side) = (side( current ), side( previous ));
+
+            // Add the point where the edge of the subject crosses the clip edge.
+            if (current_side >= 0.0) != (previous_side >= 0.0) {
+                let t: f64 = previous_side / (previous_side - current_side);
+                output.push( previous + (current - previous) * t );
+            }
+            if current_side >= 0.0 {
+                output.push( current );
+            }
+        }
+    }
+    return output;
+}
+
+// Get the signed area of the polygon formed by the corners,
+// positive if the corners are counterclockwise. Unlike
+// AngularShape::signed_area, the polygon is not checked to be valid.
+pub fn signed_area( corners: &[Point] ) -> f64 {
+    let mut sum: f64 = 0.0;
+    for i in 0..corners.len() {
+        let start: Point = corners[i];
+        let end: Point = corners[ (i + 1) % corners.len() ];
+        sum += start.x * end.y - end.x * start.y;
+    }
+    return sum / 2.0;
+}
+
+// Get the signed area of the polygon formed by the corners with the
+// trapezoid formula: each line and its projection on the x axis make a
+// trapezoid, and the trapezoids under the top lines minus those under the
+// bottom lines are the polygon. Gives the same area as signed_area in
+// another way, which makes it a cross-check.
+// https://en.wikipedia.org/wiki/Shoelace_formula#Trapezoid_formula
+pub fn trapezoid_area( corners: &[Point] ) -> f64 {
+    let mut sum: f64 = 0.0;
+    for i in 0..corners.len() {
+        let start: Point = corners[i];
+        let end: Point = corners[ (i + 1) % corners.len() ];
+        sum += (start.x - end.x) * (start.y + end.y);
+    }
+    return sum / 2.0;
+}
+
+// Get the cross product of (b - a) and (c - b), positive for a left turn at b.
+fn cross( a: Point, b: Point, c: Point ) -> f64 {
+    return (b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x);
+}
+
+// Check if the point is inside or on the counterclockwise triangle a, b, c.
+fn in_triangle( p: Point, a: Point, b: Point, c: Point ) -> bool {
+    return cross( a, b, p ) >= 0.0 && cross( b, c, p ) >= 0.0 && cross( c, a, p ) >= 0.0;
+}
+
+
+// Unit tests for the algorithms.
+#[cfg(test)]
+mod tests {
+    use crate::geometry::traits::AreaCalculatable;
+
+    use super::*;
+
+    fn square() -> Vec<Point> {
+        vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 0.0, y: 2.0 },
+        ]
+    }
+
+    #[test]
+    fn test_winding_number_counterclockwise() {
+        assert_eq!( winding_number( &Point { x: 1.0, y: 1.0 }, &square() ), 1 );
+        assert_eq!( winding_number( &Point { x: 3.0, y: 1.0 }, &square() ), 0 );
+        assert_eq!( winding_number( &Point { x: 1.0, y: -1.0 }, &square() ), 0 );
+    }
+
+    #[test]
+    fn test_winding_number_clockwise() {
+        let mut corners: Vec<Point> = square();
+        corners.reverse();
+        assert_eq!( winding_number( &Point { x: 1.0, y: 1.0 }, &corners ), -1 );
+        assert_eq!( winding_number( &Point { x: -1.0, y: 1.0 }, &corners ), 0 );
+    }
+
+    #[test]
+    fn test_winding_number_twice_around() {
+
+        // A pentagram goes around its center twice.
+        let corners: Vec<Point> = (0..5)
+            .map( |i| {
+                let angle: f64 = i as f64 * 4.0 * std::f64::consts::PI / 5.0;
+                Point { x: angle.cos(), y: angle.sin() }
+            })
+            .collect();
+        assert_eq!( winding_number( &Point { x: 0.0, y: 0.0 }, &corners ), 2 );
+        assert_eq!( winding_number( &Point { x: 2.0, y: 0.0 }, &corners ), 0 );
+    }
+
+    #[test]
+    fn test_winding_number_empty() {
+        assert_eq!( winding_number( &Point { x: 0.0, y: 0.0 }, &[] ), 0 );
+    }
+
+    fn pentagon() -> Vec<Point> {
+        vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 0.0 },
+            Point { x: 5.0, y: 3.0 },
+            Point { x: 2.0, y: 5.0 },
+            Point { x: -1.0, y: 3.0 },
+        ]
+    }
+
+    fn triangle_area( triangle: &[Point; 3] ) -> f64 {
+        return cross( triangle[0], triangle[1], triangle[2] ).abs() / 2.0;
+    }
+
+    #[test]
+    fn test_triangulate() {
+        let shape: AngularShape = AngularShape { corners: pentagon() };
+        let triangles: Vec<[Point; 3]> = triangulate( &shape.corners );
+        assert_eq!( triangles.len(), 3 );
+        let sum: f64 = triangles.iter().map( triangle_area ).sum();
+        assert!( (sum - shape.area()).abs() < 1e-9 );
+    }
+
+    #[test]
+    fn test_triangulate_concave_clockwise() {
+
+        // An L shape drawn clockwise.
+        let corners: Vec<Point> = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.0, y: 2.0 },
+            Point { x: 1.0, y: 2.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 2.0, y: 1.0 },
+            Point { x: 2.0, y: 0.0 },
+        ];
+        let triangles: Vec<[Point; 3]> = triangulate( &corners );
+        assert_eq!( triangles.len(), 4 );
+        let sum: f64 = triangles.iter().map( triangle_area ).sum();
+        assert_eq!( sum, 3.0 );
+    }
+
+    #[test]
+    fn test_triangles_to_polygon_round_trip() {
+        let original: AngularShape = AngularShape { corners: pentagon() };
+        let triangles: Vec<[Point; 3]> = triangulate( &original.corners );
+        let shape: AngularShape = triangles_to_polygon( &triangles ).unwrap();
+
+        assert_eq!( shape.corners.len(), 5 );
+        assert!( (shape.area() - original.area()).abs() < 1e-9 );
+        let sum: f64 = triangles.iter().map( triangle_area ).sum();
+        assert!( (shape.area() - sum).abs() < 1e-9 );
+        for corner in &original.corners {
+            assert!( shape.corners.contains( corner ) );
+        }
+    }
+
+    #[test]
+    fn test_triangles_to_polygon_invalid() {
+        assert!( triangles_to_polygon( &[] ).is_err() );
+
+        // Two triangles apart have two outlines.
+        let triangles: [[Point; 3]; 2] = [
+            [ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 } ],
+            [ Point { x: 5.0, y: 5.0 }, Point { x: 6.0, y: 5.0 }, Point { x: 5.0, y: 6.0 } ],
+        ];
+        assert!( triangles_to_polygon( &triangles ).is_err() );
+
+        // Two triangles touching at a corner.
+        let triangles: [[Point; 3]; 2] = [
+            [ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 } ],
+            [ Point { x: 1.0, y: 0.0 }, Point { x: 2.0, y: 0.0 }, Point { x: 2.0, y: -1.0 } ],
+        ];
+        assert!( triangles_to_polygon( &triangles ).is_err() );
+    }
+
+    #[test]
+    fn test_even_odd_area_bow_tie() {
+
+        // Two triangles meeting at (1, 1), the signed areas cancel out.
+        let bow_tie: Vec<Point> = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 0.0, y: 2.0 },
+        ];
+        assert_eq!( signed_area( &bow_tie ), 0.0 );
+        assert!( ( even_odd_area( &bow_tie ) - 2.0 ).abs() < 1e-12 );
+        assert_eq!( even_odd_area( &square() ), 4.0 );
+        assert_eq!( even_odd_area( &[] ), 0.0 );
+    }
+
+    #[test]
+    fn test_orientation_sign_exact() {
+
+        // Points the floating point formula takes as collinear, but are not.
+        // https://people.mpi-inf.mpg.de/~kettner/pub/nonrobust_cgta_06.pdf
+        let q: Point = Point { x: 12.0, y: 12.0 };
+        let r: Point = Point { x: 24.0, y: 24.0 };
+        let above: Point = Point { x: 0.5, y: 0.5000000000000001 };
+        let below: Point = Point { x: 0.5000000000000001, y: 0.5 };
+        assert_eq!( orientation_sign_exact( &above, &q, &r ), -1 );
+        assert_eq!( orientation_sign_exact( &below, &q, &r ), 1 );
+        assert_eq!( orientation_sign_exact( &Point { x: 0.5, y: 0.5 }, &q, &r ), 0 );
+
+        // Moving the points by an exact offset does not change the result.
+        let offset: Point = Point { x: -0.5, y: -0.5 };
+        assert_eq!( orientation_sign_exact( &( above + offset ), &( q + offset ), &( r + offset ) ), -1 );
+        assert_eq!( orientation_sign_exact( &( below + offset ), &( q + offset ), &( r + offset ) ), 1 );
+
+        // A point on the wrong side in floating point.
+        let q: Point = Point { x: 0.3, y: 0.7 };
+        let r: Point = Point { x: 17.1, y: 39.9 };
+        let p: Point = Point { x: 7.3, y: 17.03333333333333 };
+        let float: f64 = (r.y - q.y) * (p.x - r.x) - (r.x - q.x) * (p.y - r.y);
+        assert!( float > 0.0 );
+        assert_eq!( orientation_sign_exact( &q, &r, &p ), -1 );
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        // The inner points and the point on an edge are not corners.
+        let mut points: Vec<Point> = square();
+        points.push( Point { x: 1.0, y: 1.0 } );
+        points.push( Point { x: 0.5, y: 1.5 } );
+        points.push( Point { x: 2.0, y: 1.0 } );
+        points.push( Point { x: 0.0, y: 0.0 } );
+        let hull: Vec<Point> = convex_hull( &points );
+        assert_eq!( hull, square() );
+        assert_eq!( signed_area( &hull ), 4.0 );
+
+        // Points on one line have no area to enclose.
+        let line: Vec<Point> = (0..5).map( |i| Point { x: i as f64, y: 2.0 * i as f64 } ).collect();
+        assert!( convex_hull( &line ).len() < 3 );
+        assert!( convex_hull( &[] ).is_empty() );
+    }
+
+    #[test]
+    fn test_is_convex() {
+        assert!( is_convex( &square() ) );
+        assert!( is_convex( &square().into_iter().rev().collect::<Vec<Point>>() ) );
+        assert!( !is_convex( &[
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 1.0, y: 0.5 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 0.0, y: 2.0 },
+        ] ) );
+        assert!( !is_convex( &square()[..2] ) );
+    }
+
+    #[test]
+    fn test_convex_intersection() {
+        // Two squares overlapping in a 1 by 1 square, one of them clockwise.
+        let shifted: Vec<Point> = square().iter().rev().map( |p| *p + Point { x: 1.0, y: 1.0 } ).collect();
+        let overlap: Vec<Point> = convex_intersection( &square(), &shifted );
+        assert!( ( signed_area( &overlap ) - 1.0 ).abs() < 1e-12 );
+
+        // A square inside the other one is the intersection.
+        let inner: Vec<Point> = square().iter().map( |p| *p * 0.5 + Point { x: 0.5, y: 0.5 } ).collect();
+        assert!( ( signed_area( &convex_intersection( &square(), &inner ) ) - 1.0 ).abs() < 1e-12 );
+
+        // Squares apart from each other do not intersect.
+        let apart: Vec<Point> = square().iter().map( |p| *p + Point { x: 5.0, y: 0.0 } ).collect();
+        assert!( convex_intersection( &square(), &apart ).is_empty() );
+    }
+
+    #[test]
+    fn test_trapezoid_area() {
+        assert!( ( trapezoid_area( &square() ) - signed_area( &square() ) ).abs() < 1e-12 );
+        let clockwise: Vec<Point> = square().into_iter().rev().collect();
+        assert!( ( trapezoid_area( &clockwise ) + 4.0 ).abs() < 1e-12 );
+    }
+}
@@ -1,9 +1,14 @@
+use crate::geometry::algorithms;
 use crate::geometry::point::Point;
+use crate::geometry::circle::Circle;
 use crate::geometry::line::Line;
-use crate::geometry::traits::AreaCalculatable;
+use crate::geometry::random::Random;
+use crate::geometry::traits::{AreaCalculatable, BoundingBox, PerimeterCalculatable};
+use crate::geometry::units::{Area, Unit};
 use std::error::Error;
-use tracing::{debug};
+use tracing::{debug, warn};
 
+use super::error::DEGENERATE_TOLERANCE;
 use super::line::LineError;
 
 pub struct AngularShape {
@@ -14,6 +19,9 @@ pub struct AngularShape {
 #[derive(Debug)]
 pub enum ShapeError {
     NotValidShape( Box<dyn Error> ),
+    IndexOutOfBounds { index: usize, corner_count: usize },
+    InvalidDiagonal { from: usize, to: usize },
+    DegenerateZeroArea,
 }
 impl std::fmt::Display for ShapeError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -21,10 +29,72 @@ impl std::fmt::Display for ShapeError {
             ShapeError::NotValidShape( error ) => {
                 write!(f, "The shape is not valid. {}", error)
             }
+            ShapeError::IndexOutOfBounds { index, corner_count } => {
+                write!(f, "Corner index {} is out of bounds, the shape has {} corners.", index, corner_count)
+            }
+            ShapeError::InvalidDiagonal { from, to } => {
+                write!(f, "There is no diagonal from corner {} to corner {} inside the shape.", from, to)
+            }
+            ShapeError::DegenerateZeroArea => {
+                write!(f, "The corners of the shape are on one line and have no area.")
+            }
         }
     }
 }
 impl Error for ShapeError {}
+
+// Statistics of a shape, as written to a CSV row.
+#[derive(Debug, PartialEq)]
+pub struct ShapeStats {
+    pub corner_count: usize,
+    pub area: f64,
+    pub perimeter: f64,
+    pub centroid: Option<Point>,
+    pub width: f64,
+    pub height: f64,
+}
+
+// The area, the signed area and the centroid of a shape, computed together.
+// The signed area is positive if the corners are counterclockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaProperties {
+    pub area: f64,
+    pub signed_area: f64,
+    pub centroid: Point,
+}
+
+// Errors from parsing a CSV row.
+#[derive(Debug)]
+pub enum ParseError {
+    WrongColumnCount { expected: usize, found: usize },
+    InvalidValue { column: &'static str, value: String },
+}
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::WrongColumnCount { expected, found } => {
+                write!(f, "Expected {} columns but found {}.", expected, found)
+            }
+            ParseError::InvalidValue { column, value } => {
+                write!(f, "Invalid value '{}' in column {}.", value, column)
+            }
+        }
+    }
+}
+impl Error for ParseError {}
+
+// The columns of a CSV row.
+const CSV_COLUMNS: [&str; 7] = [
+    "corner_count", "area", "perimeter", "centroid_x", "centroid_y", "width", "height"
+];
+
+// How much the lengths of opposite sides of a rectangle can differ, relative to the longer side.
+const RECTANGLE_SIDE_TOLERANCE: f64 = 0.01;
+
+// How much the areas in area_checked can differ, relative to the area.
+const AREA_CHECK_TOLERANCE: f64 = 0.000000001;
+
+
 impl From<LineError> for ShapeError {
     fn from( error: LineError ) -> Self {
         ShapeError::NotValidShape( Box::new( error ) )
@@ -34,8 +104,67 @@ impl From<LineError> for ShapeError {
 // Implement the AngularShape struct.
 impl AngularShape {
 
-    // Check if the shape is valid.
+    // Create a rectangle with its sides along the axes, with one corner at the
+    // origin and the opposite corner at origin + (width, height). A negative
+    // width or height goes left or down from the origin, so the corners are
+    // always counterclockwise from the bottom left corner. The rectangle is
+    // valid if its shorter side is longer than DEGENERATE_TOLERANCE and
+    // longer than DEGENERATE_TOLERANCE times its longer side. Otherwise
+    // is_valid returns false if the corners are the same, or
+    // ShapeError::DegenerateZeroArea if the rectangle is only a sliver.
+    pub fn rectangle( origin: Point, width: f64, height: f64 ) -> AngularShape {
+        let min: Point = Point { x: origin.x.min( origin.x + width ), y: origin.y.min( origin.y + height ) };
+        let max: Point = Point { x: origin.x.max( origin.x + width ), y: origin.y.max( origin.y + height ) };
+        return AngularShape { corners: vec![
+            min,
+            Point { x: max.x, y: min.y },
+            max,
+            Point { x: min.x, y: max.y },
+        ] };
+    }
+
+    // Create a square with its sides along the axes, like rectangle.
+    pub fn square( origin: Point, side: f64 ) -> AngularShape {
+        return AngularShape::rectangle( origin, side, side );
+    }
+
+    // Create a regular polygon with n corners evenly around the center, at
+    // the circumradius from it. The first corner is right of the center, and
+    // the corners go counterclockwise. Fails for less than 3 corners, or a
+    // radius that is not a positive number.
+    pub fn regular( n: usize, center: Point, circumradius: f64 ) -> Result<AngularShape, ShapeError> {
+        if n < 3 {
+            return Err( ShapeError::NotValidShape( format!("A regular polygon needs at least 3 corners, not {}.", n).into() ) );
+        }
+        if !circumradius.is_finite() || circumradius <= 0.0 {
+            return Err( ShapeError::NotValidShape( format!("The radius {} of a regular polygon must be a positive number.", circumradius).into() ) );
+        }
+
+        let corners: Vec<Point> = (0..n)
+            .map( |i| {
+                let angle: f64 = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+                center + Point { x: angle.cos(), y: angle.sin() } * circumradius
+            } )
+            .collect();
+        return Ok( AngularShape { corners } );
+    }
+
+    // Check if the shape is valid. A shape that is otherwise valid but has
+    // practically no area returns ShapeError::DegenerateZeroArea.
     pub fn is_valid( &self ) -> Result<bool,  ShapeError> {
+        return self.check_valid( false );
+    }
+
+    // Check if the shape is valid, like is_valid, but check if the lines
+    // intersect with the exact orientation of the points, so that corners very
+    // close to a line are not taken as touching it.
+    pub fn is_valid_exact( &self ) -> Result<bool,  ShapeError> {
+        return self.check_valid( true );
+    }
+
+    // Check if the shape is valid, with the exact or the floating point
+    // orientation in the intersection checks.
+    fn check_valid( &self, exact: bool ) -> Result<bool,  ShapeError> {
 
         // A shape with less than 3 corner points is not a valid shape.
         if self.corners.len() < 3 {
@@ -43,10 +172,11 @@ impl AngularShape {
             return Ok(false);
         }
 
-        // A shape is not valid if the points are the same.
+        // A shape is not valid if the points are the same, or so close that
+        // the line between them has practically no length.
         for i in 0..self.corners.len() {
             for j in 0..self.corners.len() {
-                if i != j && self.corners[i] == self.corners[j] {
+                if i != j && self.corners[i].distance( &self.corners[j] ) <= DEGENERATE_TOLERANCE {
                     debug!(" Shape is not valid, corner points {i} and {j} are the same {}!", self.corners[i]);
                     return Ok(false);
                 }
@@ -54,43 +184,41 @@ impl AngularShape {
         }
 
         // Check that the starting and ending points of the subsequent lines are the same.
-        let lines: Vec<Line> = self.get_lines();
-        for i in 0..lines.len() {
-            
-            // Skip the last line as it is automatically connected to the first line.
-            if i == lines.len() - 1 {
-                break;
-            }
+        // The last line is automatically connected to the first line.
+        for (i, (line, next)) in self.edges().zip( self.edges().skip( 1 ) ).enumerate() {
 
             // Shape is not valid if the lines do not connect.
-            if lines[i].end != lines[i + 1].start {
+            if line.end != next.start {
                 debug!(" Shape is not valid, lines do not connect! End point of a line {} ({}) is not the same as the start point of the next line ({}) in the shape!", 
-                    i, lines[i].end, lines[i + 1].start);
+                    i, line.end, next.start);
                 return Ok(false);
             }
         }
 
         // The lines should no intersect each other.
-        for i in 0..lines.len() {
+        for (i, line) in self.edges().enumerate() {
 
             // Move the points a little bit as the
             // start and end point should be the same, and thus the lines
-            // actually intersect every time.
-            let adjusted_line: Line = lines[i]
-                .shorten( 0.000001 )?
+            // actually intersect every time. A short line is shortened by a
+            // part of its length, so that it is not shortened to nothing.
+            let shortening: f64 = ( 0.000001_f64 ).min( line.length() / 4.0 );
+            let adjusted_line: Line = line
+                .shorten( shortening )?
                 .flip()
-                .shorten( 0.000001)?
+                .shorten( shortening )?
                 .flip();
 
-            // Move the start point and end point a little bit.
-
             // Compare this adjusted line to the other lines.
-            for j in 0..lines.len() {
+            for (j, other) in self.edges().enumerate() {
                 if i != j {
 
-
                     // Check if the lines intersect.
-                    if adjusted_line.intersects( &lines[j] ) {
+                    let intersects: bool = match exact {
+                        true => adjusted_line.intersects_exact( &other ),
+                        false => adjusted_line.intersects( &other ),
+                    };
+                    if intersects {
                         debug!(" Shape is not valid, lines intersect! Line {i} intersects with line {j} in the shape!");
                         return Ok(false);
                     }
@@ -98,13 +226,28 @@ impl AngularShape {
             }
         }
 
+        // A shape whose corners are practically on one line has no area,
+        // even if its lines do not touch each other. The area is compared to
+        // the square of the size of the shape, so that the size of the shape
+        // does not matter, like in Triangle::is_degenerate.
+        let (min, max) = self.bounding_box().unwrap();
+        let extent: f64 = (max.x - min.x).max( max.y - min.y );
+        if algorithms::signed_area( &self.corners ).abs() <= DEGENERATE_TOLERANCE * extent * extent {
+            debug!(" Shape is not valid, it has no area!");
+            return Err( ShapeError::DegenerateZeroArea );
+        }
+
         // Area should be valid.
         return Ok(true);
         
     }
 
     // Get the lines that make up the shape.
+    // A shape with less than 2 corners has no lines.
     pub fn get_lines( &self ) -> Vec<Line> {
+        if self.corners.len() < 2 {
+            return Vec::new();
+        }
 
         // Create lines between the points.
         // The last point should be connected to the first point.
@@ -112,58 +255,551 @@ impl AngularShape {
         for i in 0..self.corners.len() {
             let start = self.corners[i];
             let end = self.corners[ (i + 1) % self.corners.len() ];
-            lines.push( Line { start, end } );
+            lines.push( Line::new( start, end ) );
         }
         
         // Return the lines.
         return lines;
     }
-}
 
-// Implement the AreaCalculatable trait for the AngularShape struct.
-impl AreaCalculatable for AngularShape {
+    // Iterate over the lines that make up the shape, like get_lines, without
+    // collecting them into a vector. The last point is connected to the first
+    // point, and a shape with less than 2 corners has no lines.
+    pub fn edges( &self ) -> impl Iterator<Item = Line> + '_ {
+        let count: usize = match self.corners.len() {
+            0 | 1 => 0,
+            n => n,
+        };
+        return (0..count).map( move |i| Line::new( self.corners[i], self.corners[ (i + 1) % count ] ) );
+    }
+
+    // Get the axis-aligned bounding box of the shape as (min corner, max corner).
+    // Returns None if the shape has no corners.
+    pub fn bounding_box( &self ) -> Option<(Point, Point)> {
+
+        // An empty shape has no bounding box.
+        if self.corners.is_empty() {
+            return None;
+        }
+
+        // Find the minimum and maximum coordinates.
+        let mut min: Point = self.corners[0];
+        let mut max: Point = self.corners[0];
+        for corner in &self.corners {
+            min.x = min.x.min( corner.x );
+            min.y = min.y.min( corner.y );
+            max.x = max.x.max( corner.x );
+            max.y = max.y.max( corner.y );
+        }
+
+        return Some( (min, max) );
+    }
+
+    // Get the perimeter of the shape, or -1 if the shape is not valid.
+    pub fn perimeter( &self ) -> f64 {
+
+        // Check if the shape is valid. Return -1 if the shape is not valid.
+        if !self.is_valid().unwrap_or( false ) {
+            return -1.0;
+        }
+
+        // Sum the lengths of the lines.
+        return self.edges().map( |line| line.length() ).sum();
+    }
+
+    // Get the centroid of the shape, or None if the shape is not valid.
+    // https://en.wikipedia.org/wiki/Centroid#Of_a_polygon
+    pub fn centroid( &self ) -> Option<Point> {
+        return self.area_properties().map( |properties| properties.centroid );
+    }
+
+    // Get the signed area of the shape, positive if the corners are
+    // counterclockwise and negative if they are clockwise.
+    // Returns None if the shape is not valid.
+    pub fn signed_area( &self ) -> Option<f64> {
+        return self.area_properties().map( |properties| properties.signed_area );
+    }
+
+    // Get the area, the signed area and the centroid of the shape in one
+    // pass over the lines. Returns None if the shape is not valid.
+    pub fn area_properties( &self ) -> Option<AreaProperties> {
+
+        // Check if the shape is valid.
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+
+        // Sum the cross products of the lines, weighted by the line end points.
+        let mut signed_area: f64 = 0.0;
+        let mut cx: f64 = 0.0;
+        let mut cy: f64 = 0.0;
+        for line in self.edges() {
+            let cross: f64 = line.start.x * line.end.y - line.end.x * line.start.y;
+            signed_area += cross / 2.0;
+            cx += (line.start.x + line.end.x) * cross;
+            cy += (line.start.y + line.end.y) * cross;
+        }
+
+        return Some( AreaProperties {
+            area: signed_area.abs(),
+            signed_area,
+            centroid: Point { x: cx / (6.0 * signed_area), y: cy / (6.0 * signed_area) },
+        } );
+    }
+
+    // Get the area like area(), and check it against the area from the
+    // trapezoid formula. A difference is logged as a warning, and panics in
+    // debug builds. Meant for debugging area discrepancies and for tests.
+    pub fn area_checked( &self ) -> f64 {
+        let area: f64 = self.area();
+        if area < 0.0 {
+            return area;
+        }
+        let trapezoid: f64 = algorithms::trapezoid_area( &self.corners ).abs();
+        let agree: bool = (area - trapezoid).abs() <= AREA_CHECK_TOLERANCE * area.max( 1.0 );
+        if !agree {
+            warn!("The shoelace area {} and the trapezoid area {} of the shape differ!", area, trapezoid);
+        }
+        debug_assert!( agree, "The shoelace area {} and the trapezoid area {} differ.", area, trapezoid );
+        return area;
+    }
+
+    // Get the area of the shape with the corners measured in the unit, or None
+    // if the shape is not valid. Use Area::to to get it in another unit.
+    pub fn area_in( &self, unit: Unit ) -> Option<Area> {
+        return self.area_properties().map( |properties| Area::new( properties.area, unit ) );
+    }
+
+    // Get the names of the columns written by to_csv_row.
+    pub fn csv_header() -> &'static str {
+        "corner_count,area,perimeter,centroid_x,centroid_y,width,height"
+    }
+
+    // Get the statistics of the shape as a CSV row, see csv_header for the columns.
+    // The centroid is left empty for invalid shapes.
+    pub fn to_csv_row( &self ) -> String {
+
+        // Centroid is only known for valid shapes.
+        let (centroid_x, centroid_y) = match self.centroid() {
+            Some( centroid ) => ( centroid.x.to_string(), centroid.y.to_string() ),
+            None => ( String::new(), String::new() ),
+        };
+
+        // Width and height of the bounding box, zero for an empty shape.
+        let (width, height) = match self.bounding_box() {
+            Some( (min, max) ) => ( max.x - min.x, max.y - min.y ),
+            None => ( 0.0, 0.0 ),
+        };
+
+        return format!( "{},{},{},{},{},{},{}",
+            self.corners.len(), self.area(), self.perimeter(), centroid_x, centroid_y, width, height );
+    }
+
+    // Parse a CSV row written by to_csv_row back to shape statistics.
+    pub fn from_csv_row( row: &str ) -> Result<ShapeStats, ParseError> {
+
+        // Check the column count.
+        let values: Vec<&str> = row.trim().split(',').map( |value| value.trim() ).collect();
+        if values.len() != CSV_COLUMNS.len() {
+            return Err( ParseError::WrongColumnCount { expected: CSV_COLUMNS.len(), found: values.len() } );
+        }
+
+        // Parse a number from the given column.
+        let number = |i: usize| -> Result<f64, ParseError> {
+            values[i].parse::<f64>().map_err( |_| ParseError::InvalidValue {
+                column: CSV_COLUMNS[i],
+                value: values[i].to_string()
+            } )
+        };
+
+        // The corner count is an integer.
+        let corner_count: usize = values[0].parse().map_err( |_| ParseError::InvalidValue {
+            column: CSV_COLUMNS[0],
+            value: values[0].to_string()
+        } )?;
+
+        // The centroid is empty for invalid shapes.
+        let centroid: Option<Point> = if values[3].is_empty() && values[4].is_empty() {
+            None
+        } else {
+            Some( Point { x: number(3)?, y: number(4)? } )
+        };
+
+        return Ok( ShapeStats {
+            corner_count,
+            area: number(1)?,
+            perimeter: number(2)?,
+            centroid,
+            width: number(5)?,
+            height: number(6)?,
+        } );
+    }
+
+    // Split the shape in two along the diagonal from corner i to corner j.
+    // The corners must not be the same or next to each other, and the diagonal must
+    // run inside the shape. Both new shapes contain the corners i and j.
+    pub fn split_at_corners( &self, i: usize, j: usize ) -> Result<(AngularShape, AngularShape), ShapeError> {
+
+        // Check the indices.
+        let count: usize = self.corners.len();
+        for index in [i, j] {
+            if index >= count {
+                return Err( ShapeError::IndexOutOfBounds { index, corner_count: count } );
+            }
+        }
+
+        // Only a valid shape can be split.
+        if !self.is_valid()? {
+            return Err( ShapeError::NotValidShape( "Only a valid shape can be split.".into() ) );
+        }
+
+        // Same or adjacent corners do not make a diagonal.
+        let (first, last) = ( i.min(j), i.max(j) );
+        if last - first < 2 || (first == 0 && last == count - 1) {
+            return Err( ShapeError::InvalidDiagonal { from: i, to: j } );
+        }
+
+        // One shape goes from the first corner to the last, the other one wraps around.
+        let inner = AngularShape { corners: self.corners[first..=last].to_vec() };
+        let outer = AngularShape {
+            corners: self.corners[last..].iter().chain( self.corners[..=first].iter() ).copied().collect()
+        };
+
+        // A diagonal outside the shape gives shapes that are not valid,
+        // or shapes that overlap so their areas do not add up.
+        let area: f64 = self.area();
+        if !inner.is_valid()? || !outer.is_valid()?
+            || (inner.area() + outer.area() - area).abs() > area * 0.000001 {
+            return Err( ShapeError::InvalidDiagonal { from: i, to: j } );
+        }
+
+        return Ok( (inner, outer) );
+    }
+
+    // Check if a point is inside the shape, using the nonzero rule: the point
+    // is inside if the outline goes around it, see winding_number. Parts of a
+    // self-overlapping shape the outline goes around twice are inside too.
+    // Points exactly on an edge may be reported either way.
+    pub fn contains_point( &self, p: &Point ) -> bool {
+        return self.winding_number( p ) != 0;
+    }
+
+    // Check if the other shape is inside this shape: all of its corners are
+    // inside, and none of its edges cross or touch the edges of this shape.
+    // A shape that shares an edge or a corner with this shape is not inside.
+    // Returns false if either shape is not valid.
+    pub fn contains_shape( &self, other: &AngularShape ) -> bool {
+        if !self.is_valid().unwrap_or( false ) || !other.is_valid().unwrap_or( false ) {
+            return false;
+        }
+        if !other.corners.iter().all( |corner| self.contains_point( corner ) ) {
+            return false;
+        }
+        return !self.edges().any( |edge| other.edges().any( |other_edge| edge.intersects( &other_edge ) ) );
+    }
+
+    // Get the winding number of the point with respect to the shape: how many
+    // times the outline goes around the point counterclockwise, negative for
+    // clockwise. The shape does not have to be valid, so self-overlapping
+    // shapes can be used with the nonzero rule. See algorithms::winding_number.
+    pub fn winding_number( &self, p: &Point ) -> i32 {
+        return algorithms::winding_number( p, &self.corners );
+    }
+
+    // Get the smallest circle that encloses all corners of the shape,
+    // or None if the shape is not valid.
+    pub fn bounding_circle( &self ) -> Option<Circle> {
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+        return Circle::minimal_enclosing( &self.corners );
+    }
+
+    // Get the circle going through the corners of the bounding box of the shape,
+    // or None if the shape is not valid. This is faster to compute than
+    // bounding_circle, but the circle is usually larger.
+    pub fn bounding_box_circle( &self ) -> Option<Circle> {
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+        let (min, max) = self.bounding_box()?;
+        return Some( Circle {
+            center: (min + max) * 0.5,
+            radius: (max.x - min.x).hypot( max.y - min.y ) / 2.0,
+        } );
+    }
+
+    // Estimate the area of the shape by throwing random points into its bounding box
+    // and scaling the share of points inside the shape by the area of the box.
+    // The same seed always gives the same estimate. Returns -1 if the shape is not valid.
+    pub fn area_monte_carlo( &self, samples: usize, seed: u64 ) -> f64 {
 
-    // https://www.mathsisfun.com/geometry/area-irregular-polygons.html
-    // Returns the area of the shape, or -1 if the shape is not valid.
-    fn area(&self) -> f64 {
-        
         // Check if the shape is valid. Return -1 if the shape is not valid.
-        if !self.is_valid().unwrap() {
+        if !self.is_valid().unwrap_or( false ) {
             return -1.0;
         }
+        if samples == 0 {
+            return 0.0;
+        }
+
+        // Count the random points that hit the shape.
+        let (min, max) = self.bounding_box().unwrap();
+        let mut random: Random = Random::new( seed );
+        let mut hits: usize = 0;
+        for _ in 0..samples {
+            let p: Point = Point { x: random.range( min.x, max.x ), y: random.range( min.y, max.y ) };
+            if self.contains_point( &p ) {
+                hits += 1;
+            }
+        }
+
+        // Scale the hit ratio by the area of the bounding box.
+        let box_area: f64 = (max.x - min.x) * (max.y - min.y);
+        return box_area * hits as f64 / samples as f64;
+    }
+
+    // Get the area filled with the even-odd rule, where parts of the shape the
+    // outline goes around twice are left empty, like the middle of a pentagram.
+    // Unlike area, this also works for shapes whose lines cross each other.
+    // For a valid shape this is the same as the area.
+    pub fn filled_area_even_odd( &self ) -> f64 {
+        return algorithms::even_odd_area( &self.corners );
+    }
+
+    // Get the ratio of the perimeter of the shape to the perimeter of its
+    // bounding box. The ratio is 1 for a rectangle and grows the more jagged
+    // the boundary is. Returns None if the shape is not valid or the bounding
+    // box has no size.
+    pub fn perimeter_ratio( &self ) -> Option<f64> {
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+        let (min, max) = self.bounding_box()?;
+        let box_perimeter: f64 = 2.0 * ((max.x - min.x) + (max.y - min.y));
+        if box_perimeter <= 0.0 {
+            return None;
+        }
+        return Some( self.perimeter() / box_perimeter );
+    }
+
+    // Check if the shape is approximately a rectangle: it has 4 corners, every
+    // interior angle is within the tolerance of 90 degrees, and the opposite
+    // sides have the same length within 1 percent.
+    pub fn is_rectangular( &self, angle_tolerance_degrees: f64 ) -> bool {
+        if self.corners.len() != 4 || !self.is_valid().unwrap_or( false ) {
+            return false;
+        }
+
+        // Check the angle between the sides meeting at each corner.
+        let count: usize = self.corners.len();
+        for i in 0..count {
+            let to_previous: Point = self.corners[ (i + count - 1) % count ] - self.corners[i];
+            let to_next: Point = self.corners[ (i + 1) % count ] - self.corners[i];
+            let dot: f64 = to_previous.x * to_next.x + to_previous.y * to_next.y;
+            let lengths: f64 = to_previous.x.hypot( to_previous.y ) * to_next.x.hypot( to_next.y );
+            let angle: f64 = (dot / lengths).clamp( -1.0, 1.0 ).acos().to_degrees();
+            if (angle - 90.0).abs() > angle_tolerance_degrees {
+                return false;
+            }
+        }
+
+        // Check that the opposite sides have the same length.
+        let lengths: Vec<f64> = self.get_lines().iter().map( Line::length ).collect();
+        for (a, b) in [ (lengths[0], lengths[2]), (lengths[1], lengths[3]) ] {
+            if (a - b).abs() > a.max( b ) * RECTANGLE_SIDE_TOLERANCE {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    // Return a copy of the shape scaled about its centroid so that its area is
+    // the target area. Fails if the shape is not valid, has no area, or the
+    // target area is negative or not finite.
+    pub fn scale_to_area( &self, target_area: f64 ) -> Result<AngularShape, ShapeError> {
+
+        // Only a valid shape with an area can be scaled.
+        if !self.is_valid()? {
+            return Err( ShapeError::NotValidShape( "Only a valid shape can be scaled.".into() ) );
+        }
+        let area: f64 = self.area();
+        let Some( centroid ) = self.centroid().filter( |_| area > 0.0 ) else {
+            return Err( ShapeError::NotValidShape( "A shape without an area can not be scaled.".into() ) );
+        };
+        if !target_area.is_finite() || target_area < 0.0 {
+            return Err( ShapeError::NotValidShape( format!("Can not scale a shape to area {}.", target_area).into() ) );
+        }
+
+        // Area grows with the square of the scale.
+        let scale: f64 = (target_area / area).sqrt();
+        let corners: Vec<Point> = self.corners
+            .iter()
+            .map( |corner| centroid + (*corner - centroid) * scale )
+            .collect();
+
+        return Ok( AngularShape { corners } );
+    }
+
+    // Return a copy of the shape moved so that the minimum corner of its
+    // bounding box is at the origin. An empty shape is returned as is.
+    pub fn translate_to_origin( &self ) -> AngularShape {
+
+        // Nothing to move if there is no bounding box.
+        let Some( (min, _) ) = self.bounding_box() else {
+            return AngularShape { corners: Vec::new() };
+        };
+
+        // Move every corner by the offset of the bounding box minimum.
+        let corners: Vec<Point> = self.corners
+            .iter()
+            .map( |corner| *corner - min )
+            .collect();
+
+        return AngularShape { corners };
+    }
+
+    // Return a copy of the shape rotated so that its longest edge is horizontal.
+    // The shape is rotated around the start point of the longest edge.
+    pub fn align_to_axis( &self ) -> AngularShape {
+
+        // Find the longest edge. The first one wins if there are many of the same length.
+        let mut longest: Option<Line> = None;
+        for line in self.get_lines() {
+            if longest.as_ref().is_none_or( |l| line.length() > l.length() ) {
+                longest = Some( line );
+            }
+        }
+
+        // Nothing to align if there are no edges.
+        let Some( longest ) = longest else {
+            return AngularShape { corners: Vec::new() };
+        };
+
+        // Rotate every corner by the negative angle of the longest edge.
+        let angle: f64 = longest.angle();
+        let corners: Vec<Point> = self.corners
+            .iter()
+            .map( |corner| corner.rotate( -angle, &longest.start ) )
+            .collect();
+
+        return AngularShape { corners };
+    }
+
+    // Return a copy of the shape without corners that are the same as the
+    // corner before them, like the repeated points digitizing often leaves.
+    // A last corner that is the same as the first one is removed too. Corners
+    // repeated elsewhere in the shape are kept, so the shape is still invalid.
+    pub fn dedup_consecutive( &self ) -> AngularShape {
+        let same = |a: &Point, b: &Point| a.distance( b ) <= DEGENERATE_TOLERANCE;
+
+        let mut corners: Vec<Point> = Vec::with_capacity( self.corners.len() );
+        for corner in &self.corners {
+            if corners.last().is_none_or( |previous| !same( previous, corner ) ) {
+                corners.push( *corner );
+            }
+        }
+
+        // The shape is closed, so the last corner also follows the first one.
+        while corners.len() > 1 && same( &corners[ corners.len() - 1 ], &corners[0] ) {
+            corners.pop();
+        }
+
+        return AngularShape { corners };
+    }
 
-        // Calcluate areas of between the lines and the x-axis
-        // and then sum then to get the area of the shape.
-        let mut areas: Vec<f64> = Vec::new();
+    // Return a copy of the shape with every edge moved inward by the distance,
+    // like padding inside a room. The new corners are where the moved edges
+    // meet. Returns None if the shape is not valid, the distance is negative
+    // or not finite, or the shape collapses: an edge turns around, the edges
+    // cross, or no area is left.
+    pub fn erode( &self, distance: f64 ) -> Option<AngularShape> {
 
-        // Get the lines that make up the shape.
-        let lines = self.get_lines();
+        // Only a valid shape can be eroded.
+        if !self.is_valid().unwrap_or( false ) || !distance.is_finite() || distance < 0.0 {
+            return None;
+        }
 
-        // Calculate the area between the lines and the x-axis.
-        for line in lines {
+        // The inside is on the left of the edges of a counterclockwise shape,
+        // and on the right of a clockwise one.
+        let side: f64 = algorithms::signed_area( &self.corners ).signum();
+        let moved: Vec<Line> = self.edges()
+            .map( |edge| {
+                let direction: Point = edge.end - edge.start;
+                let normal: Point = Point { x: -direction.y, y: direction.x } * ( side * distance / edge.length() );
+                Line::new( edge.start + normal, edge.end + normal )
+            } )
+            .collect();
 
-            // Calculate the average height of the line.
-            let height: f64 = (line.start.y + line.end.y) / 2.0;
+        // Each corner is where the moved edges before and after it meet.
+        let count: usize = moved.len();
+        let mut corners: Vec<Point> = Vec::with_capacity( count );
+        for i in 0..count {
+            let before: &Line = &moved[ ( i + count - 1 ) % count ];
+            let after: &Line = &moved[i];
+            let r: Point = before.end - before.start;
+            let s: Point = after.end - after.start;
+            let cross: f64 = r.x * s.y - r.y * s.x;
 
-            // Calculate the line lenght in x-direction.
-            let dx: f64 = line.end.x - line.start.x;
+            // Edges going the same way meet at the start of the moved edge.
+            if cross.abs() <= DEGENERATE_TOLERANCE * before.length() * after.length() {
+                corners.push( after.start );
+                continue;
+            }
+            let q: Point = after.start - before.start;
+            let t: f64 = ( q.x * s.y - q.y * s.x ) / cross;
+            corners.push( before.start + r * t );
+        }
+        let eroded: AngularShape = AngularShape { corners };
 
-            // Calculate the area of the trapezoid.
-            let area_trapezoid = height * dx;
-            areas.push( area_trapezoid);
+        // The shape collapsed if an edge turned around or the edges now cross.
+        let turned: bool = self.edges().zip( eroded.edges() ).any( |(old, new)| {
+            let a: Point = old.end - old.start;
+            let b: Point = new.end - new.start;
+            a.x * b.x + a.y * b.y <= 0.0
+        } );
+        if turned || !eroded.is_valid().unwrap_or( false ) {
+            return None;
         }
+        return Some( eroded );
+    }
+}
 
-        // Sum the areas of the trapezoids to get the area of the shape.
-        let result: f64 = areas.iter().sum();
+// Implement the AreaCalculatable trait for the AngularShape struct.
+impl AreaCalculatable for AngularShape {
+
+    // https://www.mathsisfun.com/geometry/area-irregular-polygons.html
+    // Returns the area of the shape, or -1 if the shape is not valid.
+    // The area is computed with the signed area in area_properties.
+    fn area(&self) -> f64 {
 
-        // Return the the absolute value of the result. The sign of the result
-        // depend on the direction of which the lines are drawn.
-        return result.abs()
+        // The area of a shape that is not valid is -1.
+        return match self.area_properties() {
+            Some( properties ) => properties.area,
+            None => -1.0,
+        };
     }
 
 }
 
 
+// Implement the BoundingBox trait for the AngularShape struct.
+impl BoundingBox for AngularShape {
+
+    // Returns the bounding box of the corners, or None if there are no corners.
+    fn bounding_box(&self) -> Option<(Point, Point)> {
+        AngularShape::bounding_box( self )
+    }
+}
+
+// Implement the PerimeterCalculatable trait for the AngularShape struct.
+impl PerimeterCalculatable for AngularShape {
+
+    // Returns the sum of the lengths of the lines, or -1 if the shape is not valid.
+    fn perimeter(&self) -> f64 {
+        AngularShape::perimeter( self )
+    }
+}
+
 
 // Unit tests for AngularShape.
 #[cfg(test)]
@@ -193,6 +829,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_edges() {
+        let shapes: Vec<AngularShape> = vec![
+            AngularShape { corners: vec![] },
+            AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 } ] },
+            AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 } ] },
+            AngularShape { corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 4.0, y: 0.0 },
+                Point { x: 4.0, y: 4.0 },
+                Point { x: 2.0, y: 1.0 },
+                Point { x: 0.0, y: 4.0 },
+            ] },
+        ];
+
+        // The iterator yields the same lines as get_lines.
+        for shape in shapes {
+            let edges: Vec<Line> = shape.edges().collect();
+            let lines: Vec<Line> = shape.get_lines();
+            assert!( edges.len() == lines.len() );
+            for (edge, line) in edges.iter().zip( lines.iter() ) {
+                assert!( edge.start == line.start && edge.end == line.end );
+            }
+        }
+    }
+
     #[test]
     fn test_get_lines_zero() {
         let square: AngularShape = AngularShape {
@@ -210,9 +872,7 @@ mod tests {
             ]
         };
         let lines: Vec<Line> = square.get_lines();
-        assert!( lines.len() == 1 );
-        assert!( lines[0].start == square.corners[0] );
-        assert!( lines[0].end == square.corners[0] );
+        assert!( lines.is_empty() );
     }
 
     #[test]
@@ -304,6 +964,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_is_not_valid_collinear() {
+
+        // Three points on one line are not a shape.
+        let line: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 3.0, y: 3.0 },
+            ]
+        };
+        assert!( !line.is_valid().unwrap_or( false ) );
+        assert!( line.area() == -1.0 );
+
+        // A sliver whose lines do not overlap has no area either.
+        let sliver: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 0.000000000001 },
+                Point { x: 2.0, y: 0.0 },
+            ]
+        };
+        assert!( matches!( sliver.is_valid(), Err( ShapeError::DegenerateZeroArea ) ) );
+        assert!( matches!( sliver.is_valid_exact(), Err( ShapeError::DegenerateZeroArea ) ) );
+        assert!( sliver.area() == -1.0 );
+        assert!( sliver.perimeter() == -1.0 );
+    }
+
+    #[test]
+    fn test_is_valid_small_square() {
+
+        // A small square has a small area, but it is not a sliver.
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.00001, y: 0.0 },
+                Point { x: 0.00001, y: 0.00001 },
+                Point { x: 0.0, y: 0.00001 },
+            ]
+        };
+        assert!( square.is_valid().unwrap() );
+        assert!( square.is_valid_exact().unwrap() );
+        assert!( (square.area() - 0.0000000001).abs() < 0.000000000000001 );
+
+        // A large sliver has an area above the tolerance, but it is still a sliver.
+        let sliver: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1000000.0, y: 0.00001 },
+                Point { x: 2000000.0, y: 0.0 },
+            ]
+        };
+        assert!( matches!( sliver.is_valid(), Err( ShapeError::DegenerateZeroArea ) ) );
+    }
+
     #[test]
     fn test_triangle_area() -> Result<(), Box<dyn Error>> {
         let triangle: AngularShape = AngularShape {
@@ -350,6 +1065,31 @@ mod tests {
         assert!( (pentagon.area() - 3.0).abs() < 0.000001 );
     }
 
+    #[test]
+    fn test_area_checked() {
+        let shapes: Vec<AngularShape> = vec![
+            AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }, Point { x: 2.0, y: 0.0 } ] },
+            AngularShape { corners: vec![
+                Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 } ] },
+            AngularShape { corners: vec![
+                Point { x: 0.0, y: 0.0 }, Point { x: -1.0, y: 1.0 }, Point { x: 0.5, y: 2.0 },
+                Point { x: 1.5, y: 1.0 }, Point { x: 1.0, y: 0.0 } ] },
+            AngularShape { corners: vec![
+                Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 0.0 }, Point { x: 4.0, y: 4.0 },
+                Point { x: 2.0, y: 1.0 }, Point { x: 0.0, y: 4.0 } ] },
+        ];
+
+        // The shoelace and trapezoid formulas agree, and area_checked is the area.
+        for shape in shapes {
+            let area: f64 = shape.area();
+            assert!( (algorithms::trapezoid_area( &shape.corners ).abs() - area).abs() < 0.000001 );
+            assert!( shape.area_checked() == area );
+        }
+
+        // A shape that is not valid has no area to check.
+        assert!( AngularShape { corners: vec![] }.area_checked() == -1.0 );
+    }
+
     #[test]
     fn test_not_valid_area_() {
         let square: AngularShape = AngularShape {
@@ -363,5 +1103,834 @@ mod tests {
         assert!( square.area() == -1.0 );
     }
 
+    #[test]
+    fn test_area_in() {
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 50.0, y: 0.0 },
+                Point { x: 50.0, y: 50.0 },
+                Point { x: 0.0, y: 50.0 },
+            ]
+        };
+
+        // A 50 cm square is 2500 cm² and 0.25 m².
+        let area: Area = square.area_in( Unit::Centimeter ).unwrap();
+        assert!( area == Area::new( 2500.0, Unit::Centimeter ) );
+        assert!( (area.to( Unit::Meter ).value - 0.25).abs() < 0.000000001 );
+        assert!( (area.to( Unit::Meter ).to( Unit::Centimeter ).value - 2500.0).abs() < 0.000001 );
 
-}
\ No newline at end of file
+        // A shape that is not valid has no area.
+        assert!( AngularShape { corners: vec![] }.area_in( Unit::Meter ).is_none() );
+    }
+
+    #[test]
+    fn test_translate_to_origin() {
+        let shape: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: -3.0, y: -2.0 },
+                Point { x: -3.0, y: 1.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 1.0, y: -2.0 },
+            ]
+        };
+        let moved: AngularShape = shape.translate_to_origin();
+        let (min, max) = moved.bounding_box().unwrap();
+        assert!( min == Point { x: 0.0, y: 0.0 } );
+        assert!( max == Point { x: 4.0, y: 3.0 } );
+        assert!( (moved.area() - shape.area()).abs() < 0.000001 );
+
+        // Moving again should not change anything.
+        let moved_again: AngularShape = moved.translate_to_origin();
+        assert!( moved_again.corners == moved.corners );
+    }
+
+    #[test]
+    fn test_align_to_axis() {
+
+        // Square tilted by 45 degrees, all edges are the same length.
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 2.0 },
+                Point { x: -1.0, y: 1.0 },
+            ]
+        };
+        let aligned: AngularShape = square.align_to_axis();
+
+        // The first edge was the longest and should now be horizontal.
+        let lines: Vec<Line> = aligned.get_lines();
+        assert!( lines[0].angle().abs() < 0.000001 );
+        assert!( (aligned.area() - square.area()).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_align_to_axis_longest_edge() {
+
+        // Rectangle tilted by 30 degrees, the longest edge is the second one.
+        let rectangle: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.5, y: 0.866025403784 },
+                Point { x: -1.232050807569, y: 1.866025403784 },
+                Point { x: -1.732050807569, y: 1.0 },
+            ]
+        };
+        let aligned: AngularShape = rectangle.align_to_axis();
+        let lines: Vec<Line> = aligned.get_lines();
+        assert!( lines[1].angle().sin().abs() < 0.000001 );
+        assert!( (aligned.area() - 2.0).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_csv_row_round_trip() -> Result<(), Box<dyn Error>> {
+        let shape: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 4.0, y: 0.0 },
+                Point { x: 4.0, y: 3.0 },
+                Point { x: 0.0, y: 3.0 },
+            ]
+        };
+        let row: String = shape.to_csv_row();
+        assert!( row == "4,12,14,2,1.5,4,3" );
+        assert!( AngularShape::csv_header().split(',').count() == row.split(',').count() );
+
+        let stats: ShapeStats = AngularShape::from_csv_row( &row )?;
+        assert!( stats.corner_count == 4 );
+        assert!( stats.area == 12.0 );
+        assert!( stats.perimeter == 14.0 );
+        assert!( stats.centroid == Some( Point { x: 2.0, y: 1.5 } ) );
+        assert!( stats.width == 4.0 );
+        assert!( stats.height == 3.0 );
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_row_round_trip_invalid_shape() -> Result<(), Box<dyn Error>> {
+        let line: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 2.0 }
+            ]
+        };
+        let stats: ShapeStats = AngularShape::from_csv_row( &line.to_csv_row() )?;
+        assert!( stats.corner_count == 2 );
+        assert!( stats.area == -1.0 );
+        assert!( stats.centroid.is_none() );
+        assert!( stats.height == 2.0 );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_csv_row_errors() {
+        assert!( matches!( AngularShape::from_csv_row( "4,12,14" ),
+            Err( ParseError::WrongColumnCount { expected: 7, found: 3 } ) ) );
+        assert!( matches!( AngularShape::from_csv_row( "4,twelve,14,2,1.5,4,3" ),
+            Err( ParseError::InvalidValue { column: "area", .. } ) ) );
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let square: AngularShape = AngularShape::square( Point { x: 1.0, y: 2.0 }, 3.0 );
+        assert_eq!( square.bounding_box(), Some( (Point { x: 1.0, y: 2.0 }, Point { x: 4.0, y: 5.0 }) ) );
+
+        let triangle: AngularShape = AngularShape { corners: vec![
+            Point { x: -2.0, y: 1.0 }, Point { x: 3.0, y: -4.0 }, Point { x: 0.5, y: 2.5 } ] };
+        assert_eq!( triangle.bounding_box(), Some( (Point { x: -2.0, y: -4.0 }, Point { x: 3.0, y: 2.5 }) ) );
+
+        // A shape in the negative quadrant has a negative max corner.
+        let negative: AngularShape = AngularShape { corners: vec![
+            Point { x: -5.0, y: -1.0 }, Point { x: -1.0, y: -1.5 }, Point { x: -2.0, y: -6.0 }, Point { x: -4.5, y: -3.0 } ] };
+        assert_eq!( negative.bounding_box(), Some( (Point { x: -5.0, y: -6.0 }, Point { x: -1.0, y: -1.0 }) ) );
+
+        assert_eq!( AngularShape { corners: Vec::new() }.bounding_box(), None );
+    }
+
+    #[test]
+    fn test_bounding_box_trait() {
+        let triangle: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: -1.0, y: 0.5 },
+                Point { x: 3.0, y: -2.0 },
+                Point { x: 0.0, y: 4.0 }
+            ]
+        };
+        let shape: &dyn BoundingBox = &triangle;
+        let (min, max) = shape.bounding_box().unwrap();
+        assert!( min == Point { x: -1.0, y: -2.0 } );
+        assert!( max == Point { x: 3.0, y: 4.0 } );
+    }
+
+    #[test]
+    fn test_perimeter_trait() {
+        let square: AngularShape = AngularShape::square( Point { x: 0.0, y: 0.0 }, 1.0 );
+        let triangle: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 3.0, y: 0.0 }, Point { x: 3.0, y: 4.0 } ] };
+        let line: AngularShape = AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 } ] };
+        let circle: Circle = Circle { center: Point { x: 0.0, y: 0.0 }, radius: 1.0 };
+
+        let shapes: [&dyn PerimeterCalculatable; 4] = [ &square, &triangle, &line, &circle ];
+        let perimeters: Vec<f64> = shapes.iter().map( |shape| shape.perimeter() ).collect();
+        assert_eq!( perimeters[..3], [ 4.0, 12.0, -1.0 ] );
+        assert!( (perimeters[3] - 2.0 * std::f64::consts::PI).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_split_at_corners() -> Result<(), Box<dyn Error>> {
+        let pentagon: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: -1.0, y: 1.0 },
+                Point { x: 0.5, y: 2.0 },
+                Point { x: 1.5, y: 1.0 },
+                Point { x: 1.0, y: 0.0 },
+            ]
+        };
+        let (first, second) = pentagon.split_at_corners( 0, 3 )?;
+        assert!( first.is_valid()? );
+        assert!( second.is_valid()? );
+        assert!( first.corners.len() == 4 );
+        assert!( second.corners.len() == 3 );
+        assert!( (first.area() + second.area() - pentagon.area()).abs() < 0.000001 );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_at_corners_invalid() {
+        let concave: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 2.0, y: 2.0 },
+                Point { x: 1.0, y: 0.5 },
+                Point { x: 0.0, y: 2.0 },
+            ]
+        };
+        assert!( matches!( concave.split_at_corners( 0, 5 ), Err( ShapeError::IndexOutOfBounds { index: 5, corner_count: 5 } ) ) );
+        assert!( matches!( concave.split_at_corners( 1, 2 ), Err( ShapeError::InvalidDiagonal { from: 1, to: 2 } ) ) );
+        assert!( matches!( concave.split_at_corners( 4, 0 ), Err( ShapeError::InvalidDiagonal { .. } ) ) );
+        assert!( matches!( concave.split_at_corners( 2, 2 ), Err( ShapeError::InvalidDiagonal { .. } ) ) );
+
+        // The diagonal from corner 2 to corner 4 runs outside the shape.
+        assert!( matches!( concave.split_at_corners( 2, 4 ), Err( ShapeError::InvalidDiagonal { .. } ) ) );
+        assert!( concave.split_at_corners( 1, 3 ).is_ok() );
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let concave: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 2.0, y: 2.0 },
+                Point { x: 1.0, y: 0.5 },
+                Point { x: 0.0, y: 2.0 },
+            ]
+        };
+        assert!( concave.contains_point( &Point { x: 1.0, y: 0.25 } ) );
+        assert!( concave.contains_point( &Point { x: 0.25, y: 1.5 } ) );
+        assert!( !concave.contains_point( &Point { x: 1.0, y: 1.5 } ) );
+        assert!( !concave.contains_point( &Point { x: 3.0, y: 1.0 } ) );
+        assert!( !concave.contains_point( &Point { x: -1.0, y: 1.0 } ) );
+    }
+
+    #[test]
+    fn test_area_monte_carlo() {
+        let pentagon: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: -1.0, y: 1.0 },
+                Point { x: 0.5, y: 2.0 },
+                Point { x: 1.5, y: 1.0 },
+                Point { x: 1.0, y: 0.0 },
+            ]
+        };
+        let estimate: f64 = pentagon.area_monte_carlo( 100_000, 42 );
+        assert!( (estimate - pentagon.area()).abs() < 0.05 );
+
+        // The same seed gives the same estimate.
+        assert!( estimate == pentagon.area_monte_carlo( 100_000, 42 ) );
+    }
+
+    #[test]
+    fn test_area_monte_carlo_not_valid() {
+        let line: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 2.0 }
+            ]
+        };
+        assert!( line.area_monte_carlo( 1000, 1 ) == -1.0 );
+    }
+
+    #[test]
+    fn test_bounding_circle() {
+        let pentagon: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: -1.0, y: 1.0 },
+                Point { x: 0.5, y: 2.0 },
+                Point { x: 1.5, y: 1.0 },
+                Point { x: 1.0, y: 0.0 },
+            ]
+        };
+        let circle: Circle = pentagon.bounding_circle().unwrap();
+        let box_circle: Circle = pentagon.bounding_box_circle().unwrap();
+
+        // All corners are inside both circles.
+        for corner in &pentagon.corners {
+            let center_line = Line { start: circle.center, end: *corner };
+            let box_center_line = Line { start: box_circle.center, end: *corner };
+            assert!( center_line.length() <= circle.radius + 0.000001 );
+            assert!( box_center_line.length() <= box_circle.radius + 0.000001 );
+        }
+        assert!( circle.radius <= box_circle.radius );
+        assert!( (box_circle.radius - 1.6007810593582).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_bounding_circle_not_valid() {
+        let line: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 2.0 }
+            ]
+        };
+        assert!( line.bounding_circle().is_none() );
+        assert!( line.bounding_box_circle().is_none() );
+    }
+
+    #[test]
+    fn test_translate_to_origin_empty() {
+        let shape: AngularShape = AngularShape {
+            corners: vec![]
+        };
+        assert!( shape.translate_to_origin().corners.is_empty() );
+        assert!( shape.bounding_box().is_none() );
+    }
+
+    #[test]
+    fn test_winding_number_matches_algorithm() {
+        let mut random: Random = Random::new( 216 );
+        for _ in 0..1000 {
+
+            // A star-shaped polygon from corners at increasing angles around the origin.
+            let corner_count: usize = 3 + (random.next_u64() % 8) as usize;
+            let mut angles: Vec<f64> = (0..corner_count).map( |_| random.range( 0.0, std::f64::consts::TAU ) ).collect();
+            angles.sort_by( f64::total_cmp );
+            let shape: AngularShape = AngularShape {
+                corners: angles.iter().map( |angle| {
+                    let radius: f64 = random.range( 1.0, 10.0 );
+                    Point { x: radius * angle.cos(), y: radius * angle.sin() }
+                }).collect()
+            };
+            let p: Point = Point { x: random.range( -10.0, 10.0 ), y: random.range( -10.0, 10.0 ) };
+
+            assert_eq!( shape.winding_number( &p ), algorithms::winding_number( &p, &shape.corners ) );
+        }
+    }
+
+    #[test]
+    fn test_winding_number() {
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 2.0, y: 2.0 },
+                Point { x: 0.0, y: 2.0 },
+            ]
+        };
+        assert_eq!( square.winding_number( &Point { x: 1.0, y: 1.0 } ), 1 );
+        assert_eq!( square.winding_number( &Point { x: 3.0, y: 1.0 } ), 0 );
+        assert!( square.contains_point( &Point { x: 1.0, y: 1.0 } ) );
+
+        // The outline of a pentagram goes around its middle twice.
+        let pentagram: AngularShape = AngularShape {
+            corners: [ 0, 2, 4, 1, 3 ].iter()
+                .map( |i| {
+                    let angle: f64 = std::f64::consts::FRAC_PI_2 + *i as f64 * 2.0 * std::f64::consts::PI / 5.0;
+                    Point { x: angle.cos(), y: angle.sin() }
+                })
+                .collect()
+        };
+        let middle: Point = Point { x: 0.0, y: 0.0 };
+        let point: Point = Point { x: 0.0, y: 0.9 };
+        assert_eq!( pentagram.winding_number( &middle ), 2 );
+        assert_eq!( pentagram.winding_number( &point ), 1 );
+        assert_eq!( pentagram.winding_number( &Point { x: 0.0, y: 1.5 } ), 0 );
+        assert!( pentagram.contains_point( &middle ) );
+    }
+
+    #[test]
+    fn test_winding_number_invalid() {
+        let line: AngularShape = AngularShape {
+            corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 } ]
+        };
+        assert_eq!( line.winding_number( &Point { x: 0.5, y: 0.5 } ), 0 );
+    }
+
+    #[test]
+    fn test_scale_to_area() {
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 3.0, y: 1.0 },
+                Point { x: 3.0, y: 3.0 },
+                Point { x: 1.0, y: 3.0 },
+            ]
+        };
+        let scaled: AngularShape = square.scale_to_area( 100.0 ).unwrap();
+        assert!( (scaled.area() - 100.0).abs() < 1e-9 );
+
+        // The side is 10 and the centroid stays in place.
+        for line in scaled.get_lines() {
+            assert!( (line.length() - 10.0).abs() < 1e-9 );
+        }
+        assert_eq!( scaled.centroid(), square.centroid() );
+        assert_eq!( scaled.corners[0], Point { x: -3.0, y: -3.0 } );
+    }
+
+    #[test]
+    fn test_scale_to_area_invalid() {
+        let line: AngularShape = AngularShape {
+            corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 } ]
+        };
+        assert!( line.scale_to_area( 10.0 ).is_err() );
+
+        let triangle: AngularShape = AngularShape {
+            corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 } ]
+        };
+        assert!( triangle.scale_to_area( -1.0 ).is_err() );
+        assert!( triangle.scale_to_area( f64::NAN ).is_err() );
+    }
+
+    #[test]
+    fn test_is_rectangular() {
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 2.0, y: 2.0 },
+                Point { x: 0.0, y: 2.0 },
+            ]
+        };
+        assert!( square.is_rectangular( 0.0 ) );
+
+        // A parallelogram with 60 and 120 degree angles.
+        let parallelogram: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 4.0, y: 0.0 },
+                Point { x: 5.0, y: 3.0_f64.sqrt() },
+                Point { x: 1.0, y: 3.0_f64.sqrt() },
+            ]
+        };
+        assert!( !parallelogram.is_rectangular( 1.0 ) );
+
+        let triangle: AngularShape = AngularShape {
+            corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 } ]
+        };
+        assert!( !triangle.is_rectangular( 45.0 ) );
+    }
+
+    #[test]
+    fn test_is_rectangular_tolerance() {
+
+        // A room outline leaning by half a degree has 89.5 and 90.5 degree corners.
+        let lean: f64 = 5.0 * 0.5_f64.to_radians().tan();
+        let room: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 10.0, y: 0.0 },
+                Point { x: 10.0 + lean, y: 5.0 },
+                Point { x: lean, y: 5.0 },
+            ]
+        };
+        assert!( room.is_rectangular( 1.0 ) );
+        assert!( !room.is_rectangular( 0.25 ) );
+
+        // Right angles alone are not enough if the opposite sides differ.
+        let trapezoid: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 10.0, y: 0.0 },
+                Point { x: 10.0, y: 5.0 },
+                Point { x: 0.0, y: 5.5 },
+            ]
+        };
+        assert!( !trapezoid.is_rectangular( 5.0 ) );
+    }
+
+    #[test]
+    fn test_centroid() {
+        let square: AngularShape = AngularShape::square( Point { x: 0.0, y: 0.0 }, 1.0 );
+        assert_eq!( square.centroid(), Some( Point { x: 0.5, y: 0.5 } ) );
+
+        // The centroid of a triangle is the average of its corners.
+        let triangle: AngularShape = AngularShape { corners: vec![
+            Point { x: 1.0, y: 1.0 }, Point { x: 4.0, y: 1.0 }, Point { x: 1.0, y: 5.0 } ] };
+        let centroid: Point = triangle.centroid().unwrap();
+        assert!( (centroid.x - 2.0).abs() < 0.000001 );
+        assert!( (centroid.y - 7.0 / 3.0).abs() < 0.000001 );
+
+        // The centroid of an L shape is not the average of its corners, which is (1, 1).
+        let l_shape: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 0.0 }, Point { x: 2.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 2.0 }, Point { x: 0.0, y: 2.0 } ] };
+        let centroid: Point = l_shape.centroid().unwrap();
+        assert!( (centroid.x - 5.0 / 6.0).abs() < 0.000001 );
+        assert!( (centroid.y - 5.0 / 6.0).abs() < 0.000001 );
+
+        // The clockwise shape has the same centroid.
+        let reversed: AngularShape = AngularShape { corners: l_shape.corners.iter().rev().copied().collect() };
+        assert!( reversed.centroid().unwrap().distance( &centroid ) < 0.000001 );
+
+        let line: AngularShape = AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 } ] };
+        assert_eq!( line.centroid(), None );
+    }
+
+    #[test]
+    fn test_perimeter() {
+        let triangle: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 3.0, y: 0.0 }, Point { x: 0.0, y: 4.0 } ] };
+        assert!( (triangle.perimeter() - 12.0).abs() < 0.000001 );
+
+        let square: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 2.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 } ] };
+        assert!( (square.perimeter() - 8.0).abs() < 0.000001 );
+
+        // A house: 4 + 3 + sqrt(8) + sqrt(8) + 3.
+        let pentagon: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 0.0 }, Point { x: 4.0, y: 3.0 }, Point { x: 2.0, y: 5.0 }, Point { x: 0.0, y: 3.0 } ] };
+        assert!( (pentagon.perimeter() - (10.0 + 2.0 * 8.0_f64.sqrt())).abs() < 0.000001 );
+
+        // The perimeter of a shape that is not valid is -1, like the area.
+        let line: AngularShape = AngularShape { corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 } ] };
+        assert!( line.perimeter() == -1.0 );
+        let bow_tie: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 2.0 } ] };
+        assert!( bow_tie.perimeter() == -1.0 );
+    }
+
+    #[test]
+    fn test_perimeter_ratio() {
+        let square: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+            ]
+        };
+        assert_eq!( square.perimeter_ratio(), Some( 1.0 ) );
+
+        // A comb with ten teeth on top of a 10 x 1 bar.
+        let mut corners: Vec<Point> = vec![ Point { x: 0.0, y: 0.0 }, Point { x: 10.0, y: 0.0 } ];
+        for tooth in (0..10).rev() {
+            corners.push( Point { x: tooth as f64 + 1.0, y: 1.0 } );
+            corners.push( Point { x: tooth as f64 + 0.5, y: 6.0 } );
+        }
+        corners.push( Point { x: 0.0, y: 1.0 } );
+        let comb: AngularShape = AngularShape { corners };
+        assert!( comb.perimeter_ratio().unwrap() > 1.5 );
+
+        let line: AngularShape = AngularShape {
+            corners: vec![ Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 } ]
+        };
+        assert_eq!( line.perimeter_ratio(), None );
+    }
+
+    #[test]
+    fn test_filled_area_even_odd_pentagram() {
+
+        // A pentagram drawn through every other corner of a regular pentagon.
+        let corners: Vec<Point> = [ 0, 2, 4, 1, 3 ].iter()
+            .map( |i| {
+                let angle: f64 = std::f64::consts::FRAC_PI_2 + *i as f64 * 2.0 * std::f64::consts::PI / 5.0;
+                Point { x: angle.cos(), y: angle.sin() }
+            })
+            .collect();
+        let pentagram: AngularShape = AngularShape { corners };
+        assert!( !pentagram.is_valid().unwrap() );
+
+        // The pentagon in the middle is left empty, but the signed area counts it twice.
+        let inner_radius: f64 = 72.0_f64.to_radians().cos() / 36.0_f64.to_radians().cos();
+        let inner_area: f64 = 2.5 * inner_radius * inner_radius * 72.0_f64.to_radians().sin();
+        let lines: Vec<Line> = pentagram.get_lines();
+        let signed_area: f64 = lines.iter().map( |line| (line.start.y + line.end.y) / 2.0 * (line.end.x - line.start.x) ).sum();
+        let filled: f64 = pentagram.filled_area_even_odd();
+        assert!( ( filled - ( signed_area.abs() - 2.0 * inner_area ) ).abs() < 1e-9 );
+        assert!( ( filled - signed_area.abs() ).abs() > 0.5 );
+    }
+
+    #[test]
+    fn test_filled_area_even_odd_valid_shape() {
+        let shape: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 4.0, y: 0.0 },
+                Point { x: 4.0, y: 3.0 },
+                Point { x: 2.0, y: 1.0 },
+                Point { x: 0.0, y: 3.0 },
+            ]
+        };
+        assert!( ( shape.filled_area_even_odd() - shape.area() ).abs() < 1e-9 );
+    }
+
+    #[test]
+    fn test_dedup_consecutive() {
+        let shape: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 1.0, y: 0.0 },
+                Point { x: 1.0, y: 1e-12 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+                Point { x: 0.0, y: 1.0 },
+            ]
+        };
+        assert!( !shape.is_valid().unwrap() );
+
+        let deduped: AngularShape = shape.dedup_consecutive();
+        assert_eq!( deduped.corners, vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 0.0, y: 1.0 },
+        ] );
+        assert!( deduped.is_valid().unwrap() );
+    }
+
+    #[test]
+    fn test_dedup_consecutive_wrap_around() {
+        let shape: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 0.0, y: 0.0 },
+            ]
+        };
+        assert_eq!( shape.dedup_consecutive().corners.len(), 3 );
+
+        // A corner repeated later is not removed.
+        let repeated: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 2.0, y: 0.0 },
+                Point { x: 2.0, y: 2.0 },
+            ]
+        };
+        assert_eq!( repeated.dedup_consecutive().corners.len(), 5 );
+        assert!( !repeated.dedup_consecutive().is_valid().unwrap() );
+
+        // A shape of one point repeated keeps the point.
+        let dot: AngularShape = AngularShape { corners: vec![ Point { x: 1.0, y: 1.0 }; 3 ] };
+        assert_eq!( dot.dedup_consecutive().corners, vec![ Point { x: 1.0, y: 1.0 } ] );
+    }
+
+    #[test]
+    fn test_area_properties_pentagon() {
+
+        // A clockwise pentagon with an area of 3.
+        let pentagon: AngularShape = AngularShape {
+            corners: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: -1.0, y: 1.0 },
+                Point { x: 0.5, y: 2.0 },
+                Point { x: 1.5, y: 1.0 },
+                Point { x: 1.0, y: 0.0 },
+            ]
+        };
+        let properties: AreaProperties = pentagon.area_properties().unwrap();
+        assert!( ( properties.area - 3.0 ).abs() < 1e-12 );
+        assert!( ( properties.signed_area + 3.0 ).abs() < 1e-12 );
+        assert_eq!( properties.area, pentagon.area() );
+        assert_eq!( Some( properties.signed_area ), pentagon.signed_area() );
+        assert_eq!( Some( properties.centroid ), pentagon.centroid() );
+
+        // The centroid matches the mean of the triangle centroids weighted by their areas.
+        let mut weighted: Point = Point { x: 0.0, y: 0.0 };
+        for triangle in algorithms::triangulate( &pentagon.corners ) {
+            let area: f64 = AngularShape { corners: triangle.to_vec() }.area();
+            weighted.x += area * ( triangle[0].x + triangle[1].x + triangle[2].x ) / 3.0;
+            weighted.y += area * ( triangle[0].y + triangle[1].y + triangle[2].y ) / 3.0;
+        }
+        assert!( ( properties.centroid.x - weighted.x / 3.0 ).abs() < 1e-12 );
+        assert!( ( properties.centroid.y - weighted.y / 3.0 ).abs() < 1e-12 );
+
+        // Reversing the corners only changes the sign.
+        let mut reversed: AngularShape = AngularShape { corners: pentagon.corners.clone() };
+        reversed.corners.reverse();
+        assert_eq!( reversed.signed_area(), Some( -properties.signed_area ) );
+        assert!( AngularShape { corners: vec![] }.area_properties().is_none() );
+    }
+
+    #[test]
+    fn test_is_valid_exact() {
+        // The exact check agrees with the floating point one on ordinary shapes.
+        let square: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 1.0 }, Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 0.0 } ] };
+        assert!( square.is_valid().unwrap() );
+        assert!( square.is_valid_exact().unwrap() );
+
+        let bow_tie: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 2.0 } ] };
+        assert_eq!( bow_tie.is_valid_exact().is_ok_and( |valid| valid ), bow_tie.is_valid().is_ok_and( |valid| valid ) );
+        assert!( !bow_tie.is_valid_exact().is_ok_and( |valid| valid ) );
+    }
+
+    #[test]
+    fn test_is_valid_near_duplicate_corners() {
+        // Corners 1e-14 apart are the same corner.
+        let shape: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 0.00000000000001, y: 0.0 },
+        ] };
+        assert!( !shape.is_valid().unwrap() );
+
+        // Distinct corners close to each other are still fine.
+        let shape: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 0.001, y: 0.0 },
+        ] };
+        assert!( shape.is_valid().unwrap() );
+    }
+
+    #[test]
+    fn test_erode() {
+        // A 4 by 4 square eroded by 1 is a 2 by 2 square with the same center.
+        let square: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 4.0 }, Point { x: 4.0, y: 4.0 }, Point { x: 4.0, y: 0.0 } ] };
+        let eroded: AngularShape = square.erode( 1.0 ).unwrap();
+        assert!( (eroded.area() - 4.0).abs() < 0.000001 );
+        assert_eq!( eroded.bounding_box(), Some( (Point { x: 1.0, y: 1.0 }, Point { x: 3.0, y: 3.0 }) ) );
+
+        // The direction of the corners does not matter.
+        let reversed: AngularShape = AngularShape { corners: square.corners.iter().rev().copied().collect() };
+        assert!( (reversed.erode( 1.0 ).unwrap().area() - 4.0).abs() < 0.000001 );
+
+        // A corner on a straight edge stays on the edge.
+        let l_shape: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 4.0 }, Point { x: 2.0, y: 4.0 }, Point { x: 2.0, y: 2.0 },
+            Point { x: 4.0, y: 2.0 }, Point { x: 4.0, y: 0.0 }, Point { x: 2.0, y: 0.0 } ] };
+        let eroded: AngularShape = l_shape.erode( 0.5 ).unwrap();
+        assert!( (eroded.area() - 5.0).abs() < 0.000001 );
+
+        assert!( (square.erode( 0.0 ).unwrap().area() - 16.0).abs() < 0.000001 );
+        assert!( square.erode( -1.0 ).is_none() );
+    }
+
+    #[test]
+    fn test_erode_collapses() {
+        let square: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 4.0 }, Point { x: 4.0, y: 4.0 }, Point { x: 4.0, y: 0.0 } ] };
+        assert!( square.erode( 2.0 ).is_none() );
+        assert!( square.erode( 3.0 ).is_none() );
+
+        // A thin rectangle collapses before a wide one.
+        let thin: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 1.0 }, Point { x: 10.0, y: 1.0 }, Point { x: 10.0, y: 0.0 } ] };
+        assert!( thin.erode( 0.4 ).is_some() );
+        assert!( thin.erode( 0.6 ).is_none() );
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let sizes: [(f64, f64); 4] = [ (3.0, 2.0), (0.001, 0.01), (1000000.0, 250000.0), (0.5, 4000.0) ];
+        for (width, height) in sizes {
+            let rectangle: AngularShape = AngularShape::rectangle( Point { x: -1.0, y: 2.0 }, width, height );
+            assert!( rectangle.is_valid().unwrap() );
+            assert!( rectangle.signed_area().unwrap() > 0.0 );
+            assert!( (rectangle.area() - width * height).abs() <= 0.000000001 * width * height );
+        }
+
+        // A negative width or height goes the other way from the origin.
+        let rectangle: AngularShape = AngularShape::rectangle( Point { x: 1.0, y: 1.0 }, -3.0, -2.0 );
+        assert_eq!( rectangle.corners[0], Point { x: -2.0, y: -1.0 } );
+        assert_eq!( rectangle.corners[2], Point { x: 1.0, y: 1.0 } );
+        assert!( rectangle.signed_area().unwrap() > 0.0 );
+        assert_eq!( rectangle.area(), 6.0 );
+
+        let square: AngularShape = AngularShape::square( Point { x: 0.0, y: 0.0 }, 2.5 );
+        assert_eq!( square.area(), 6.25 );
+        assert!( !AngularShape::square( Point { x: 0.0, y: 0.0 }, 0.0 ).is_valid().unwrap_or( false ) );
+    }
+
+    #[test]
+    fn test_rectangle_small_sides() {
+        let origin: Point = Point { x: 0.0, y: 0.0 };
+
+        // Sides shorter than the shortening in the intersection check are fine.
+        let thin: AngularShape = AngularShape::rectangle( origin, 0.0000005, 1.0 );
+        assert!( thin.is_valid().unwrap() );
+        assert!( thin.is_valid_exact().unwrap() );
+        assert!( AngularShape::square( origin, 0.00001 ).is_valid().unwrap() );
+        assert!( AngularShape::square( origin, 0.0000001 ).is_valid().unwrap() );
+
+        // A side that is short compared to the other one has no area.
+        let sliver: AngularShape = AngularShape::rectangle( origin, 0.0000005, 1000000.0 );
+        assert!( matches!( sliver.is_valid(), Err( ShapeError::DegenerateZeroArea ) ) );
+
+        // A side within DEGENERATE_TOLERANCE of 0 makes its corners the same.
+        assert!( !AngularShape::square( origin, DEGENERATE_TOLERANCE ).is_valid().unwrap() );
+    }
+
+    #[test]
+    fn test_regular() {
+        let center: Point = Point { x: 2.0, y: -1.0 };
+        for n in [3, 4, 6, 100] {
+            let polygon: AngularShape = AngularShape::regular( n, center, 2.0 ).unwrap();
+            assert_eq!( polygon.corners.len(), n );
+            assert!( polygon.is_valid().unwrap() );
+
+            // The area of a regular polygon is n / 2 * r² * sin(2π / n).
+            let expected: f64 = n as f64 / 2.0 * 4.0 * ( 2.0 * std::f64::consts::PI / n as f64 ).sin();
+            assert!( (polygon.area() - expected).abs() < 0.000000001, "{n}: {} != {expected}", polygon.area() );
+        }
+
+        assert!( AngularShape::regular( 2, center, 1.0 ).is_err() );
+        assert!( AngularShape::regular( 0, center, 1.0 ).is_err() );
+        assert!( AngularShape::regular( 5, center, 0.0 ).is_err() );
+        assert!( AngularShape::regular( 5, center, -1.0 ).is_err() );
+        assert!( AngularShape::regular( 5, center, f64::INFINITY ).is_err() );
+    }
+
+    #[test]
+    fn test_contains_shape() {
+        let big: AngularShape = AngularShape::square( Point { x: 0.0, y: 0.0 }, 10.0 );
+        let small: AngularShape = AngularShape::square( Point { x: 2.0, y: 2.0 }, 3.0 );
+        assert!( big.contains_shape( &small ) );
+        assert!( !small.contains_shape( &big ) );
+
+        let overlapping: AngularShape = AngularShape::square( Point { x: 8.0, y: 8.0 }, 5.0 );
+        assert!( !big.contains_shape( &overlapping ) );
+
+        let disjoint: AngularShape = AngularShape::square( Point { x: 20.0, y: 0.0 }, 1.0 );
+        assert!( !big.contains_shape( &disjoint ) );
+
+        // All corners of the triangle are inside the U shape, but its edge crosses the gap.
+        let u_shape: AngularShape = AngularShape { corners: vec![
+            Point { x: 0.0, y: 0.0 }, Point { x: 6.0, y: 0.0 }, Point { x: 6.0, y: 6.0 }, Point { x: 4.0, y: 6.0 },
+            Point { x: 4.0, y: 2.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 6.0 }, Point { x: 0.0, y: 6.0 } ] };
+        let triangle: AngularShape = AngularShape { corners: vec![
+            Point { x: 1.0, y: 5.0 }, Point { x: 5.0, y: 5.0 }, Point { x: 3.0, y: 1.0 } ] };
+        assert!( !u_shape.contains_shape( &triangle ) );
+
+        // Sharing an edge is not inside.
+        let corner: AngularShape = AngularShape::square( Point { x: 0.0, y: 0.0 }, 2.0 );
+        assert!( !big.contains_shape( &corner ) );
+    }
+}
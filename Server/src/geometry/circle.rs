@@ -0,0 +1,259 @@
+use std::f64::consts::PI;
+
+use crate::geometry::angular_shape::ShapeError;
+use crate::geometry::point::Point;
+use crate::geometry::random::Random;
+use crate::geometry::traits::{AreaCalculatable, BoundingBox, PerimeterCalculatable};
+
+// Tolerance for deciding if a point is on the edge of a circle.
+const EPSILON: f64 = 0.000000001;
+
+// A circle is defined by its center point and radius.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+// Implement the Circle struct.
+impl Circle {
+
+    // Check if the circle is valid. A circle with a negative or not finite
+    // radius is not valid. A circle with radius 0 is a point, which is valid.
+    pub fn is_valid( &self ) -> Result<bool, ShapeError> {
+        if !self.radius.is_finite() || self.radius < 0.0 {
+            return Err( ShapeError::NotValidShape( format!("The radius {} of the circle is not a positive number.", self.radius).into() ) );
+        }
+        return Ok( self.center.x.is_finite() && self.center.y.is_finite() );
+    }
+
+    // Get the circumference of the circle, or -1 if the circle is not valid.
+    pub fn circumference( &self ) -> f64 {
+        if !self.is_valid().unwrap_or( false ) {
+            return -1.0;
+        }
+        return 2.0 * PI * self.radius;
+    }
+
+    // Check if the point is inside the circle or on its edge, with a small
+    // tolerance. A circle that is not valid contains no points.
+    pub fn contains_point( &self, p: &Point ) -> bool {
+        return self.is_valid().unwrap_or( false ) && self.encloses( p );
+    }
+
+    // Get the smallest circle that encloses all the points, or None if there are no points.
+    // Uses Welzl's algorithm in its iterative form, which runs in expected linear time
+    // when the points are in random order.
+    // https://en.wikipedia.org/wiki/Smallest-circle_problem#Welzl's_algorithm
+    pub fn minimal_enclosing( points: &[Point] ) -> Option<Circle> {
+
+        // There is no circle without points.
+        if points.is_empty() {
+            return None;
+        }
+
+        // Shuffle the points with a fixed seed, so the result does not depend on luck.
+        let mut points: Vec<Point> = points.to_vec();
+        let mut random: Random = Random::new( 0 );
+        for i in (1..points.len()).rev() {
+            let j: usize = (random.next_u64() % (i as u64 + 1)) as usize;
+            points.swap( i, j );
+        }
+
+        // Grow the circle whenever a point is outside it. The point that is outside
+        // must be on the edge of the new circle, and so on for up to three points.
+        let mut circle: Circle = Circle { center: points[0], radius: 0.0 };
+        for i in 1..points.len() {
+            if circle.encloses( &points[i] ) {
+                continue;
+            }
+            circle = Circle { center: points[i], radius: 0.0 };
+            for j in 0..i {
+                if circle.encloses( &points[j] ) {
+                    continue;
+                }
+                circle = Circle::from_diameter( &points[i], &points[j] );
+                for k in 0..j {
+                    if !circle.encloses( &points[k] ) {
+                        circle = Circle::from_three_points( &points[i], &points[j], &points[k] );
+                    }
+                }
+            }
+        }
+
+        return Some( circle );
+    }
+
+    // Get the circle that has the line between the two points as its diameter.
+    fn from_diameter( a: &Point, b: &Point ) -> Circle {
+        let center: Point = (*a + *b) * 0.5;
+        return Circle { center, radius: center.distance( a ) };
+    }
+
+    // Get the circle going through all three points.
+    // For collinear points this is the circle over the two points furthest apart.
+    // https://en.wikipedia.org/wiki/Circumcircle#Cartesian_coordinates_2
+    fn from_three_points( a: &Point, b: &Point, c: &Point ) -> Circle {
+
+        // Use coordinates relative to a to keep the numbers small.
+        let b: Point = *b - *a;
+        let c: Point = *c - *a;
+        let d: f64 = 2.0 * (b.x * c.y - b.y * c.x);
+
+        // Collinear points have no circumcircle.
+        if d.abs() < EPSILON {
+            let candidates: [Circle; 3] = [
+                Circle::from_diameter( a, &(*a + b) ),
+                Circle::from_diameter( a, &(*a + c) ),
+                Circle::from_diameter( &(*a + b), &(*a + c) ),
+            ];
+            return candidates.into_iter().max_by( |x, y| x.radius.total_cmp( &y.radius ) ).unwrap();
+        }
+
+        // Circumcenter relative to a.
+        let b_squared: f64 = b.x * b.x + b.y * b.y;
+        let c_squared: f64 = c.x * c.x + c.y * c.y;
+        let center: Point = Point {
+            x: (c.y * b_squared - b.y * c_squared) / d,
+            y: (b.x * c_squared - c.x * b_squared) / d,
+        };
+        return Circle { center: *a + center, radius: center.distance( &Point { x: 0.0, y: 0.0 } ) };
+    }
+
+    // Check if the point is inside the circle or on its edge, with a small tolerance.
+    fn encloses( &self, p: &Point ) -> bool {
+        return self.center.distance( p ) <= self.radius + EPSILON * self.radius.max( 1.0 );
+    }
+}
+
+// Implement the AreaCalculatable trait for the Circle struct.
+impl AreaCalculatable for Circle {
+
+    // Returns the area of the circle, PI * r², or -1 if the circle is not valid.
+    fn area(&self) -> f64 {
+        if !self.is_valid().unwrap_or( false ) {
+            return -1.0;
+        }
+        return PI * self.radius * self.radius;
+    }
+}
+
+// Implement the PerimeterCalculatable trait for the Circle struct.
+impl PerimeterCalculatable for Circle {
+
+    // Returns the circumference of the circle, or -1 if the circle is not valid.
+    fn perimeter(&self) -> f64 {
+        return self.circumference();
+    }
+}
+
+// Implement the BoundingBox trait for the Circle struct.
+impl BoundingBox for Circle {
+
+    // Returns the box from center - radius to center + radius, or None for a negative radius.
+    fn bounding_box(&self) -> Option<(Point, Point)> {
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+        let offset: Point = Point { x: self.radius, y: self.radius };
+        return Some( (self.center - offset, self.center + offset) );
+    }
+}
+
+
+// Unit tests for Circle.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_enclosing_square() {
+        let circle: Circle = Circle::minimal_enclosing( &[
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 0.0, y: 1.0 },
+        ] ).unwrap();
+        assert!( (circle.center.x - 0.5).abs() < 0.000001 );
+        assert!( (circle.center.y - 0.5).abs() < 0.000001 );
+        assert!( (circle.radius - 0.5_f64.sqrt()).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_minimal_enclosing_obtuse_triangle() {
+
+        // The circle of an obtuse triangle has the longest side as its diameter.
+        let circle: Circle = Circle::minimal_enclosing( &[
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 0.0 },
+            Point { x: 2.0, y: 0.5 },
+        ] ).unwrap();
+        assert!( (circle.center.x - 2.0).abs() < 0.000001 );
+        assert!( circle.center.y.abs() < 0.000001 );
+        assert!( (circle.radius - 2.0).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_minimal_enclosing_collinear_and_single() {
+        let circle: Circle = Circle::minimal_enclosing( &[
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 3.0, y: 3.0 },
+        ] ).unwrap();
+        assert!( (circle.radius - 18.0_f64.sqrt() / 2.0).abs() < 0.000001 );
+
+        let single: Circle = Circle::minimal_enclosing( &[ Point { x: 2.0, y: -1.0 } ] ).unwrap();
+        assert!( single == Circle { center: Point { x: 2.0, y: -1.0 }, radius: 0.0 } );
+        assert!( Circle::minimal_enclosing( &[] ).is_none() );
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let circle: Circle = Circle { center: Point { x: 1.0, y: -2.0 }, radius: 3.0 };
+        let (min, max) = circle.bounding_box().unwrap();
+        assert!( min == Point { x: -2.0, y: -5.0 } );
+        assert!( max == Point { x: 4.0, y: 1.0 } );
+
+        let invalid: Circle = Circle { center: Point { x: 0.0, y: 0.0 }, radius: -1.0 };
+        assert!( invalid.bounding_box().is_none() );
+        let nan: Circle = Circle { center: Point { x: 0.0, y: 0.0 }, radius: f64::NAN };
+        assert!( nan.bounding_box().is_none() );
+        let infinite: Circle = Circle { center: Point { x: f64::INFINITY, y: 0.0 }, radius: 1.0 };
+        assert!( infinite.bounding_box().is_none() );
+    }
+
+    #[test]
+    fn test_area() {
+        let point: Circle = Circle { center: Point { x: 1.0, y: 1.0 }, radius: 0.0 };
+        assert_eq!( point.area(), 0.0 );
+        assert_eq!( point.circumference(), 0.0 );
+
+        let unit: Circle = Circle { center: Point { x: 0.0, y: 0.0 }, radius: 1.0 };
+        assert!( (unit.area() - PI).abs() < 0.000001 );
+        assert!( (unit.circumference() - 2.0 * PI).abs() < 0.000001 );
+
+        let circle: Circle = Circle { center: Point { x: -2.0, y: 5.0 }, radius: 3.0 };
+        assert!( (circle.area() - 28.274333882308138).abs() < 0.000001 );
+        assert!( (circle.circumference() - 18.84955592153876).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_not_valid() {
+        let circle: Circle = Circle { center: Point { x: 0.0, y: 0.0 }, radius: -1.0 };
+        assert!( circle.is_valid().is_err() );
+        assert_eq!( circle.area(), -1.0 );
+        assert_eq!( circle.circumference(), -1.0 );
+        assert!( !circle.contains_point( &Point { x: 0.0, y: 0.0 } ) );
+        assert!( Circle { center: Point { x: 0.0, y: 0.0 }, radius: f64::NAN }.is_valid().is_err() );
+        assert!( Circle { center: Point { x: 0.0, y: 0.0 }, radius: 0.0 }.is_valid().unwrap() );
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let circle: Circle = Circle { center: Point { x: 1.0, y: 1.0 }, radius: 2.0 };
+        assert!( circle.contains_point( &Point { x: 1.0, y: 1.0 } ) );
+        assert!( circle.contains_point( &Point { x: 3.0, y: 1.0 } ) );
+        assert!( circle.contains_point( &Point { x: 2.0, y: 2.0 } ) );
+        assert!( !circle.contains_point( &Point { x: 2.5, y: 2.5 } ) );
+    }
+}
@@ -0,0 +1,167 @@
+use std::f64::consts::PI;
+
+use crate::geometry::angular_shape::ShapeError;
+use crate::geometry::point::Point;
+use crate::geometry::traits::{AreaCalculatable, BoundingBox};
+
+// Tolerance for deciding if a point is on the edge of an ellipse.
+const EPSILON: f64 = 0.000000001;
+
+// An ellipse is defined by its center point, its semi-major axis a along
+// the x axis, its semi-minor axis b along the y axis, and the angle in
+// radians it is rotated counterclockwise about its center.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ellipse {
+    pub center: Point,
+    pub a: f64,
+    pub b: f64,
+    pub rotation: f64,
+}
+
+// Implement the Ellipse struct.
+impl Ellipse {
+
+    // Create a new ellipse that is not rotated.
+    pub fn new( center: Point, a: f64, b: f64 ) -> Ellipse {
+        return Ellipse { center, a, b, rotation: 0.0 };
+    }
+
+    // Return the ellipse rotated counterclockwise by the angle in radians.
+    pub fn rotated( &self, radians: f64 ) -> Ellipse {
+        return Ellipse { rotation: self.rotation + radians, ..*self };
+    }
+
+    // Check if the ellipse is valid. An ellipse with a negative or not
+    // finite axis is not a valid shape, and the error tells which axis. An
+    // axis of 0 flattens the ellipse to a line, which is valid but has no area.
+    pub fn is_valid( &self ) -> Result<bool, ShapeError> {
+        for (name, axis) in [ ("semi-major", self.a), ("semi-minor", self.b) ] {
+            if !axis.is_finite() || axis < 0.0 {
+                return Err( ShapeError::NotValidShape( format!("The {} axis {} of the ellipse is not a positive number.", name, axis).into() ) );
+            }
+        }
+        return Ok( self.center.x.is_finite() && self.center.y.is_finite() && self.rotation.is_finite() );
+    }
+
+    // Check if the point is inside the ellipse or on its edge, with a small
+    // tolerance. A shape that is not valid contains no points.
+    pub fn contains( &self, p: &Point ) -> bool {
+        if !self.is_valid().unwrap_or( false ) {
+            return false;
+        }
+
+        // Turn the point back by the rotation, so the axes are along x and y.
+        let d: Point = p.rotate( -self.rotation, &self.center ) - self.center;
+
+        // A flat ellipse is the line between its ends.
+        if self.a == 0.0 || self.b == 0.0 {
+            return d.x.abs() <= self.a + EPSILON && d.y.abs() <= self.b + EPSILON;
+        }
+        let (x, y) = (d.x / self.a, d.y / self.b);
+        return x * x + y * y <= 1.0 + EPSILON;
+    }
+}
+
+// Implement the AreaCalculatable trait for the Ellipse struct.
+impl AreaCalculatable for Ellipse {
+
+    // Returns the area of the ellipse, PI * a * b, or -1 if the ellipse is not valid.
+    fn area(&self) -> f64 {
+        if !self.is_valid().unwrap_or( false ) {
+            return -1.0;
+        }
+        return PI * self.a * self.b;
+    }
+}
+
+// Implement the BoundingBox trait for the Ellipse struct.
+impl BoundingBox for Ellipse {
+
+    // Returns the box around the rotated ellipse, or None if the ellipse is not valid.
+    // https://math.stackexchange.com/questions/91132/how-to-get-the-limits-of-rotated-ellipse
+    fn bounding_box(&self) -> Option<(Point, Point)> {
+        if !self.is_valid().unwrap_or( false ) {
+            return None;
+        }
+        let (sin, cos) = self.rotation.sin_cos();
+        let offset: Point = Point {
+            x: ((self.a * cos).powi( 2 ) + (self.b * sin).powi( 2 )).sqrt(),
+            y: ((self.a * sin).powi( 2 ) + (self.b * cos).powi( 2 )).sqrt(),
+        };
+        return Some( (self.center - offset, self.center + offset) );
+    }
+}
+
+
+// Unit tests for Ellipse.
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn test_area() {
+        let ellipse: Ellipse = Ellipse::new( Point { x: 1.0, y: 1.0 }, 3.0, 2.0 );
+        assert!( (ellipse.area() - 6.0 * PI).abs() < 0.000001 );
+
+        // An ellipse with equal axes is a circle.
+        let circle: Ellipse = Ellipse::new( Point { x: 0.0, y: 0.0 }, 2.0, 2.0 );
+        assert!( (circle.area() - PI * 2.0 * 2.0).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_not_valid() {
+        let ellipse: Ellipse = Ellipse::new( Point { x: 0.0, y: 0.0 }, -1.0, 2.0 );
+        assert!( matches!( ellipse.is_valid(), Err( ShapeError::NotValidShape( _ ) ) ) );
+        assert_eq!( ellipse.is_valid().unwrap_err().to_string(),
+            "The shape is not valid. The semi-major axis -1 of the ellipse is not a positive number." );
+        assert!( ellipse.area() == -1.0 );
+        assert!( ellipse.bounding_box().is_none() );
+        assert!( !ellipse.contains( &Point { x: 0.0, y: 0.0 } ) );
+        assert!( Ellipse::new( Point { x: 0.0, y: 0.0 }, 1.0, f64::NAN ).is_valid().is_err() );
+        assert!( Ellipse::new( Point { x: 0.0, y: 0.0 }, 1.0, f64::INFINITY ).is_valid().is_err() );
+
+        // A center or rotation that is not finite is not valid, but the axes are.
+        assert!( !Ellipse::new( Point { x: f64::NAN, y: 0.0 }, 1.0, 1.0 ).is_valid().unwrap() );
+        assert!( !Ellipse::new( Point { x: 0.0, y: 0.0 }, 1.0, 1.0 ).rotated( f64::INFINITY ).is_valid().unwrap() );
+
+        // A flat ellipse is valid, but has no area.
+        let flat: Ellipse = Ellipse::new( Point { x: 0.0, y: 0.0 }, 2.0, 0.0 );
+        assert!( flat.is_valid().unwrap() );
+        assert!( flat.area() == 0.0 );
+        assert!( flat.contains( &Point { x: 1.5, y: 0.0 } ) );
+        assert!( !flat.contains( &Point { x: 1.5, y: 0.1 } ) );
+    }
+
+    #[test]
+    fn test_contains() {
+        let ellipse: Ellipse = Ellipse::new( Point { x: 1.0, y: 1.0 }, 3.0, 1.0 );
+        assert!( ellipse.contains( &Point { x: 1.0, y: 1.0 } ) );
+        assert!( ellipse.contains( &Point { x: 4.0, y: 1.0 } ) );
+        assert!( ellipse.contains( &Point { x: 3.0, y: 1.5 } ) );
+        assert!( !ellipse.contains( &Point { x: 1.0, y: 2.5 } ) );
+        assert!( !ellipse.contains( &Point { x: 4.1, y: 1.0 } ) );
+    }
+
+    #[test]
+    fn test_rotated() {
+        // A quarter turn makes the long axis vertical.
+        let ellipse: Ellipse = Ellipse::new( Point { x: 1.0, y: 1.0 }, 3.0, 1.0 ).rotated( FRAC_PI_2 );
+        assert!( ellipse.contains( &Point { x: 1.0, y: 3.5 } ) );
+        assert!( !ellipse.contains( &Point { x: 3.5, y: 1.0 } ) );
+        assert!( (ellipse.area() - 3.0 * PI).abs() < 0.000001 );
+
+        let (min, max) = ellipse.bounding_box().unwrap();
+        assert!( (min.x - 0.0).abs() < 0.000001 && (min.y + 2.0).abs() < 0.000001 );
+        assert!( (max.x - 2.0).abs() < 0.000001 && (max.y - 4.0).abs() < 0.000001 );
+
+        // At 45 degrees the box is a square.
+        let diagonal: Ellipse = Ellipse::new( Point { x: 0.0, y: 0.0 }, 3.0, 1.0 ).rotated( FRAC_PI_2 / 2.0 );
+        let (min, max) = diagonal.bounding_box().unwrap();
+        assert!( (max.x - 5.0_f64.sqrt()).abs() < 0.000001 && (max.y - 5.0_f64.sqrt()).abs() < 0.000001 );
+        assert!( min == Point { x: -max.x, y: -max.y } );
+        assert!( diagonal.contains( &Point { x: 2.0, y: 2.0 } ) );
+        assert!( !diagonal.contains( &Point { x: 1.0, y: -1.0 } ) );
+    }
+}
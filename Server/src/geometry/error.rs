@@ -0,0 +1,59 @@
+// How close two points can be to count as the same point. Lines shorter
+// than this are degenerate, and corners closer than this are duplicates.
+pub const DEGENERATE_TOLERANCE: f64 = 0.000000001;
+
+// The policy for degenerate input, where points that should be distinct are
+// the same point. Each function handles it in the way that fits its result,
+// so there is no shared error type:
+// - Line::length is 0 and Line::angle is 0.
+// - Line::try_new and Line::shorten return LineError::ZeroLength.
+// - Line::intersects treats the line as its point, which intersects another
+//   line if it lies on it. Two points are collinear with any third point.
+// - AngularShape::get_lines returns no lines for less than 2 corners.
+// - AngularShape::is_valid returns false for duplicate corners, and
+//   ShapeError::DegenerateZeroArea for corners on one line.
+
+
+// Unit tests for the degenerate input policy.
+#[cfg(test)]
+mod tests {
+    use crate::geometry::angular_shape::AngularShape;
+    use crate::geometry::line::{Line, LineError};
+    use crate::geometry::point::Point;
+
+    use super::*;
+
+    // A named check and whether it has the documented outcome.
+    type Case<'a> = (&'a str, Box<dyn Fn() -> bool + 'a>);
+
+    #[test]
+    fn test_degenerate_policy() {
+        let p: Point = Point { x: 1.0, y: 2.0 };
+        let near: Point = Point { x: 1.0, y: 2.0 + DEGENERATE_TOLERANCE / 2.0 };
+        let degenerate: Line = Line::new( p, p );
+        let is_degenerate = |result: Result<Line, LineError>| matches!(
+            result, Err( LineError::ZeroLength { point } ) if point == p );
+
+        // Each case is a function and the documented outcome for coincident points.
+        let cases: Vec<Case> = vec![
+            ( "length is zero", Box::new( || degenerate.length() == 0.0 ) ),
+            ( "angle is zero", Box::new( || degenerate.angle() == 0.0 ) ),
+            ( "try_new rejects the same point", Box::new( || is_degenerate( Line::try_new( p, p ) ) ) ),
+            ( "try_new rejects a nearly same point", Box::new( || is_degenerate( Line::try_new( p, near ) ) ) ),
+            ( "shorten rejects", Box::new( || is_degenerate( degenerate.shorten( 0.0 ) ) ) ),
+            ( "intersects a line through the point", Box::new( || degenerate.intersects(
+                &Line::new( Point { x: 0.0, y: 1.0 }, Point { x: 2.0, y: 3.0 } ) ) ) ),
+            ( "does not intersect a line beside the point", Box::new( || !degenerate.intersects(
+                &Line::new( Point { x: 0.0, y: 0.0 }, Point { x: 2.0, y: 0.0 } ) ) ) ),
+            ( "does not intersect a collinear line past the point", Box::new( || !degenerate.intersects(
+                &Line::new( Point { x: 2.0, y: 3.0 }, Point { x: 3.0, y: 4.0 } ) ) ) ),
+            ( "intersects the same point", Box::new( || degenerate.intersects( &Line::new( p, p ) ) ) ),
+            ( "no lines for one corner", Box::new( || AngularShape { corners: vec![ p ] }.get_lines().is_empty() ) ),
+            ( "not valid with duplicate corners", Box::new( || !AngularShape { corners: vec![
+                p, near, Point { x: 3.0, y: 0.0 }, Point { x: 0.0, y: 0.0 } ] }.is_valid().unwrap() ) ),
+        ];
+        for (name, case) in cases {
+            assert!( case(), "{name}" );
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::error::Error;
 
+use crate::geometry::algorithms;
+use crate::geometry::error::DEGENERATE_TOLERANCE;
 use crate::geometry::point::Point;
 
 // A line is defined by two points.
@@ -17,6 +19,9 @@ pub enum LineError {
     LineTooShortForShortening {
         current_length: f64,
         requested_shortening: f64
+    },
+    ZeroLength {
+        point: Point
     }
 }
 impl std::fmt::Display for LineError {
@@ -25,6 +30,9 @@ impl std::fmt::Display for LineError {
             LineError::LineTooShortForShortening { current_length, requested_shortening } => {
                 write!(f, "The line is too short for shortening. The current length is {} and the requested shortening is {}.", current_length, requested_shortening)
             }
+            LineError::ZeroLength { point } => {
+                write!(f, "The line starts and ends at the same point {}.", point)
+            }
         }
     }
 }
@@ -35,13 +43,52 @@ impl Error for LineError {
 // Implement the Line struct.
 impl Line {
 
-    // Get the length of the line
+    // Create a new line from the start point to the end point.
+    pub fn new( start: Point, end: Point ) -> Line {
+        Line { start, end }
+    }
+
+    // Create a new line from the start point to the end point,
+    // or an error if the points are the same and the line has no length.
+    pub fn try_new( start: Point, end: Point ) -> Result<Line> {
+        let line: Line = Line { start, end };
+        if line.is_degenerate() {
+            return Err( LineError::ZeroLength { point: start } );
+        }
+        Ok( line )
+    }
+
+    // Check if the line is so short that its start and end are the same point.
+    pub fn is_degenerate( &self ) -> bool {
+        return self.length() <= DEGENERATE_TOLERANCE;
+    }
+
+    // Get the length of the line, 0 if the start and end are the same point.
     pub fn length( &self ) -> f64 {
         let dx = self.start.x - self.end.x;
         let dy = self.start.y - self.end.y;
         ( (dx * dx + dy * dy)  as f64).sqrt()
     }
 
+    // Get the direction of the line from start to end in radians.
+    // The angle is measured counterclockwise from the positive x-axis and is in range (-π, π].
+    // The angle of a line without length is 0.
+    pub fn angle( &self ) -> f64 {
+        let dx = self.end.x - self.start.x;
+        let dy = self.end.y - self.start.y;
+
+        // atan2 keeps the sign of a zero dy, so a line pointing left can get -π,
+        // and a line without length can get -0 or -π.
+        if dx == 0.0 && dy == 0.0 {
+            return 0.0;
+        }
+        let angle: f64 = dy.atan2( dx );
+        if angle == -std::f64::consts::PI {
+            return std::f64::consts::PI;
+        }
+        angle
+    }
+
     // Return a flipped version of the line.
     pub fn flip( &self ) -> Line {
         return Line {
@@ -50,8 +97,14 @@ impl Line {
         }
     }
 
-    // Return a shortened version of the line.
+    // Return a shortened version of the line. A line without length has no
+    // direction to shorten it in, so it is an error even when delta_l is 0.
     pub fn shorten( &self, delta_l: f64 ) -> Result<Line> {
+
+        // A line without length has no direction.
+        if self.is_degenerate() {
+            return Err( LineError::ZeroLength { point: self.start } );
+        }
         
         // Do not shorten if the line is too short.
         let length: f64 = self.length();
@@ -62,18 +115,9 @@ impl Line {
             } );
         }
 
-        // Make shorter variable names.
-        let x1: f64 = self.start.x;
-        let x2: f64 = self.end.x;
-        let y1: f64 = self.start.y;
-        let y2: f64 = self.end.y;
-
-        // Calculate delta for x and y.
-        let dx: f64 = ( x2 - x1 ) / ( x2.powi(2) - 2.0 * x2 * x1 + x1.powi(2) + y2.powi(2) - 2.0 * y2 * y1 + y1.powi(2) ).sqrt();
-        let dy: f64 = ( y2 - y1 ) / ( x2.powi(2) - 2.0 * x2 * x1 + x1.powi(2) + y2.powi(2) - 2.0 * y2 * y1 + y1.powi(2) ).sqrt();
-
-        // Keep the starting point as is, modify end point.
-        let new_end: Point = Point{ x: x2 - delta_l * dx, y: y2 - delta_l * dy };
+        // Move the end point towards the start point along the unit vector of the line.
+        let direction: Point = (self.end - self.start) * (1.0 / length);
+        let new_end: Point = self.end - direction * delta_l;
 
         // Return the new line.
         return Ok( Line {
@@ -82,16 +126,136 @@ impl Line {
         } );
     }
 
+    // Get the point halfway between the start and the end. A line without
+    // length has its point as the midpoint.
+    pub fn midpoint( &self ) -> Point {
+        return (self.start + self.end) * 0.5;
+    }
+
+    // Get the point at parameter t along the line, the start at 0 and the
+    // end at 1.
+    pub fn point_at( &self, t: f64 ) -> Point {
+        return self.start + (self.end - self.start) * t;
+    }
+
+    // Get the parameter t of a point on the line, the inverse of point_at.
+    // Returns None if the point is not on the line within DEGENERATE_TOLERANCE.
+    // A line without length has its point at 0.
+    pub fn t_for_point( &self, p: &Point ) -> Option<f64> {
+        if self.is_degenerate() {
+            return if p.distance( &self.start ) <= DEGENERATE_TOLERANCE { Some( 0.0 ) } else { None };
+        }
+
+        // The ends are exact, other points are projected on the line and
+        // must be close to their projection.
+        if *p == self.start {
+            return Some( 0.0 );
+        }
+        if *p == self.end {
+            return Some( 1.0 );
+        }
+        let direction: Point = self.end - self.start;
+        let length: f64 = self.length();
+        let t: f64 = ((p.x - self.start.x) * direction.x + (p.y - self.start.y) * direction.y) / (length * length);
+        let tolerance: f64 = DEGENERATE_TOLERANCE / length;
+        if t < -tolerance || t > 1.0 + tolerance || p.distance( &self.point_at( t ) ) > DEGENERATE_TOLERANCE {
+            return None;
+        }
+        return Some( t.clamp( 0.0, 1.0 ) );
+    }
+
+    // Get the shortest distance from the point to the line. The point is
+    // projected on the line, and a projection past an end is moved to that
+    // end, so the distance is to the segment and not the infinite line.
+    // A line without length is its point.
+    pub fn distance_to_point( &self, p: Point ) -> f64 {
+        if self.is_degenerate() {
+            return p.distance( &self.start );
+        }
+        let direction: Point = self.end - self.start;
+        let t: f64 = ((p.x - self.start.x) * direction.x + (p.y - self.start.y) * direction.y) / self.start.distance_squared( &self.end );
+        return p.distance( &self.point_at( t.clamp( 0.0, 1.0 ) ) );
+    }
+
+    // Check if the point is on the line, within epsilon. The point must be
+    // at most epsilon from the infinite line through the ends, and between
+    // the ends on both axes with epsilon to spare, like on_segment. A line
+    // without length contains the points within epsilon of its point.
+    pub fn contains_point( &self, p: Point, epsilon: f64 ) -> bool {
+        if self.is_degenerate() {
+            return p.distance( &self.start ) <= epsilon;
+        }
+
+        // The orientation value is the distance from the line times its length.
+        return self.orientation( self.start, self.end, p, epsilon * self.length() ) == 0 &&
+            self.on_segment( self.start, p, self.end, epsilon );
+    }
+
+    // Return the line moved by dx and dy.
+    pub fn translate( &self, dx: f64, dy: f64 ) -> Line {
+        let offset: Point = Point { x: dx, y: dy };
+        return Line {
+            start: self.start + offset,
+            end: self.end + offset,
+        };
+    }
+
+    // Return the line rotated counterclockwise by the given angle in radians
+    // around a point.
+    pub fn rotate( &self, radians: f64, about: &Point ) -> Line {
+        return Line {
+            start: self.start.rotate( radians, about ),
+            end: self.end.rotate( radians, about ),
+        };
+    }
+
+    // Return the perpendicular bisector of the line: a line through the middle
+    // of this line, at right angles to it and reaching half_length to each
+    // side. The bisector turns counterclockwise from this line, so it starts
+    // on the right side of the line. Returns None for a line without length.
+    pub fn perpendicular_bisector( &self, half_length: f64 ) -> Option<Line> {
+        if self.is_degenerate() {
+            return None;
+        }
+        let middle: Point = self.midpoint();
+        let direction: Point = (self.end - self.start) * (1.0 / self.length());
+        let normal: Point = Point { x: -direction.y, y: direction.x };
+        return Some( Line {
+            start: middle - normal * half_length,
+            end: middle + normal * half_length,
+        } );
+    }
+
     // Check if the line intersects with another line.
+    // A line without length is treated as its point, which intersects the other
+    // line if it lies on it.
     // https://www.geeksforgeeks.org/check-if-two-given-line-segments-intersect/
     pub fn intersects( &self, other: &Line ) -> bool {
-        
+        return self.intersects_with( other, false );
+    }
+
+    // Check if the line intersects with another line, like intersects, but
+    // with the exact orientation of the points. Nearly collinear points are
+    // not taken as collinear, and the result does not change when both lines
+    // are moved by the same exact offset.
+    pub fn intersects_exact( &self, other: &Line ) -> bool {
+        return self.intersects_with( other, true );
+    }
+
+    // Check if the line intersects with another line, with the exact or the
+    // floating point orientation of the points.
+    fn intersects_with( &self, other: &Line, exact: bool ) -> bool {
+        let orientation = |p: Point, q: Point, r: Point| match exact {
+            true => self.orientation_exact( p, q, r ),
+            false => self.orientation( p, q, r, 0.0 ),
+        };
+
         // Find the 4 orientations required for
         // the general and special cases
-        let o1: i32 = self.orientation( self.start, self.end, other.start );
-        let o2: i32 = self.orientation( self.start, self.end, other.end );
-        let o3: i32 = self.orientation( other.start, other.end, self.start );
-        let o4: i32 = self.orientation( other.start, other.end, self.end );
+        let o1: i32 = orientation( self.start, self.end, other.start );
+        let o2: i32 = orientation( self.start, self.end, other.end );
+        let o3: i32 = orientation( other.start, other.end, self.start );
+        let o4: i32 = orientation( other.start, other.end, self.end );
 
         // General case
         if o1 != o2 && o3 != o4 {
@@ -100,49 +264,137 @@ impl Line {
 
         // Special Cases
         // self.start, self.end and other.start are colinear and other.start lies on segment self.start, self.end
-        if o1 == 0 && self.on_segment( self.start, other.start, self.end ) {
+        if o1 == 0 && self.on_segment( self.start, other.start, self.end, 0.0 ) {
             return true;
         }
 
         // self.start, self.end and other.end are colinear and other.end lies on segment self.start, self.end
-        if o2 == 0 && self.on_segment( self.start, other.end, self.end ) {
+        if o2 == 0 && self.on_segment( self.start, other.end, self.end, 0.0 ) {
             return true;
         }
 
         // other.start, other.end and self.start are colinear and self.start lies on segment other.start, other.end
-        if o3 == 0 && self.on_segment( other.start, self.start, other.end ) {
+        if o3 == 0 && self.on_segment( other.start, self.start, other.end, 0.0 ) {
             return true;
         }
 
         // other.start, other.end and self.end are colinear and self.end lies on segment other.start, other.end
-        if o4 == 0 && self.on_segment( other.start, self.end, other.end ) {
+        if o4 == 0 && self.on_segment( other.start, self.end, other.end, 0.0 ) {
             return true;
         }
 
         return false;
     }
 
+    // Get the point where the line meets another line, or None if the lines
+    // do not intersect. The point is found with the parametric form of the
+    // lines, start + t * (end - start), where t is within [0, 1] on the line.
+    // Collinear lines that overlap meet in the middle of the overlap. The
+    // result is consistent with intersects: if the lines intersect there is
+    // a point.
+    // https://en.wikipedia.org/wiki/Line%E2%80%93line_intersection#Given_two_points_on_each_line_segment
+    pub fn intersection_point( &self, other: &Line ) -> Option<Point> {
+        if !self.intersects( other ) {
+            return None;
+        }
+
+        // Collinear lines, and lines without length, meet where they overlap.
+        if let Some( overlap ) = self.overlap( other ) {
+            return Some( overlap.midpoint() );
+        }
+
+        // Solve start + t * d1 = other.start + u * d2 for t. The lines are known
+        // to intersect, so t is clamped to [0, 1] against rounding errors.
+        let d1: Point = self.end - self.start;
+        let d2: Point = other.end - other.start;
+        let denominator: f64 = d1.x * d2.y - d1.y * d2.x;
+        if denominator == 0.0 {
+            return None;
+        }
+        let offset: Point = other.start - self.start;
+        let t: f64 = ((offset.x * d2.y - offset.y * d2.x) / denominator).clamp( 0.0, 1.0 );
+        return Some( self.start + d1 * t );
+    }
+
+    // Get the part of the line shared with another line when the lines are collinear and overlap.
+    // The overlap has the same direction as this line, and its start and end are the same
+    // point if the lines only touch. Returns None if the lines do not overlap or are not
+    // collinear, also when they cross each other.
+    pub fn overlap( &self, other: &Line ) -> Option<Line> {
+
+        // Measure along this line, or along the other line if this line is a single point.
+        if self.start == self.end {
+            if other.start == other.end {
+                return if self.start == other.start { Some( Line { start: self.start, end: self.end } ) } else { None };
+            }
+            return other.overlap( self ).map( |_| Line { start: self.start, end: self.end } );
+        }
+
+        // Both ends of the other line must be on the line through this line.
+        if self.orientation( self.start, self.end, other.start, 0.0 ) != 0 ||
+            self.orientation( self.start, self.end, other.end, 0.0 ) != 0 {
+            return None;
+        }
+
+        // Position of a point along this line, 0 at the start and 1 at the end.
+        let direction: Point = self.end - self.start;
+        let squared_length: f64 = self.start.distance_squared( &self.end );
+        let position = |p: Point| ((p.x - self.start.x) * direction.x + (p.y - self.start.y) * direction.y) / squared_length;
+
+        // The overlap is the intersection of the position ranges of the lines.
+        let other_start: f64 = position( other.start );
+        let other_end: f64 = position( other.end );
+        let from: f64 = other_start.min( other_end ).max( 0.0 );
+        let to: f64 = other_start.max( other_end ).min( 1.0 );
+        if from > to {
+            return None;
+        }
+
+        // Use the exact corner points where possible to avoid rounding errors.
+        let point_at = |t: f64| {
+            if t == 0.0 { self.start }
+            else if t == 1.0 { self.end }
+            else if t == other_start { other.start }
+            else if t == other_end { other.end }
+            else { self.start + direction * t }
+        };
+        return Some( Line { start: point_at( from ), end: point_at( to ) } );
+    }
+
     // To find orientation of ordered triplet (p, q, r).
     // The function returns following values
-    // 0 --> p, q and r are collinear
+    // 0 --> p, q and r are collinear, also when two of them are the same point
     // 1 --> Clockwise
     // 2 --> Counterclockwise
-    fn orientation( &self, p: Point, q: Point, r: Point ) -> i32 {
+    // The points are collinear if the value of the formula is within epsilon.
+    // The value is the distance of r from the line through p and q times the
+    // distance between p and q.
+    fn orientation( &self, p: Point, q: Point, r: Point, epsilon: f64 ) -> i32 {
 
         // See https://www.geeksforgeeks.org/orientation-3-ordered-points/
         // for details of below formula.
         let val: f64 = (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y);
-        if val == 0.0 {
+        if val.abs() <= epsilon {
             return 0; // collinear
         }
         return if val > 0.0 { 1 } else { 2 }; // clock or counterclock wise
     }
 
+    // Find the orientation of the ordered triplet (p, q, r) like orientation,
+    // but exactly.
+    fn orientation_exact( &self, p: Point, q: Point, r: Point ) -> i32 {
+        return match algorithms::orientation_sign_exact( &p, &q, &r ) {
+            0 => 0, // collinear
+            1 => 1, // clockwise
+            _ => 2, // counterclockwise
+        };
+    }
+
     // Given three collinear points p, q, r, the function checks if
-    // point q lies on line segment 'pr'.
-    fn on_segment( &self, p: Point, q: Point, r: Point ) -> bool {
-        if q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) &&
-            q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y) {
+    // point q lies on line segment 'pr', with epsilon to spare on both axes.
+    fn on_segment( &self, p: Point, q: Point, r: Point, epsilon: f64 ) -> bool {
+        if q.x <= p.x.max(r.x) + epsilon && q.x >= p.x.min(r.x) - epsilon &&
+            q.y <= p.y.max(r.y) + epsilon && q.y >= p.y.min(r.y) - epsilon {
             return true;
         }
         return false;
@@ -156,8 +408,10 @@ impl Line {
 #[cfg(test)]
 mod tests {
     use std::result::Result;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 
     use super::*;
+    use crate::geometry::random::Random;
 
     #[test]
     fn test_length() {
@@ -168,6 +422,33 @@ mod tests {
         assert_eq!( line.length(), 4.0 );
     }
 
+    #[test]
+    fn test_midpoint() {
+        let line: Line = Line {
+            start: Point { x: 0.0, y: 0.0 },
+            end: Point { x: 4.0, y: 0.0 },
+        };
+        assert_eq!( line.midpoint(), Point { x: 2.0, y: 0.0 } );
+    }
+
+    #[test]
+    fn test_midpoint_zero() {
+        let line: Line = Line {
+            start: Point { x: 1.0, y: 1.0 },
+            end: Point { x: 1.0, y: 1.0 },
+        };
+        assert_eq!( line.midpoint(), Point { x: 1.0, y: 1.0 } );
+    }
+
+    #[test]
+    fn test_midpoint_negative() {
+        let line: Line = Line {
+            start: Point { x: -3.0, y: -1.0 },
+            end: Point { x: -1.0, y: -5.0 },
+        };
+        assert_eq!( line.midpoint(), Point { x: -2.0, y: -3.0 } );
+    }
+
     #[test]
     fn test_length_zero() {
         let line: Line = Line {
@@ -234,6 +515,9 @@ mod tests {
             Err( LineError::LineTooShortForShortening { current_length: _, requested_shortening: _ } ) => {
                 assert!( true );
             }
+            Err( e ) => {
+                assert!( false, "Unexpected error: {}", e );
+            }
         }
     }
 
@@ -309,6 +593,32 @@ mod tests {
         assert_eq!( flipped_line.end, line.start );
     }
 
+    #[test]
+    fn test_angle() {
+        let origo: Point = Point { x: 0.0, y: 0.0 };
+        let angle = |x: f64, y: f64| Line { start: origo, end: Point { x, y } }.angle();
+
+        // The four axis directions. Pointing left is π, not -π.
+        assert_eq!( angle( 1.0, 0.0 ), 0.0 );
+        assert!( (angle( 0.0, 1.0 ) - FRAC_PI_2).abs() < 0.000001 );
+        assert!( (angle( -1.0, 0.0 ) - PI).abs() < 0.000001 );
+        assert!( (angle( 0.0, -1.0 ) + FRAC_PI_2).abs() < 0.000001 );
+
+        // A diagonal at 45 degrees, and the same line flipped.
+        let diagonal: Line = Line { start: Point { x: 1.0, y: 1.0 }, end: Point { x: 3.0, y: 3.0 } };
+        assert!( (diagonal.angle() - FRAC_PI_4).abs() < 0.000001 );
+        assert!( (diagonal.flip().angle() + 3.0 * FRAC_PI_4).abs() < 0.000001 );
+
+        // Pointing left with a negative zero is still π, not -π.
+        assert_eq!( angle( -1.0, -0.0 ), PI );
+        assert_eq!( Line { start: Point { x: 2.0, y: 0.0 }, end: Point { x: 1.0, y: -0.0 } }.angle(), PI );
+
+        // A line without length has angle 0, with any signs of zero.
+        assert_eq!( angle( 0.0, 0.0 ), 0.0 );
+        assert!( angle( -0.0, -0.0 ).is_sign_positive() );
+        assert_eq!( angle( -0.0, -0.0 ), 0.0 );
+    }
+
     #[test]
     fn test_intersect() {
 
@@ -394,4 +704,272 @@ mod tests {
         assert!( line1.intersects( &line2 ) );
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_overlap_full() {
+        let line1: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 4.0, y: 4.0 } };
+        let line2: Line = Line { start: Point { x: 4.0, y: 4.0 }, end: Point { x: 0.0, y: 0.0 } };
+        let overlap: Line = line1.overlap( &line2 ).unwrap();
+        assert_eq!( overlap.start, line1.start );
+        assert_eq!( overlap.end, line1.end );
+
+        // A line inside the other overlaps entirely.
+        let inner: Line = Line { start: Point { x: 1.0, y: 1.0 }, end: Point { x: 2.0, y: 2.0 } };
+        let overlap: Line = line1.overlap( &inner ).unwrap();
+        assert_eq!( overlap.start, inner.start );
+        assert_eq!( overlap.end, inner.end );
+    }
+
+    #[test]
+    fn test_overlap_partial() {
+        let line1: Line = Line { start: Point { x: 0.0, y: 1.0 }, end: Point { x: 4.0, y: 1.0 } };
+        let line2: Line = Line { start: Point { x: 6.0, y: 1.0 }, end: Point { x: 2.0, y: 1.0 } };
+        let overlap: Line = line1.overlap( &line2 ).unwrap();
+        assert_eq!( overlap.start, Point { x: 2.0, y: 1.0 } );
+        assert_eq!( overlap.end, Point { x: 4.0, y: 1.0 } );
+
+        // The overlap follows the direction of the line it is asked from.
+        let overlap: Line = line2.overlap( &line1 ).unwrap();
+        assert_eq!( overlap.start, Point { x: 4.0, y: 1.0 } );
+        assert_eq!( overlap.end, Point { x: 2.0, y: 1.0 } );
+    }
+
+    #[test]
+    fn test_overlap_touching() {
+        let line1: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 2.0, y: 0.0 } };
+        let line2: Line = Line { start: Point { x: 2.0, y: 0.0 }, end: Point { x: 5.0, y: 0.0 } };
+        let overlap: Line = line1.overlap( &line2 ).unwrap();
+        assert_eq!( overlap.start, Point { x: 2.0, y: 0.0 } );
+        assert_eq!( overlap.end, Point { x: 2.0, y: 0.0 } );
+    }
+
+    #[test]
+    fn test_overlap_none() {
+        let line1: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 2.0, y: 0.0 } };
+
+        // Collinear but apart.
+        let apart: Line = Line { start: Point { x: 3.0, y: 0.0 }, end: Point { x: 5.0, y: 0.0 } };
+        assert!( line1.overlap( &apart ).is_none() );
+
+        // Crossing lines are not collinear.
+        let crossing: Line = Line { start: Point { x: 1.0, y: -1.0 }, end: Point { x: 1.0, y: 1.0 } };
+        assert!( line1.intersects( &crossing ) );
+        assert!( line1.overlap( &crossing ).is_none() );
+
+        // Parallel lines.
+        let parallel: Line = Line { start: Point { x: 0.0, y: 1.0 }, end: Point { x: 2.0, y: 1.0 } };
+        assert!( line1.overlap( &parallel ).is_none() );
+    }
+
+    #[test]
+    fn test_overlap_point() {
+        let line: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 2.0, y: 2.0 } };
+        let point: Line = Line { start: Point { x: 1.0, y: 1.0 }, end: Point { x: 1.0, y: 1.0 } };
+        assert_eq!( point.overlap( &line ).unwrap().start, point.start );
+        assert_eq!( line.overlap( &point ).unwrap().end, point.end );
+        assert!( point.overlap( &Line { start: Point { x: 3.0, y: 3.0 }, end: Point { x: 4.0, y: 4.0 } } ).is_none() );
+    }
+
+    #[test]
+    fn test_new() {
+        let line: Line = Line::new( Point { x: 1.0, y: 2.0 }, Point { x: 4.0, y: 6.0 } );
+        assert_eq!( line.start, Point { x: 1.0, y: 2.0 } );
+        assert_eq!( line.end, Point { x: 4.0, y: 6.0 } );
+        assert_eq!( line.length(), 5.0 );
+
+        // A line without length can still be created with new.
+        let point: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 } );
+        assert_eq!( point.length(), 0.0 );
+    }
+
+    #[test]
+    fn test_try_new() {
+        let line: Line = Line::try_new( Point { x: 0.0, y: 0.0 }, Point { x: 0.0, y: 2.0 } ).unwrap();
+        assert_eq!( line.length(), 2.0 );
+
+        let result: Result<Line, LineError> = Line::try_new( Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 } );
+        assert!( matches!( result, Err( LineError::ZeroLength { point } ) if point == Point { x: 1.0, y: 1.0 } ) );
+    }
+
+    #[test]
+    fn test_intersects_exact() {
+        // The end of the second line is just below the first line, but the floating point
+        // orientation takes it as on the line, so the lines seem to touch.
+        let line: Line = Line { start: Point { x: 0.1, y: 0.1 }, end: Point { x: 24.3, y: 24.3 } };
+        let near: Line = Line { start: Point { x: 5.1, y: 5.099999999999996 }, end: Point { x: 6.0, y: 1.0 } };
+        assert!( line.intersects( &near ) );
+        assert!( !line.intersects_exact( &near ) );
+        assert!( !near.intersects_exact( &line ) );
+
+        // Crossing lines intersect with both predicates.
+        let crossing: Line = Line { start: Point { x: 5.0, y: 0.0 }, end: Point { x: 0.0, y: 5.0 } };
+        assert!( line.intersects( &crossing ) );
+        assert!( line.intersects_exact( &crossing ) );
+    }
+
+    #[test]
+    fn test_perpendicular_bisector() {
+        let horizontal: Line = Line::new( Point { x: 0.0, y: 1.0 }, Point { x: 4.0, y: 1.0 } );
+        let bisector: Line = horizontal.perpendicular_bisector( 2.0 ).unwrap();
+        assert_eq!( bisector.start, Point { x: 2.0, y: -1.0 } );
+        assert_eq!( bisector.end, Point { x: 2.0, y: 3.0 } );
+
+        let diagonal: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 4.0, y: 5.0 } );
+        let bisector: Line = diagonal.perpendicular_bisector( 1.5 ).unwrap();
+        let along: Point = diagonal.end - diagonal.start;
+        let across: Point = bisector.end - bisector.start;
+        assert!( (along.x * across.x + along.y * across.y).abs() < 1e-12 );
+        assert!( (bisector.length() - 3.0).abs() < 1e-12 );
+        let middle: Point = (bisector.start + bisector.end) * 0.5;
+        assert!( (middle.x - 2.5).abs() < 1e-12 && (middle.y - 3.0).abs() < 1e-12 );
+
+        let point: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 } );
+        assert!( point.perpendicular_bisector( 1.0 ).is_none() );
+    }
+
+    #[test]
+    fn test_distance_to_point() {
+        let line: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 4.0, y: 0.0 } };
+
+        // Above the middle the distance is straight down to the line.
+        assert_eq!( line.distance_to_point( Point { x: 2.0, y: 3.0 } ), 3.0 );
+        assert_eq!( line.distance_to_point( Point { x: 1.0, y: 0.0 } ), 0.0 );
+
+        // Past an end the distance is to that end.
+        assert_eq!( line.distance_to_point( Point { x: 7.0, y: 4.0 } ), 5.0 );
+        assert_eq!( line.distance_to_point( Point { x: -3.0, y: -4.0 } ), 5.0 );
+
+        // A line without length is its point.
+        let point: Line = Line { start: Point { x: 1.0, y: 1.0 }, end: Point { x: 1.0, y: 1.0 } };
+        assert_eq!( point.distance_to_point( Point { x: 4.0, y: 5.0 } ), 5.0 );
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let line: Line = Line { start: Point { x: 0.0, y: 0.0 }, end: Point { x: 3.0, y: 3.0 } };
+        assert!( line.contains_point( Point { x: 1.0, y: 1.0 }, 0.0 ) );
+        assert!( line.contains_point( Point { x: 0.0, y: 0.0 }, 0.0 ) );
+        assert!( line.contains_point( Point { x: 3.0, y: 3.0 }, 0.0 ) );
+
+        // Just off the line is outside with a small epsilon, and inside with a larger one.
+        let off: Point = Point { x: 1.0, y: 1.000001 };
+        assert!( !line.contains_point( off, 0.0000001 ) );
+        assert!( line.contains_point( off, 0.00001 ) );
+
+        // Collinear but past the end.
+        assert!( !line.contains_point( Point { x: 4.0, y: 4.0 }, 0.000001 ) );
+        assert!( line.contains_point( Point { x: 3.0000001, y: 3.0000001 }, 0.000001 ) );
+
+        let point: Line = Line { start: Point { x: 1.0, y: 1.0 }, end: Point { x: 1.0, y: 1.0 } };
+        assert!( point.contains_point( Point { x: 1.0, y: 1.0 }, 0.0 ) );
+        assert!( !point.contains_point( Point { x: 1.0, y: 1.1 }, 0.01 ) );
+    }
+
+    #[test]
+    fn test_translate() {
+        let line: Line = Line::new( Point { x: 1.0, y: 2.0 }, Point { x: 4.0, y: 6.0 } );
+        let moved: Line = line.translate( -3.0, 0.5 );
+        assert!( moved.start == Point { x: -2.0, y: 2.5 } );
+        assert!( moved.end == Point { x: 1.0, y: 6.5 } );
+        assert!( moved.length() == line.length() );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let line: Line = Line::new( Point { x: 1.0, y: 2.0 }, Point { x: 4.0, y: 6.0 } );
+        let about: Point = Point { x: 1.0, y: 1.0 };
+        let rotated: Line = line.rotate( std::f64::consts::FRAC_PI_2, &about );
+
+        // A quarter turn around (1, 1) takes (1, 2) to (0, 1) and (4, 6) to (-4, 4).
+        assert!( (rotated.start.x - 0.0).abs() < 1e-12 && (rotated.start.y - 1.0).abs() < 1e-12 );
+        assert!( (rotated.end.x + 4.0).abs() < 1e-12 && (rotated.end.y - 4.0).abs() < 1e-12 );
+        assert!( (rotated.length() - line.length()).abs() < 1e-12 );
+    }
+
+    #[test]
+    fn test_intersection_point() {
+        // Crossing lines meet in the middle.
+        let line1: Line = Line::new( Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 4.0 } );
+        let line2: Line = Line::new( Point { x: 0.0, y: 4.0 }, Point { x: 4.0, y: 0.0 } );
+        assert_eq!( line1.intersection_point( &line2 ), Some( Point { x: 2.0, y: 2.0 } ) );
+
+        // A line ending on the other one meets it at its end.
+        let touching: Line = Line::new( Point { x: 1.0, y: 3.0 }, Point { x: 2.0, y: 2.0 } );
+        assert_eq!( line1.intersection_point( &touching ), Some( Point { x: 2.0, y: 2.0 } ) );
+
+        // Parallel lines, and lines that would only cross if they were longer, do not meet.
+        let parallel: Line = Line::new( Point { x: 0.0, y: 1.0 }, Point { x: 4.0, y: 5.0 } );
+        assert_eq!( line1.intersection_point( &parallel ), None );
+        let short: Line = Line::new( Point { x: 0.0, y: 4.0 }, Point { x: 1.0, y: 3.0 } );
+        assert_eq!( line1.intersection_point( &short ), None );
+
+        // Collinear lines meet in the middle of the overlap, and a point on the line at the point.
+        let collinear: Line = Line::new( Point { x: 2.0, y: 2.0 }, Point { x: 6.0, y: 6.0 } );
+        assert_eq!( line1.intersection_point( &collinear ), Some( Point { x: 3.0, y: 3.0 } ) );
+        let point: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 } );
+        assert_eq!( line1.intersection_point( &point ), Some( Point { x: 1.0, y: 1.0 } ) );
+    }
+
+    #[test]
+    fn test_intersection_point_cases() {
+        // The crossing lines of test_intersect meet in the middle, in either order.
+        let line1: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 5.0, y: 5.0 } );
+        let line2: Line = Line::new( Point { x: 1.0, y: 5.0 }, Point { x: 5.0, y: 1.0 } );
+        assert_eq!( line1.intersection_point( &line2 ), Some( Point { x: 3.0, y: 3.0 } ) );
+        assert_eq!( line2.intersection_point( &line1 ), Some( Point { x: 3.0, y: 3.0 } ) );
+
+        // A T-junction meets where the stem ends on the bar.
+        let bar: Line = Line::new( Point { x: 1.0, y: 3.0 }, Point { x: 5.0, y: 3.0 } );
+        let stem: Line = Line::new( Point { x: 3.0, y: 0.0 }, Point { x: 3.0, y: 3.0 } );
+        assert_eq!( bar.intersection_point( &stem ), Some( Point { x: 3.0, y: 3.0 } ) );
+        assert_eq!( stem.intersection_point( &bar ), Some( Point { x: 3.0, y: 3.0 } ) );
+
+        // Parallel lines do not meet.
+        let below: Line = Line::new( Point { x: 1.0, y: 2.0 }, Point { x: 5.0, y: 2.0 } );
+        assert_eq!( bar.intersection_point( &below ), None );
+        assert!( !bar.intersects( &below ) );
+    }
+
+    #[test]
+    fn test_intersection_point_consistent() {
+        // Lines that intersect have an intersection point, which is on both lines.
+        let mut random: Random = Random::new( 7 );
+        let mut point = || Point { x: random.range( -5.0, 5.0 ), y: random.range( -5.0, 5.0 ) };
+        for _ in 0..1000 {
+            let line1: Line = Line::new( point(), point() );
+            let line2: Line = Line::new( point(), point() );
+            let intersection: Option<Point> = line1.intersection_point( &line2 );
+            assert_eq!( intersection.is_some(), line1.intersects( &line2 ) );
+            if let Some( p ) = intersection {
+                for line in [ &line1, &line2 ] {
+                    let distance: f64 = p.distance( &line.start ) + p.distance( &line.end ) - line.length();
+                    assert!( distance < 1e-9, "{p} is not on the line" );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_t_for_point() {
+        let line: Line = Line::new( Point { x: 1.0, y: 1.0 }, Point { x: 5.0, y: 3.0 } );
+
+        // The middle is at 0.5 and the ends at 0 and 1.
+        assert!( (line.t_for_point( &Point { x: 3.0, y: 2.0 } ).unwrap() - 0.5).abs() < 1e-12 );
+        assert_eq!( line.t_for_point( &line.start ), Some( 0.0 ) );
+        assert_eq!( line.t_for_point( &line.end ), Some( 1.0 ) );
+
+        // The inverse of point_at.
+        let t: f64 = line.t_for_point( &line.point_at( 0.3 ) ).unwrap();
+        assert!( (t - 0.3).abs() < 1e-12 );
+
+        // Points beside the line or past its ends are not on it.
+        assert_eq!( line.t_for_point( &Point { x: 3.0, y: 2.1 } ), None );
+        assert_eq!( line.t_for_point( &line.point_at( 1.1 ) ), None );
+        assert_eq!( line.t_for_point( &line.point_at( -0.1 ) ), None );
+
+        // A line without length only has its point.
+        let point: Line = Line::new( line.start, line.start );
+        assert_eq!( point.t_for_point( &line.start ), Some( 0.0 ) );
+        assert_eq!( point.t_for_point( &line.end ), None );
+    }
+
+}
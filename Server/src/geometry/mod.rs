@@ -1,5 +1,11 @@
-
 pub mod point;
 pub mod line;
 pub mod angular_shape;
-pub mod traits;
\ No newline at end of file
+pub mod traits;
+pub mod random;
+pub mod circle;
+pub mod algorithms;
+pub mod error;
+pub mod units;
+pub mod ellipse;
+pub mod triangle;
\ No newline at end of file
@@ -1,4 +1,9 @@
 use std::fmt::Display;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// The number of decimals shown when a point is displayed. Debug shows the
+// exact coordinates.
+pub const DISPLAY_DECIMALS: usize = 6;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Point {
@@ -6,8 +11,146 @@ pub struct Point {
     pub y: f64,
 }
 
+// Implement the Point struct.
+impl Point {
+
+    // Return the point rotated counterclockwise by the given angle in radians
+    // around another point.
+    pub fn rotate( &self, radians: f64, about: &Point ) -> Point {
+        let (sin, cos) = radians.sin_cos();
+        let d: Point = *self - *about;
+        return *about + Point { x: d.x * cos - d.y * sin, y: d.x * sin + d.y * cos };
+    }
+
+    // Format the point with the coordinates rounded to the number of decimals.
+    // Trailing zeros are left out, so 1.0000000000000002 is shown as 1.
+    pub fn fmt_rounded( &self, decimals: usize ) -> String {
+        return format!("(x: {}, y:{})", round( self.x, decimals ), round( self.y, decimals ));
+    }
+
+    // Return the distance to another point.
+    pub fn distance( &self, other: &Point ) -> f64 {
+        return (self.x - other.x).hypot( self.y - other.y );
+    }
+
+    // Return the squared distance to another point. Cheaper than distance
+    // when distances are only compared with each other.
+    pub fn distance_squared( &self, other: &Point ) -> f64 {
+        let dx: f64 = self.x - other.x;
+        let dy: f64 = self.y - other.y;
+        return dx * dx + dy * dy;
+    }
+}
+
+// Format a number rounded to the number of decimals, without trailing zeros.
+fn round( value: f64, decimals: usize ) -> String {
+    let text: String = format!("{:.*}", decimals, value);
+    let text: &str = if text.contains( '.' ) { text.trim_end_matches( '0' ).trim_end_matches( '.' ) } else { &text };
+    return if text == "-0" { "0".to_string() } else { text.to_string() };
+}
+
+// Points are displayed with DISPLAY_DECIMALS decimals.
 impl Display for Point {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "(x: {}, y:{})", self.x, self.y)
+        write!(f, "{}", self.fmt_rounded( DISPLAY_DECIMALS ))
+    }
+}
+
+// Points can be added and subtracted like vectors.
+impl Add for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Point { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Point { x: self.x - other.x, y: self.y - other.y }
     }
-}
\ No newline at end of file
+}
+
+// Multiplying a point by a number scales it like a vector.
+impl Mul<f64> for Point {
+    type Output = Point;
+    fn mul(self, scale: f64) -> Point {
+        Point { x: self.x * scale, y: self.y * scale }
+    }
+}
+
+// Negating a point mirrors it through the origin.
+impl Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Point { x: -self.x, y: -self.y }
+    }
+}
+
+
+// Unit tests for Point.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        assert_eq!( Point { x: 1.0, y: 2.0 } + Point { x: 3.0, y: 4.0 }, Point { x: 4.0, y: 6.0 } );
+    }
+
+    #[test]
+    fn test_sub() {
+        assert_eq!( Point { x: 3.0, y: 4.0 } - Point { x: 1.0, y: 2.0 }, Point { x: 2.0, y: 2.0 } );
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!( Point { x: 1.0, y: 2.0 } * 3.0, Point { x: 3.0, y: 6.0 } );
+    }
+
+    #[test]
+    fn test_neg() {
+        assert_eq!( -Point { x: 1.0, y: 2.0 }, Point { x: -1.0, y: -2.0 } );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let rotated: Point = Point { x: 2.0, y: 1.0 }.rotate( std::f64::consts::FRAC_PI_2, &Point { x: 1.0, y: 1.0 } );
+        assert!( (rotated.x - 1.0).abs() < 0.000001 );
+        assert!( (rotated.y - 2.0).abs() < 0.000001 );
+    }
+
+    #[test]
+    fn test_distance() {
+        let a: Point = Point { x: 1.0, y: 2.0 };
+        let b: Point = Point { x: 4.0, y: 6.0 };
+        assert_eq!( a.distance( &b ), 5.0 );
+        assert_eq!( b.distance( &a ), 5.0 );
+        assert_eq!( a.distance( &a ), 0.0 );
+    }
+
+    #[test]
+    fn test_distance_squared() {
+        // Points in the same quadrant.
+        let a: Point = Point { x: 1.0, y: 2.0 };
+        let b: Point = Point { x: 4.0, y: 6.0 };
+        assert_eq!( a.distance_squared( &b ), 25.0 );
+        assert_eq!( a.distance_squared( &a ), 0.0 );
+
+        // Points in opposite quadrants.
+        let c: Point = Point { x: -2.0, y: -2.0 };
+        assert_eq!( c.distance_squared( &b ), 100.0 );
+        assert_eq!( b.distance_squared( &c ), 100.0 );
+        assert_eq!( c.distance( &b ), 10.0 );
+        assert_eq!( c.distance( &c ), 0.0 );
+    }
+
+    #[test]
+    fn test_fmt_rounded() {
+        let noisy: Point = Point { x: 1.0000000000000002, y: 0.1 + 0.2 };
+        assert_eq!( noisy.to_string(), "(x: 1, y:0.3)" );
+        assert_eq!( format!("{:?}", noisy), "Point { x: 1.0000000000000002, y: 0.30000000000000004 }" );
+        assert_eq!( Point { x: -0.0000001, y: 2.5 }.fmt_rounded( 3 ), "(x: 0, y:2.5)" );
+        assert_eq!( Point { x: 1234.56789, y: -3.0 }.fmt_rounded( 2 ), "(x: 1234.57, y:-3)" );
+        assert_eq!( Point { x: 1.5, y: 2.0 }.fmt_rounded( 0 ), "(x: 2, y:2)" );
+    }
+}
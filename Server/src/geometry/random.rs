@@ -0,0 +1,62 @@
+// A small deterministic pseudo random number generator (SplitMix64).
+// It is not suitable for cryptography, but the same seed always gives the
+// same numbers, which makes it useful for sampling and tests.
+// https://prng.di.unimi.it/splitmix64.c
+pub struct Random {
+    state: u64
+}
+
+// Implement the Random struct.
+impl Random {
+
+    // Create a new generator from a seed.
+    pub fn new( seed: u64 ) -> Random {
+        Random { state: seed }
+    }
+
+    // Get the next random 64-bit number.
+    pub fn next_u64( &mut self ) -> u64 {
+        self.state = self.state.wrapping_add( 0x9E3779B97F4A7C15 );
+        let mut z: u64 = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul( 0xBF58476D1CE4E5B9 );
+        z = (z ^ (z >> 27)).wrapping_mul( 0x94D049BB133111EB );
+        return z ^ (z >> 31);
+    }
+
+    // Get the next random number in range [0, 1).
+    pub fn next_f64( &mut self ) -> f64 {
+
+        // Use the top 53 bits, which is the precision of f64.
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    // Get the next random number in range [min, max).
+    pub fn range( &mut self, min: f64, max: f64 ) -> f64 {
+        return min + (max - min) * self.next_f64();
+    }
+}
+
+
+// Unit tests for Random.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let mut first: Random = Random::new( 7 );
+        let mut second: Random = Random::new( 7 );
+        for _ in 0..100 {
+            assert!( first.next_u64() == second.next_u64() );
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let mut random: Random = Random::new( 1 );
+        for _ in 0..1000 {
+            let value: f64 = random.range( -2.0, 3.0 );
+            assert!( (-2.0..3.0).contains( &value ) );
+        }
+    }
+}
@@ -1,5 +1,20 @@
+use crate::geometry::point::Point;
+
 pub trait AreaCalculatable {
     
     // Returns the area of the shape, or -1.0 if the shape is not valid.
     fn area(&self) -> f64;
-}
\ No newline at end of file
+}
+
+pub trait BoundingBox {
+
+    // Returns the axis-aligned bounding box of the shape as (min corner, max corner),
+    // or None if the shape has no extent to bound.
+    fn bounding_box(&self) -> Option<(Point, Point)>;
+}
+
+pub trait PerimeterCalculatable {
+
+    // Returns the length of the outline of the shape, or -1.0 if the shape is not valid.
+    fn perimeter(&self) -> f64;
+}
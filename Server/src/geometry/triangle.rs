@@ -0,0 +1,162 @@
+use crate::geometry::algorithms;
+use crate::geometry::angular_shape::ShapeError;
+use crate::geometry::error::DEGENERATE_TOLERANCE;
+use crate::geometry::point::Point;
+use crate::geometry::traits::{AreaCalculatable, PerimeterCalculatable};
+
+// A triangle, defined by its corners or only by the lengths of its sides.
+// A triangle from corners also knows its sides, but a triangle from sides
+// has no position, so it has no corners.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    corners: Option<[Point; 3]>,
+    sides: [f64; 3],
+}
+
+// Implement the Triangle struct.
+impl Triangle {
+
+    // Create a triangle from its corners. The corners can be on one line,
+    // which makes a degenerate triangle.
+    pub fn from_points( a: Point, b: Point, c: Point ) -> Triangle {
+        return Triangle {
+            corners: Some( [a, b, c] ),
+            sides: [a.distance( &b ), b.distance( &c ), c.distance( &a )],
+        };
+    }
+
+    // Create a triangle from the lengths of its sides. Fails if a side is
+    // negative or not finite, or if a side is longer than the other two
+    // together, so that the sides do not meet.
+    // https://en.wikipedia.org/wiki/Triangle_inequality
+    pub fn from_sides( a: f64, b: f64, c: f64 ) -> Result<Triangle, ShapeError> {
+        let sides: [f64; 3] = [a, b, c];
+        if sides.iter().any( |side| !side.is_finite() || *side < 0.0 ) {
+            return Err( ShapeError::NotValidShape( format!("The sides {}, {} and {} must be positive numbers.", a, b, c).into() ) );
+        }
+
+        // Sides that just meet make a flat triangle, which is allowed.
+        let longest: f64 = a.max( b ).max( c );
+        if 2.0 * longest > a + b + c + DEGENERATE_TOLERANCE * longest {
+            return Err( ShapeError::NotValidShape( format!("The sides {}, {} and {} do not make a triangle.", a, b, c).into() ) );
+        }
+
+        return Ok( Triangle { corners: None, sides } );
+    }
+
+    // Get the corners of the triangle, or None if it was made from sides.
+    pub fn corners( &self ) -> Option<[Point; 3]> {
+        return self.corners;
+    }
+
+    // Get the lengths of the sides of the triangle. For a triangle from
+    // corners a, b and c the sides are ab, bc and ca.
+    pub fn sides( &self ) -> [f64; 3] {
+        return self.sides;
+    }
+
+    // Get the perimeter of the triangle.
+    pub fn perimeter( &self ) -> f64 {
+        return self.sides.iter().sum();
+    }
+
+    // Check if the triangle is flat, with its corners on one line. The area
+    // is compared to the square of the longest side, so the size of the
+    // triangle does not matter.
+    pub fn is_degenerate( &self ) -> bool {
+        let longest: f64 = self.sides.iter().fold( 0.0, |longest, side| longest.max( *side ) );
+        return self.area() <= DEGENERATE_TOLERANCE * longest * longest;
+    }
+}
+
+// Implement the AreaCalculatable trait for the Triangle struct.
+impl AreaCalculatable for Triangle {
+
+    // Returns the area of the triangle. The area of a triangle from corners
+    // is half of the cross product of two sides. The area of a triangle from
+    // sides uses Heron's formula in the form that is stable for thin triangles.
+    // https://en.wikipedia.org/wiki/Heron%27s_formula#Numerical_stability
+    fn area(&self) -> f64 {
+        if let Some( corners ) = &self.corners {
+            return algorithms::signed_area( corners ).abs();
+        }
+
+        // Sort the sides from the longest, a >= b >= c. The parentheses matter.
+        let mut sides: [f64; 3] = self.sides;
+        sides.sort_by( |x, y| y.total_cmp( x ) );
+        let [a, b, c] = sides;
+        let product: f64 = (a + (b + c)) * (c - (a - b)) * (c + (a - b)) * (a + (b - c));
+
+        // Rounding can make the product of a flat triangle slightly negative.
+        return product.max( 0.0 ).sqrt() / 4.0;
+    }
+}
+
+// Implement the PerimeterCalculatable trait for the Triangle struct.
+impl PerimeterCalculatable for Triangle {
+
+    // Returns the sum of the sides of the triangle.
+    fn perimeter(&self) -> f64 {
+        return Triangle::perimeter( self );
+    }
+}
+
+
+// Unit tests for Triangle.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_points() {
+        let triangle: Triangle = Triangle::from_points( Point { x: 0.0, y: 0.0 }, Point { x: 4.0, y: 0.0 }, Point { x: 0.0, y: 3.0 } );
+        assert!( (triangle.area() - 6.0).abs() < 0.000000001 );
+        assert_eq!( triangle.sides(), [4.0, 5.0, 3.0] );
+        assert_eq!( triangle.perimeter(), 12.0 );
+        assert!( triangle.corners().is_some() );
+        assert!( !triangle.is_degenerate() );
+    }
+
+    #[test]
+    fn test_from_sides() {
+        let triangle: Triangle = Triangle::from_sides( 3.0, 4.0, 5.0 ).unwrap();
+        assert!( (triangle.area() - 6.0).abs() < 0.000000001 );
+        assert!( triangle.corners().is_none() );
+
+        // Sides that do not meet are not a triangle.
+        assert!( Triangle::from_sides( 1.0, 2.0, 4.0 ).is_err() );
+        assert!( Triangle::from_sides( -3.0, 4.0, 5.0 ).is_err() );
+        assert!( Triangle::from_sides( 3.0, f64::NAN, 5.0 ).is_err() );
+    }
+
+    #[test]
+    fn test_area_formulas_agree() {
+        let triangles: [[Point; 3]; 4] = [
+            [Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 0.0 }, Point { x: 0.5, y: 0.8660254037844386 }],
+            [Point { x: -2.5, y: 1.0 }, Point { x: 7.0, y: -3.0 }, Point { x: 1.0, y: 6.5 }],
+            [Point { x: 100.0, y: 100.0 }, Point { x: 101.0, y: 130.0 }, Point { x: 99.0, y: 102.0 }],
+            [Point { x: 0.0, y: 0.0 }, Point { x: 10.0, y: 0.1 }, Point { x: 20.0, y: 0.0 }],
+        ];
+        for [a, b, c] in triangles {
+            let from_points: Triangle = Triangle::from_points( a, b, c );
+            let [ab, bc, ca] = from_points.sides();
+            let from_sides: Triangle = Triangle::from_sides( ab, bc, ca ).unwrap();
+            assert!( (from_points.area() - from_sides.area()).abs() < 0.000000001, "{} != {}", from_points.area(), from_sides.area() );
+        }
+    }
+
+    #[test]
+    fn test_is_degenerate() {
+        let flat: Triangle = Triangle::from_points( Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 }, Point { x: 3.0, y: 3.0 } );
+        assert!( flat.is_degenerate() );
+        assert_eq!( flat.area(), 0.0 );
+
+        let point: Triangle = Triangle::from_points( Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 } );
+        assert!( point.is_degenerate() );
+
+        // Sides that just meet are a flat triangle.
+        let flat: Triangle = Triangle::from_sides( 1.0, 2.0, 3.0 ).unwrap();
+        assert!( flat.is_degenerate() );
+        assert!( !Triangle::from_sides( 2.0, 2.0, 3.0 ).unwrap().is_degenerate() );
+    }
+}
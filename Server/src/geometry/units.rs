@@ -0,0 +1,269 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+// The units of length that coordinates can be measured in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Kilometer,
+    Foot,
+}
+
+// The size of an acre in square meters. An acre is not the square of a unit
+// of length, so it is only used when converting areas to acres.
+pub const ACRE_IN_SQUARE_METERS: f64 = 4046.8564224;
+
+// The units that areas can be given in: the square of a unit of length, or
+// the acre.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AreaUnit {
+    Square( Unit ),
+    Acre,
+}
+
+// Implement the Unit enum.
+impl Unit {
+
+    // Get all the units, from the shortest metric unit to the foot.
+    pub fn all() -> [Unit; 5] {
+        return [Unit::Millimeter, Unit::Centimeter, Unit::Meter, Unit::Kilometer, Unit::Foot];
+    }
+
+    // Find the unit with the symbol, like "cm", or None if there is none.
+    pub fn from_symbol( symbol: &str ) -> Option<Unit> {
+        return Unit::all().into_iter().find( |unit| unit.symbol() == symbol );
+    }
+
+    // Get the length of the unit in meters.
+    pub fn meters( &self ) -> f64 {
+        return match self {
+            Unit::Millimeter => 0.001,
+            Unit::Centimeter => 0.01,
+            Unit::Meter => 1.0,
+            Unit::Kilometer => 1000.0,
+            Unit::Foot => 0.3048,
+        };
+    }
+
+    // Get the symbol of the unit, like "cm".
+    pub fn symbol( &self ) -> &'static str {
+        return match self {
+            Unit::Millimeter => "mm",
+            Unit::Centimeter => "cm",
+            Unit::Meter => "m",
+            Unit::Kilometer => "km",
+            Unit::Foot => "ft",
+        };
+    }
+}
+
+// Implement the AreaUnit enum.
+impl AreaUnit {
+
+    // Get all the units of area, the squares of the units of length and the acre.
+    pub fn all() -> [AreaUnit; 6] {
+        let [mm, cm, m, km, ft] = Unit::all().map( AreaUnit::Square );
+        return [mm, cm, m, km, ft, AreaUnit::Acre];
+    }
+
+    // Find the unit of area with the symbol, like "cm2" or "acre", or None if there is none.
+    pub fn from_symbol( symbol: &str ) -> Option<AreaUnit> {
+        return AreaUnit::all().into_iter().find( |unit| unit.symbol() == symbol );
+    }
+
+    // Get the symbol of the unit of area, like "cm2".
+    pub fn symbol( &self ) -> String {
+        return match self {
+            AreaUnit::Square( unit ) => format!( "{}2", unit.symbol() ),
+            AreaUnit::Acre => "acre".to_string(),
+        };
+    }
+
+    // Convert an area in square meters to the unit.
+    pub fn convert_square_meters( &self, square_meters: f64 ) -> f64 {
+        return match self {
+            AreaUnit::Square( unit ) => Area::new( square_meters, Unit::Meter ).to( *unit ).value,
+            AreaUnit::Acre => square_meters / ACRE_IN_SQUARE_METERS,
+        };
+    }
+}
+
+// A length in a unit.
+//
+// Lengths and areas are separate types, so a length can not be added to an
+// area. Lengths in different units can be added, the result is in the unit
+// of the left side.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Length {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+// An area in a unit squared, for example square centimeters for Unit::Centimeter.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Area {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+// Implement the Length struct.
+impl Length {
+
+    // Create a new length.
+    pub fn new( value: f64, unit: Unit ) -> Length {
+        return Length { value, unit };
+    }
+
+    // Convert the length to another unit.
+    pub fn to( &self, unit: Unit ) -> Length {
+        return Length { value: self.value * self.unit.meters() / unit.meters(), unit };
+    }
+}
+
+// Implement the Area struct.
+impl Area {
+
+    // Create a new area in the unit squared.
+    pub fn new( value: f64, unit: Unit ) -> Area {
+        return Area { value, unit };
+    }
+
+    // Convert the area to another unit squared.
+    pub fn to( &self, unit: Unit ) -> Area {
+        let factor: f64 = self.unit.meters() / unit.meters();
+        return Area { value: self.value * factor * factor, unit };
+    }
+}
+
+// Lengths are added and subtracted in the unit of the left side.
+impl Add for Length {
+    type Output = Length;
+    fn add( self, other: Length ) -> Length {
+        return Length::new( self.value + other.to( self.unit ).value, self.unit );
+    }
+}
+impl Sub for Length {
+    type Output = Length;
+    fn sub( self, other: Length ) -> Length {
+        return Length::new( self.value - other.to( self.unit ).value, self.unit );
+    }
+}
+
+// A length times a length is an area, in the unit of the left side.
+impl Mul for Length {
+    type Output = Area;
+    fn mul( self, other: Length ) -> Area {
+        return Area::new( self.value * other.to( self.unit ).value, self.unit );
+    }
+}
+
+// Scale a length.
+impl Mul<f64> for Length {
+    type Output = Length;
+    fn mul( self, factor: f64 ) -> Length {
+        return Length::new( self.value * factor, self.unit );
+    }
+}
+
+// Areas are added and subtracted in the unit of the left side.
+impl Add for Area {
+    type Output = Area;
+    fn add( self, other: Area ) -> Area {
+        return Area::new( self.value + other.to( self.unit ).value, self.unit );
+    }
+}
+impl Sub for Area {
+    type Output = Area;
+    fn sub( self, other: Area ) -> Area {
+        return Area::new( self.value - other.to( self.unit ).value, self.unit );
+    }
+}
+
+// Scale an area.
+impl Mul<f64> for Area {
+    type Output = Area;
+    fn mul( self, factor: f64 ) -> Area {
+        return Area::new( self.value * factor, self.unit );
+    }
+}
+
+// Write lengths like "2 cm".
+impl fmt::Display for Length {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f, "{} {}", self.value, self.unit.symbol() )
+    }
+}
+
+// Write areas like "4 cm²".
+impl fmt::Display for Area {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        write!( f, "{} {}²", self.value, self.unit.symbol() )
+    }
+}
+
+
+// Unit tests for the units.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Check that two values are the same within rounding errors.
+    fn assert_close( a: f64, b: f64 ) {
+        assert!( (a - b).abs() <= 0.000000001 * b.abs().max( 1.0 ), "{a} != {b}" );
+    }
+
+    #[test]
+    fn test_length_to() {
+        assert_close( Length::new( 150.0, Unit::Centimeter ).to( Unit::Meter ).value, 1.5 );
+        assert_close( Length::new( 2.0, Unit::Kilometer ).to( Unit::Millimeter ).value, 2000000.0 );
+        assert_eq!( Length::new( 2.0, Unit::Kilometer ).to( Unit::Millimeter ).unit, Unit::Millimeter );
+    }
+
+    #[test]
+    fn test_area_to() {
+        let area: Area = Area::new( 10000.0, Unit::Centimeter );
+        assert_close( area.to( Unit::Meter ).value, 1.0 );
+        assert_close( area.to( Unit::Meter ).to( Unit::Centimeter ).value, 10000.0 );
+        assert_close( Area::new( 1.0, Unit::Kilometer ).to( Unit::Meter ).value, 1000000.0 );
+        assert_close( Area::new( 100.0, Unit::Meter ).to( Unit::Foot ).value, 1076.3910416709722 );
+    }
+
+    #[test]
+    fn test_from_symbol() {
+        for unit in Unit::all() {
+            assert_eq!( Unit::from_symbol( unit.symbol() ), Some( unit ) );
+        }
+        assert_eq!( Unit::from_symbol( "ft" ), Some( Unit::Foot ) );
+        assert_eq!( Unit::from_symbol( "M" ), None );
+        assert_eq!( Unit::from_symbol( "acre" ), None );
+    }
+
+    #[test]
+    fn test_area_unit() {
+        for unit in AreaUnit::all() {
+            assert_eq!( AreaUnit::from_symbol( &unit.symbol() ), Some( unit ) );
+        }
+        assert_eq!( AreaUnit::from_symbol( "ft2" ), Some( AreaUnit::Square( Unit::Foot ) ) );
+        assert_eq!( AreaUnit::from_symbol( "ft" ), None );
+        assert_eq!( AreaUnit::from_symbol( "hectare" ), None );
+        assert_close( AreaUnit::Square( Unit::Foot ).convert_square_meters( 100.0 ), 1076.3910416709722 );
+        assert_close( AreaUnit::Acre.convert_square_meters( ACRE_IN_SQUARE_METERS * 2.0 ), 2.0 );
+    }
+
+    #[test]
+    fn test_operations() {
+        let sum: Length = Length::new( 1.0, Unit::Meter ) + Length::new( 50.0, Unit::Centimeter );
+        assert_eq!( sum.unit, Unit::Meter );
+        assert_close( sum.value, 1.5 );
+        assert_close( (Length::new( 1.0, Unit::Meter ) - Length::new( 50.0, Unit::Centimeter )).value, 0.5 );
+
+        let area: Area = Length::new( 2.0, Unit::Meter ) * Length::new( 50.0, Unit::Centimeter );
+        assert_eq!( area.unit, Unit::Meter );
+        assert_close( area.value, 1.0 );
+        assert_close( (area + Area::new( 5000.0, Unit::Centimeter )).value, 1.5 );
+        assert_close( (area * 3.0 - Area::new( 1.0, Unit::Meter )).value, 2.0 );
+        assert_eq!( area.to_string(), "1 m²" );
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use tracing::info;
+use tracing_subscriber::{filter::LevelFilter, reload};
+
+use crate::webserver::{
+    http_method::HttpMethod, http_status::HttpStatus, json::JsonValue, request::Request,
+    response::Response, routehandler::RouteHandler,
+};
+
+/// The level names accepted when changing a log level.
+const LEVEL_NAMES: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// A log level filter that can be changed while the program is running.
+pub trait LevelHandle: Send + Sync {
+
+    /// Sets the level of the filter.
+    /// # Returns
+    /// `true` if the level was changed.
+    fn set_level(&self, level: LevelFilter) -> bool;
+
+    /// Gets the current level of the filter, or `None` if the filter no longer exists.
+    fn level(&self) -> Option<LevelFilter>;
+}
+
+/// Implement LevelHandle for the reload handles of level filters.
+impl<S: 'static> LevelHandle for reload::Handle<LevelFilter, S> {
+    fn set_level(&self, level: LevelFilter) -> bool {
+        self.modify( |filter| *filter = level ).is_ok()
+    }
+
+    fn level(&self) -> Option<LevelFilter> {
+        self.clone_current()
+    }
+}
+
+/// The log levels of the console and the log file.
+/// Cloning gives another handle to the same levels.
+#[derive(Clone)]
+pub struct LogLevels {
+    console: Arc<dyn LevelHandle>,
+    file: Arc<dyn LevelHandle>,
+}
+
+/// Implement the LogLevels struct.
+impl LogLevels {
+
+    /// Creates new LogLevels from the handles of the console and log file filters.
+    pub fn new( console: impl LevelHandle + 'static, file: impl LevelHandle + 'static ) -> LogLevels {
+        LogLevels {
+            console: Arc::new( console ),
+            file: Arc::new( file ),
+        }
+    }
+
+    /// Gets the current levels as a JSON object with `console` and `file` members.
+    pub fn to_json(&self) -> JsonValue {
+        let level_name = |handle: &Arc<dyn LevelHandle>| match handle.level() {
+            Some( level ) => JsonValue::from( level.to_string() ),
+            None => JsonValue::Null,
+        };
+        JsonValue::Object( vec![
+            ("console".to_string(), level_name( &self.console )),
+            ("file".to_string(), level_name( &self.file )),
+        ] )
+    }
+
+    /// Changes the levels from a JSON object.
+    /// `level` changes both the console and the log file, `console` and `file` change only one of them.
+    ///
+    /// # Arguments
+    /// * `body` - The JSON text, like `{"level": "debug"}`.
+    ///
+    /// # Returns
+    /// An error message if the JSON or any of the levels is not valid. Nothing is changed in that case.
+    pub fn update_from_json(&self, body: &str) -> Result<(), String> {
+        let json = JsonValue::parse( body ).map_err( |e| e.to_string() )?;
+
+        // Parse all levels before changing any of them.
+        let level = parse_level( &json, "level" )?;
+        let console = parse_level( &json, "console" )?.or( level );
+        let file = parse_level( &json, "file" )?.or( level );
+        if console.is_none() && file.is_none() {
+            return Err( "Expected a 'level', 'console' or 'file' member.".to_string() );
+        }
+
+        // Apply the new levels.
+        if let Some( console ) = console {
+            self.console.set_level( console );
+        }
+        if let Some( file ) = file {
+            self.file.set_level( file );
+        }
+        info!("Log levels changed to {}", self.to_json());
+        Ok(())
+    }
+}
+
+/// Parse the level in the given member of a JSON object.
+/// # Returns
+/// `None` if there is no such member, or an error message if the level is not valid.
+fn parse_level( json: &JsonValue, member: &str ) -> Result<Option<LevelFilter>, String> {
+    let Some( value ) = json.get( member ) else {
+        return Ok( None );
+    };
+    let name = value.as_str().map( str::to_lowercase ).unwrap_or_default();
+    if !LEVEL_NAMES.contains( &name.as_str() ) {
+        return Err( format!("Invalid level {} for '{}', expected one of: {}.", value, member, LEVEL_NAMES.join(", ")) );
+    }
+    Ok( Some( name.parse().unwrap() ) )
+}
+
+/// Creates the routes for reading and changing the log levels at runtime:
+/// `GET /admin/log-level` and `PUT /admin/log-level`. Trace logging writes out whole requests,
+/// so the routes are only for administrators, see `RouteHandler::with_admin_only`.
+///
+/// # Arguments
+/// * `levels` - The log levels the routes read and change.
+pub fn log_level_routes( levels: LogLevels ) -> Vec<RouteHandler> {
+    let get_levels = levels.clone();
+    vec![
+        RouteHandler::new(
+            HttpMethod::GET,
+            "/admin/log-level",
+            Arc::new( move |_request: Request| Response::json( HttpStatus::Ok, &get_levels.to_json() ) )
+        ).with_name("get-log-level").with_description("Reports the log levels of the console and the log file.").with_admin_only(),
+        RouteHandler::new(
+            HttpMethod::PUT,
+            "/admin/log-level",
+            Arc::new( move |request: Request| match levels.update_from_json( &request.body ) {
+                Ok(()) => Response::json( HttpStatus::Ok, &levels.to_json() ),
+                Err( message ) => Response::json(
+                    HttpStatus::BadRequest,
+                    &JsonValue::Object( vec![ ("error".to_string(), JsonValue::from( message )) ] )
+                ),
+            })
+        ).with_name("set-log-level").with_description("Changes the log levels of the console and the log file.").with_admin_only(),
+    ]
+}
+
+
+// Unit tests for LogLevels.
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Mutex;
+
+    use tracing::{debug, field::{Field, Visit}, Event, Subscriber};
+    use tracing_subscriber::{layer::Context, prelude::*, Layer, Registry};
+
+    use super::*;
+    use crate::webserver::webserver::WebServer;
+
+    /// A layer that captures the messages of the events.
+    struct CaptureLayer( Arc<Mutex<Vec<String>>> );
+
+    impl<S: Subscriber> Layer<S> for CaptureLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            struct MessageVisitor( String );
+            impl Visit for MessageVisitor {
+                fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                    if field.name() == "message" {
+                        self.0 = format!("{:?}", value);
+                    }
+                }
+            }
+            let mut visitor = MessageVisitor( String::new() );
+            event.record( &mut visitor );
+            self.0.lock().unwrap().push( visitor.0 );
+        }
+    }
+
+    /// Create a request with a body.
+    fn request( method: &str, body: &str ) -> Request {
+        let mut request = Request::new( &vec![ format!("{method} /admin/log-level HTTP/1.1") ] );
+        request.body = body.to_string();
+        request
+    }
+
+    #[test]
+    fn test_update_level_at_runtime() {
+        let captured = Arc::new( Mutex::new( Vec::new() ) );
+        let (console_filter, console_handle) = reload::Layer::new( LevelFilter::INFO );
+        let (_file_filter, file_handle) = reload::Layer::<LevelFilter, Registry>::new( LevelFilter::INFO );
+        let subscriber = Registry::default()
+            .with( CaptureLayer( captured.clone() ).with_filter( console_filter ) );
+        let levels = LogLevels::new( console_handle, file_handle );
+
+        tracing::subscriber::with_default( subscriber, || {
+            debug!("before");
+            levels.update_from_json( r#"{"level": "debug"}"# ).unwrap();
+            debug!("after");
+        });
+
+        let captured = captured.lock().unwrap();
+        assert!( !captured.contains( &"before".to_string() ) );
+        assert!( captured.contains( &"after".to_string() ) );
+    }
+
+    #[test]
+    fn test_log_level_routes() {
+        let (_console_filter, console_handle) = reload::Layer::<LevelFilter, Registry>::new( LevelFilter::INFO );
+        let (_file_filter, file_handle) = reload::Layer::<LevelFilter, Registry>::new( LevelFilter::DEBUG );
+        let routes = log_level_routes( LogLevels::new( console_handle, file_handle ) );
+        let get = &routes[0].handler;
+        let put = &routes[1].handler;
+
+        // Levels are reported separately.
+        assert_eq!( get( request( "GET", "" ) ).body, r#"{"console":"info","file":"debug"}"# );
+
+        // A single layer can be changed.
+        let response = put( request( "PUT", r#"{"console": "WARN"}"# ) );
+        assert_eq!( response.status, HttpStatus::Ok );
+        assert_eq!( response.body, r#"{"console":"warn","file":"debug"}"# );
+
+        // Invalid levels are rejected without changing anything.
+        for body in [ r#"{"level": "loud"}"#, r#"{"level": 3}"#, r#"{"console": "info", "file": "x"}"#, "{}", "debug" ] {
+            assert_eq!( put( request( "PUT", body ) ).status, HttpStatus::BadRequest );
+        }
+        assert_eq!( get( request( "GET", "" ) ).body, r#"{"console":"warn","file":"debug"}"# );
+    }
+
+    #[test]
+    fn test_log_level_routes_admin_only() {
+        let (_console_filter, console_handle) = reload::Layer::<LevelFilter, Registry>::new( LevelFilter::INFO );
+        let (_file_filter, file_handle) = reload::Layer::<LevelFilter, Registry>::new( LevelFilter::INFO );
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        for route in log_level_routes( LogLevels::new( console_handle, file_handle ) ) {
+            assert!( route.admin_only );
+            assert!( server.add_route( route ) );
+        }
+
+        // Local clients need the token when local access is off.
+        server.set_admin_token( "s3cret" );
+        server.set_admin_local_access( false );
+        server.start().unwrap();
+        let send = |authorization: &str| {
+            let mut stream = TcpStream::connect( server.local_addr().unwrap() ).unwrap();
+            let body = r#"{"console": "trace"}"#;
+            write!( stream, "PUT /admin/log-level HTTP/1.1\r\n{authorization}Content-Length: {}\r\n\r\n{body}", body.len() ).unwrap();
+            let mut response = String::new();
+            stream.read_to_string( &mut response ).unwrap();
+            response
+        };
+        let denied = send( "" );
+        assert!( denied.starts_with( "HTTP/1.1 401 Unauthorized" ), "{denied}" );
+        let allowed = send( "Authorization: Bearer s3cret\r\n" );
+        assert!( allowed.ends_with( r#"{"console":"trace","file":"info"}"# ), "{allowed}" );
+        server.stop();
+    }
+}
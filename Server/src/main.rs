@@ -1,8 +1,11 @@
 mod geometry;
+mod logging;
+mod shape_routes;
 mod webserver;
 
-use webserver::{http_method::HttpMethod, http_status::HttpStatus, response::Response, webserver::WebServer};
+use webserver::{http_method::HttpMethod, http_status::HttpStatus, response::{BodyFormat, Response}, webserver::WebServer};
 use crate::webserver::routehandler::RouteHandler;
+use crate::logging::LogLevels;
 
 use tracing::{info, error};
 use tracing_subscriber::{
@@ -10,7 +13,8 @@ use tracing_subscriber::{
     fmt,
     layer::Layer,
     Registry, 
-    filter
+    filter,
+    reload
 };
 use std::sync::Arc;
 use std::io::Write;
@@ -22,13 +26,13 @@ use std::fs;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Setup logging.
-    setup_tracing();
+    let log_levels = setup_tracing();
 
     // Start the webserver on localhost 8080.
     let mut server = WebServer::new("localhost", "8080");
 
     // Add all routes to the server.
-    for route in define_routes() {
+    for route in define_routes( log_levels ) {
 
         // Add the route to the server.
         // Log route info before moving it.
@@ -43,8 +47,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Add the route listing the routes.
+    if !server.add_route_listing() {
+        error!("Failed to add the route listing route");
+        std::process::exit(1);
+    }
+
+    // Add the routes for recording requests. Recording is off until turned on.
+    if !server.add_recording_routes() {
+        error!("Failed to add the request recording routes");
+        std::process::exit(1);
+    }
+
+    // Add the plain text route listing in debug builds.
+    #[cfg(debug_assertions)]
+    if !server.add_debug_routes() {
+        error!("Failed to add the debug route listing route");
+        std::process::exit(1);
+    }
+
     // Start the server.
-    server.start();
+    server.start()?;
 
     // Wait for the user to press Enter to stop the server.
     println!("Press Enter to exit...");
@@ -61,7 +84,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 // Setup logging with tracing library.
-fn setup_tracing() {
+// Returns the log levels, which can be changed while the server is running.
+fn setup_tracing() -> LogLevels {
 
     // Log file access.
     let log_file = std::fs::OpenOptions::new()
@@ -70,6 +94,10 @@ fn setup_tracing() {
         .open("log.txt")
         .unwrap();
 
+    // The level filters are wrapped in reload layers so they can be changed at runtime.
+    let (console_filter, console_handle) = reload::Layer::new(filter::LevelFilter::INFO);
+    let (file_filter, file_handle) = reload::Layer::new(filter::LevelFilter::DEBUG);
+
     // Subscribe to the tracing events.
     let subcriber = Registry::default()
         .with(
@@ -77,24 +105,61 @@ fn setup_tracing() {
             fmt::Layer::new()
                 .compact()
                 .with_ansi(true)
-                .with_filter(filter::LevelFilter::INFO)
+                .with_filter(console_filter)
         )
         .with(
             // Debug messages and higher are written to the log file.
             fmt::Layer::new()
                 .with_writer(log_file)
                 .with_ansi(false)
-                .with_filter(filter::LevelFilter::DEBUG)
+                .with_filter(file_filter)
         );
     
     // Apply the subscriber.
     tracing::subscriber::set_global_default(subcriber).unwrap();
+
+    LogLevels::new(console_handle, file_handle)
+}
+
+/// The front page, relative to the directory the server is run in.
+const INDEX_PAGE: &str = "src/webserver/index.html";
+
+/// Serve an HTML page from a file.
+/// # Arguments
+/// * `path` - The path of the file.
+/// # Returns
+/// The page, or a 500 response if the file can not be read. The error is logged.
+fn serve_page( path: &str ) -> Response {
+    let response_body = match fs::read_to_string(path) {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Could not read the page '{}': {}", path, e);
+            return Response::from_message(
+                HttpStatus::InternalServerError,
+                "The page is not available.",
+                BodyFormat::Html
+            );
+        }
+    };
+    let length = response_body.len();
+    let response: Response = Response::new(
+        HttpStatus::Ok,
+        response_body,
+        vec![
+            ("Content-Type".to_string(), "text/html; charset=utf-8".to_string()),
+            ("Content-Length".to_string(), length.to_string()),
+        ]
+    );
+    info!("Response: {}", response);
+    response
 }
 
 /// Function to define all routes this web server will handle.
+/// # Arguments
+/// * `log_levels` - The log levels the admin routes can change.
 /// # Returns
 /// A list of routes.
-fn define_routes() -> Vec<RouteHandler> {
+fn define_routes( log_levels: LogLevels ) -> Vec<RouteHandler> {
 
     // Create a vector to hold the routes.
     let mut routes = Vec::new();
@@ -103,25 +168,36 @@ fn define_routes() -> Vec<RouteHandler> {
     routes.push(webserver::routehandler::RouteHandler::new(
         HttpMethod::GET,
         "/",
-        Arc::new(|_path| {
-
-            // Return the index.html file.
-            let response_body = fs::read_to_string("src/webserver/index.html").unwrap();
-            let length = response_body.len();
-            let response: Response = Response::new(
-                HttpStatus::Ok,
-                response_body,
-                vec![
-                    ("Content-Type".to_string(), "text/html; charset=utf-8".to_string()),
-                    ("Content-Length".to_string(), length.to_string()),
-                ]
-            );
-            info!("Response: {}", response);
-            
-            return response;
-        })
-    ) );
+        Arc::new(|_path| serve_page(INDEX_PAGE))
+    ).with_name("index").with_description("Serves the front page.") );
+
+    // Add the routes for changing the log levels at runtime.
+    routes.extend( logging::log_level_routes( log_levels ) );
+
+    // Add the routes for calculating with shapes.
+    routes.extend( shape_routes::shape_routes( shape_routes::DEFAULT_PRECISION ) );
 
     // Return the routes.
     routes
 }
+
+
+// Unit tests for the routes.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_page() {
+        let response = serve_page( INDEX_PAGE );
+        assert_eq!( response.status, HttpStatus::Ok );
+        assert!( response.body.contains( "<html" ) );
+    }
+
+    #[test]
+    fn test_serve_missing_page() {
+        let response = serve_page( "src/webserver/missing.html" );
+        assert_eq!( response.status, HttpStatus::InternalServerError );
+        assert!( response.body.contains( "The page is not available." ) );
+    }
+}
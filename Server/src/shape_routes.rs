@@ -0,0 +1,518 @@
+use std::sync::Arc;
+
+use crate::geometry::{algorithms, angular_shape::AngularShape, point::Point, traits::AreaCalculatable};
+use crate::geometry::units::{Area, AreaUnit, Unit};
+use crate::webserver::{
+    http_method::HttpMethod, http_status::HttpStatus, json::JsonValue, request::Request,
+    response::Response, routehandler::RouteHandler,
+};
+
+/// The number of decimal places the numbers in the responses are rounded to, unless the request
+/// asks for another precision.
+pub const DEFAULT_PRECISION: usize = 6;
+
+/// The most decimal places a request can ask for with `?precision=`.
+pub const MAX_PRECISION: usize = 12;
+
+/// Creates the routes for calculating with shapes: `POST /api/area`, `POST /convex-hull` and `POST /compare`.
+/// The numbers in the responses are rounded to the decimal places of the `precision` query
+/// parameter, from 0 to `MAX_PRECISION`, or to `default_precision`.
+///
+/// # Arguments
+/// * `default_precision` - The decimal places for requests without `?precision=`, like `DEFAULT_PRECISION`.
+pub fn shape_routes( default_precision: usize ) -> Vec<RouteHandler> {
+    vec![
+        RouteHandler::new(
+            HttpMethod::POST,
+            "/api/area",
+            Arc::new( move |request: Request| area( &request, default_precision ) )
+        ).consumes( &[ "application/json" ] )
+            .with_name("area")
+            .with_description("Calculates the area of a shape given as a JSON object with an array of corners, \
+                in the unit of ?unit= from coordinates in the unit of ?coord_unit=."),
+        RouteHandler::new(
+            HttpMethod::POST,
+            "/convex-hull",
+            Arc::new( move |request: Request| convex_hull( &request, default_precision ) )
+        ).consumes( &[ "application/json" ] )
+            .with_name("convex-hull")
+            .with_description("Finds the convex hull of a JSON array of points and its area."),
+        RouteHandler::new(
+            HttpMethod::POST,
+            "/compare",
+            Arc::new( move |request: Request| compare( &request, default_precision ) )
+        ).consumes( &[ "application/json" ] )
+            .with_name("compare")
+            .with_description("Compares the areas of two shapes and finds their overlap if they are convex."),
+    ]
+}
+
+/// Calculates the area of a shape in a JSON object like `{"corners": [{"x": 0, "y": 0}, ...]}`.
+/// The coordinates are in meters, or in the unit of length of the `coord_unit` query parameter,
+/// like `cm`. The area is in square meters, or in the unit of the `unit` query parameter, which
+/// is the square of a unit of length like `ft2`, or `acre`.
+///
+/// # Returns
+/// A JSON object with the `area` of the shape and its `unit`.
+/// 400 Bad Request if the body is not JSON or a unit or the precision is not valid, and 422
+/// Unprocessable Entity with the field-level `errors` if the body does not have the corners, or
+/// the corners are not a valid shape.
+fn area( request: &Request, default_precision: usize ) -> Response {
+    let precision = match precision( request, default_precision ) {
+        Ok( precision ) => precision,
+        Err( response ) => return response,
+    };
+    let unit = match AreaUnit::from_symbol( request.query_param( "unit" ).unwrap_or( "m2" ) ) {
+        Some( unit ) => unit,
+        None => return unknown_unit( "unit", &AreaUnit::all().map( |unit| unit.symbol() ) ),
+    };
+    let coord_unit = match Unit::from_symbol( request.query_param( "coord_unit" ).unwrap_or( "m" ) ) {
+        Some( coord_unit ) => coord_unit,
+        None => return unknown_unit( "coord_unit", &Unit::all().map( |unit| unit.symbol().to_string() ) ),
+    };
+
+    let json = match JsonValue::parse( &request.body ) {
+        Ok( json ) => json,
+        Err( e ) => return error( HttpStatus::BadRequest, &e.to_string() ),
+    };
+    let corners = match validate_corners( &json ) {
+        Ok( corners ) => corners,
+        Err( errors ) => return field_errors( &errors ),
+    };
+    let shape = AngularShape { corners };
+    if !shape.is_valid().unwrap_or( false ) {
+        return field_errors( &[ ("corners".to_string(), "The corners do not make a valid shape.".to_string()) ] );
+    }
+    let square_meters = Area::new( shape.area(), coord_unit ).to( Unit::Meter ).value;
+    Response::json_rounded( HttpStatus::Ok, &JsonValue::Object( vec![
+        ("area".to_string(), JsonValue::from( unit.convert_square_meters( square_meters ) )),
+        ("unit".to_string(), JsonValue::from( unit.symbol().as_str() )),
+    ] ), precision )
+}
+
+/// Get the decimal places the numbers in the response are rounded to, from the `precision`
+/// query parameter or the default.
+///
+/// # Returns
+/// A 400 Bad Request response if the precision is not a whole number from 0 to `MAX_PRECISION`.
+fn precision( request: &Request, default_precision: usize ) -> Result<usize, Response> {
+    match request.query_param( "precision" ) {
+        None => Ok( default_precision ),
+        Some( value ) => value.parse::<usize>().ok()
+            .filter( |precision| *precision <= MAX_PRECISION )
+            .ok_or_else( || error( HttpStatus::BadRequest,
+                &format!("The precision '{}' is not a number of decimal places from 0 to {}.", value, MAX_PRECISION) ) ),
+    }
+}
+
+/// Create a 400 Bad Request response for a query parameter with an unknown unit, with the
+/// `supported` units.
+fn unknown_unit( parameter: &str, supported: &[String] ) -> Response {
+    Response::json( HttpStatus::BadRequest, &JsonValue::Object( vec![
+        ("error".to_string(), JsonValue::from( format!("Unknown unit in '{}'. The supported units are {}.", parameter, supported.join( ", " )).as_str() )),
+        ("supported".to_string(), JsonValue::Array( supported.iter().map( |unit| JsonValue::from( unit.as_str() ) ).collect() )),
+    ] ) )
+}
+
+/// Get the corners of a JSON object with an array of points in `corners`.
+///
+/// # Returns
+/// The errors of all the fields that are missing or not numbers, as pairs of the
+/// path of the field, like `corners[1].x`, and a message.
+fn validate_corners( json: &JsonValue ) -> Result<Vec<Point>, Vec<(String, String)>> {
+    let field_error = |field: &str, message: &str| vec![ (field.to_string(), message.to_string()) ];
+    if !matches!( json, JsonValue::Object( _ ) ) {
+        return Err( field_error( "", "Expected an object with the 'corners' of the shape." ) );
+    }
+    let items = match json.get( "corners" ) {
+        Some( JsonValue::Array( items ) ) => items,
+        Some( _ ) => return Err( field_error( "corners", "Expected an array of points." ) ),
+        None => return Err( field_error( "corners", "The 'corners' member is missing." ) ),
+    };
+
+    let mut corners = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        if !matches!( item, JsonValue::Object( _ ) ) {
+            errors.push( (format!("corners[{}]", i), "Expected a point with 'x' and 'y'.".to_string()) );
+            continue;
+        }
+        let mut coordinate = |name: &str| match item.get( name ) {
+            Some( JsonValue::Number( value ) ) => Some( *value ),
+            Some( _ ) => {
+                errors.push( (format!("corners[{}].{}", i, name), format!("The '{}' coordinate is not a number.", name)) );
+                None
+            }
+            None => {
+                errors.push( (format!("corners[{}].{}", i, name), format!("The '{}' coordinate is missing.", name)) );
+                None
+            }
+        };
+        if let (Some( x ), Some( y )) = (coordinate( "x" ), coordinate( "y" )) {
+            corners.push( Point { x, y } );
+        }
+    }
+    match errors.is_empty() {
+        true => Ok( corners ),
+        false => Err( errors ),
+    }
+}
+
+/// Finds the convex hull of the points in a JSON array, like `[{"x": 0, "y": 0}, ...]`.
+///
+/// # Returns
+/// A JSON object with the `corners` of the hull, counterclockwise, and its `area`.
+/// 400 Bad Request if the body is not an array of points or the precision is not valid, and
+/// 422 Unprocessable Entity if the points do not enclose any area.
+fn convex_hull( request: &Request, default_precision: usize ) -> Response {
+    let precision = match precision( request, default_precision ) {
+        Ok( precision ) => precision,
+        Err( response ) => return response,
+    };
+    let points = match parse_points( &request.body ) {
+        Ok( points ) => points,
+        Err( message ) => return error( HttpStatus::BadRequest, &message ),
+    };
+    if points.len() < 3 {
+        return error( HttpStatus::UnprocessableEntity, &format!("Expected at least 3 points, got {}.", points.len()) );
+    }
+    // Points that are nearly on one line can have a hull of 3 or more corners that still has no area.
+    let hull = AngularShape { corners: algorithms::convex_hull( &points ) };
+    if hull.corners.len() < 3 || !hull.is_valid().unwrap_or( false ) {
+        return error( HttpStatus::UnprocessableEntity, "The points are on one line and have no hull." );
+    }
+    Response::json_rounded( HttpStatus::Ok, &JsonValue::Object( vec![
+        ("corners".to_string(), points_json( &hull.corners )),
+        ("area".to_string(), JsonValue::from( hull.area() )),
+    ] ), precision )
+}
+
+/// Compares two shapes in a JSON object like `{"first": [{"x": 0, "y": 0}, ...], "second": [...]}`.
+///
+/// # Returns
+/// A JSON object with the areas of the shapes, their `difference` (first minus second), and
+/// the `overlap`, the area covered by both. The overlap is only calculated for convex shapes
+/// and is null if either shape is not convex.
+/// 400 Bad Request if the body is not such an object or the precision is not valid, and 422
+/// Unprocessable Entity if either shape is not valid.
+fn compare( request: &Request, default_precision: usize ) -> Response {
+    let precision = match precision( request, default_precision ) {
+        Ok( precision ) => precision,
+        Err( response ) => return response,
+    };
+    let parsed = JsonValue::parse( &request.body ).map_err( |e| e.to_string() ).and_then( |json| {
+        let shape = |name: &str| match json.get( name ) {
+            Some( value ) => points_from_json( value ).map_err( |message| format!("Shape '{}': {}", name, message) ),
+            None => Err( format!("Expected a '{}' member.", name) ),
+        };
+        Ok( (shape( "first" )?, shape( "second" )?) )
+    });
+    let (first, second) = match parsed {
+        Ok( (first, second) ) => (AngularShape { corners: first }, AngularShape { corners: second }),
+        Err( message ) => return error( HttpStatus::BadRequest, &message ),
+    };
+    for (name, shape) in [("first", &first), ("second", &second)] {
+        if !shape.is_valid().unwrap_or( false ) {
+            return error( HttpStatus::UnprocessableEntity, &format!("The {} shape is not valid.", name) );
+        }
+    }
+
+    let (first_area, second_area) = (first.area(), second.area());
+    let overlap = match algorithms::is_convex( &first.corners ) && algorithms::is_convex( &second.corners ) {
+        true => JsonValue::from( algorithms::signed_area( &algorithms::convex_intersection( &first.corners, &second.corners ) ).abs() ),
+        false => JsonValue::Null,
+    };
+    Response::json_rounded( HttpStatus::Ok, &JsonValue::Object( vec![
+        ("first_area".to_string(), JsonValue::from( first_area )),
+        ("second_area".to_string(), JsonValue::from( second_area )),
+        ("difference".to_string(), JsonValue::from( first_area - second_area )),
+        ("overlap".to_string(), overlap),
+    ] ), precision )
+}
+
+/// Parse a JSON array of points with `x` and `y` members.
+/// # Returns
+/// An error message if the text is not such an array.
+fn parse_points( body: &str ) -> Result<Vec<Point>, String> {
+    points_from_json( &JsonValue::parse( body ).map_err( |e| e.to_string() )? )
+}
+
+/// Get the points of a JSON array of points with `x` and `y` members.
+/// # Returns
+/// An error message if the value is not such an array.
+fn points_from_json( json: &JsonValue ) -> Result<Vec<Point>, String> {
+    let items = json.as_array().ok_or( "Expected an array of points." )?;
+    items.iter()
+        .enumerate()
+        .map( |(i, item)| {
+            let coordinate = |name: &str| item.get( name )
+                .and_then( JsonValue::as_f64 )
+                .ok_or( format!("Point {} has no number '{}'.", i, name) );
+            Ok( Point { x: coordinate( "x" )?, y: coordinate( "y" )? } )
+        })
+        .collect()
+}
+
+/// Create a JSON array of points with `x` and `y` members.
+fn points_json( points: &[Point] ) -> JsonValue {
+    JsonValue::Array( points.iter()
+        .map( |p| JsonValue::Object( vec![
+            ("x".to_string(), JsonValue::from( p.x )),
+            ("y".to_string(), JsonValue::from( p.y )),
+        ] ) )
+        .collect() )
+}
+
+/// Create a 422 Unprocessable Entity response with the errors of the fields, like
+/// `{"error": "...", "errors": [{"field": "corners[0].x", "message": "..."}]}`.
+/// The `error` is the message of the first field.
+fn field_errors( errors: &[(String, String)] ) -> Response {
+    let items = errors.iter()
+        .map( |(field, message)| JsonValue::Object( vec![
+            ("field".to_string(), JsonValue::from( field.as_str() )),
+            ("message".to_string(), JsonValue::from( message.as_str() )),
+        ] ) )
+        .collect();
+    let first = errors.first().map( |(_, message)| message.as_str() ).unwrap_or( "The request is not valid." );
+    Response::json( HttpStatus::UnprocessableEntity, &JsonValue::Object( vec![
+        ("error".to_string(), JsonValue::from( first )),
+        ("errors".to_string(), JsonValue::Array( items )),
+    ] ) )
+}
+
+/// Create a JSON error response.
+fn error( status: HttpStatus, message: &str ) -> Response {
+    Response::json( status, &JsonValue::Object( vec![ ("error".to_string(), JsonValue::from( message )) ] ) )
+}
+
+
+// Unit tests for the shape routes.
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+
+    use crate::webserver::webserver::WebServer;
+
+    use super::*;
+
+    /// Start a server with the shape routes on a random port.
+    fn start_server() -> WebServer {
+        let mut server = WebServer::bind_random_port( "127.0.0.1" );
+        for route in shape_routes( DEFAULT_PRECISION ) {
+            assert!( server.add_route( route ) );
+        }
+        server.start().unwrap();
+        server
+    }
+
+    /// Post a JSON body to the path on the server, and return the raw response.
+    fn post( addr: SocketAddr, path: &str, body: &str ) -> String {
+        let mut stream = TcpStream::connect( addr ).unwrap();
+        write!( stream, "POST {path} HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}", body.len() ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string( &mut response ).unwrap();
+        response
+    }
+
+    /// Get the JSON body of a raw response.
+    fn body_json( response: &str ) -> JsonValue {
+        JsonValue::parse( response.split( "\r\n\r\n" ).nth( 1 ).unwrap() ).unwrap()
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        // A cloud of points inside a hexagon, with the hexagon corners among them.
+        let mut points: Vec<String> = (0..6)
+            .map( |i| {
+                let angle = i as f64 * std::f64::consts::PI / 3.0;
+                format!(r#"{{"x": {}, "y": {}}}"#, 10.0 * angle.cos(), 10.0 * angle.sin())
+            })
+            .collect();
+        for i in 0..50 {
+            points.push( format!(r#"{{"x": {}, "y": {}}}"#, (i % 7) as f64 - 3.0, (i % 5) as f64 - 2.0) );
+        }
+        let response = post( addr, "/convex-hull", &format!("[{}]", points.join(", ")) );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ), "{response}" );
+
+        let json = body_json( &response );
+        assert_eq!( json.get( "corners" ).and_then( JsonValue::as_array ).map( <[JsonValue]>::len ), Some( 6 ) );
+        let area = json.get( "area" ).and_then( JsonValue::as_f64 ).unwrap();
+        assert!( (area - 150.0 * 3f64.sqrt()).abs() < 0.000001, "{area}" );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_convex_hull_errors() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        // Too few points, or points on one line, have no hull.
+        let response = post( addr, "/convex-hull", r#"[{"x": 0, "y": 0}, {"x": 1, "y": 1}]"# );
+        assert!( response.starts_with( "HTTP/1.1 422 Unprocessable Entity" ), "{response}" );
+        let response = post( addr, "/convex-hull", r#"[{"x": 0, "y": 0}, {"x": 1, "y": 1}, {"x": 2, "y": 2}]"# );
+        assert!( response.starts_with( "HTTP/1.1 422 Unprocessable Entity" ), "{response}" );
+
+        // Points nearly on one line have a hull with corners, but without area.
+        let response = post( addr, "/convex-hull", r#"[{"x": 0, "y": 0}, {"x": 1, "y": 1e-12}, {"x": 2, "y": 0}]"# );
+        assert!( response.starts_with( "HTTP/1.1 422 Unprocessable Entity" ), "{response}" );
+        assert!( !response.contains( "-1" ), "{response}" );
+
+        // Anything else than an array of points is a bad request.
+        for body in [ "{}", r#"[{"x": 0}]"#, "[1, 2, 3]", "not json" ] {
+            assert!( post( addr, "/convex-hull", body ).starts_with( "HTTP/1.1 400 Bad Request" ), "{body}" );
+        }
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        let square = |x: f64, y: f64| format!(
+            r#"[{{"x": {x}, "y": {y}}}, {{"x": {}, "y": {y}}}, {{"x": {}, "y": {}}}, {{"x": {x}, "y": {}}}]"#,
+            x + 2.0, x + 2.0, y + 2.0, y + 2.0 );
+
+        // Two 2 by 2 squares overlapping in a 1 by 1 square.
+        let response = post( addr, "/compare", &format!(r#"{{"first": {}, "second": {}}}"#, square( 0.0, 0.0 ), square( 1.0, 1.0 )) );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ), "{response}" );
+        let json = body_json( &response );
+        assert_eq!( json.get( "first_area" ).and_then( JsonValue::as_f64 ), Some( 4.0 ) );
+        assert_eq!( json.get( "difference" ).and_then( JsonValue::as_f64 ), Some( 0.0 ) );
+        let overlap = json.get( "overlap" ).and_then( JsonValue::as_f64 ).unwrap();
+        assert!( (overlap - 1.0).abs() < 1e-9, "{overlap}" );
+
+        // The overlap of a concave shape is not calculated.
+        let concave = r#"[{"x": 0, "y": 0}, {"x": 4, "y": 0}, {"x": 4, "y": 4}, {"x": 2, "y": 1}, {"x": 0, "y": 4}]"#;
+        let json = body_json( &post( addr, "/compare", &format!(r#"{{"first": {}, "second": {}}}"#, concave, square( 1.0, 1.0 )) ) );
+        assert_eq!( json.get( "first_area" ).and_then( JsonValue::as_f64 ), Some( 10.0 ) );
+        assert_eq!( json.get( "difference" ).and_then( JsonValue::as_f64 ), Some( 6.0 ) );
+        assert_eq!( json.get( "overlap" ), Some( &JsonValue::Null ) );
+
+        // Invalid shapes and bodies are rejected.
+        let bow_tie = r#"[{"x": 0, "y": 0}, {"x": 2, "y": 2}, {"x": 2, "y": 0}, {"x": 0, "y": 2}]"#;
+        let response = post( addr, "/compare", &format!(r#"{{"first": {}, "second": {}}}"#, square( 0.0, 0.0 ), bow_tie) );
+        assert!( response.starts_with( "HTTP/1.1 422 Unprocessable Entity" ), "{response}" );
+        assert!( post( addr, "/compare", &format!(r#"{{"first": {}}}"#, square( 0.0, 0.0 )) ).starts_with( "HTTP/1.1 400 Bad Request" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_area() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        let response = post( addr, "/api/area", r#"{"corners": [{"x": 0, "y": 0}, {"x": 4, "y": 0}, {"x": 4, "y": 3}]}"# );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ), "{response}" );
+        assert_eq!( body_json( &response ).get( "area" ).and_then( JsonValue::as_f64 ), Some( 6.0 ) );
+        assert!( post( addr, "/api/area", "not json" ).starts_with( "HTTP/1.1 400 Bad Request" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_area_unit() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        let square = r#"{"corners": [{"x": 0, "y": 0}, {"x": 10, "y": 0}, {"x": 10, "y": 10}, {"x": 0, "y": 10}]}"#;
+        let area = |path: &str| {
+            let json = body_json( &post( addr, path, square ) );
+            (json.get( "area" ).and_then( JsonValue::as_f64 ).unwrap(), json.get( "unit" ).and_then( JsonValue::as_str ).unwrap().to_string())
+        };
+
+        // The coordinates are in meters unless told otherwise.
+        assert_eq!( area( "/api/area" ), (100.0, "m2".to_string()) );
+        let (ft2, unit) = area( "/api/area?unit=ft2" );
+        assert!( (ft2 - 1076.39).abs() < 0.01, "{ft2}" );
+        assert_eq!( unit, "ft2" );
+        let (acres, _) = area( "/api/area?unit=acre" );
+        assert!( (acres - 0.0247).abs() < 0.0001, "{acres}" );
+        let (cm2, _) = area( "/api/area?unit=cm2&coord_unit=cm" );
+        assert!( (cm2 - 100.0).abs() < 0.000001, "{cm2}" );
+        let (km2, _) = area( "/api/area?unit=km2&coord_unit=km" );
+        assert!( (km2 - 100.0).abs() < 0.000001, "{km2}" );
+
+        // Unknown units are a bad request that lists the supported units.
+        let response = post( addr, "/api/area?unit=hectare", square );
+        assert!( response.starts_with( "HTTP/1.1 400 Bad Request" ), "{response}" );
+        let supported = body_json( &response ).get( "supported" ).and_then( JsonValue::as_array ).unwrap().len();
+        assert_eq!( supported, 6 );
+        assert!( post( addr, "/api/area?coord_unit=yd", square ).starts_with( "HTTP/1.1 400 Bad Request" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_area_precision() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        // A triangle with an area of 10 / 3.
+        let triangle = r#"{"corners": [{"x": 0, "y": 0}, {"x": 10, "y": 0}, {"x": 0, "y": 0.6666666666666666}]}"#;
+        let body = |path: &str| post( addr, path, triangle ).split( "\r\n\r\n" ).nth( 1 ).unwrap().to_string();
+        assert_eq!( body( "/api/area?precision=0" ), r#"{"area":3,"unit":"m2"}"# );
+        assert_eq!( body( "/api/area?precision=2" ), r#"{"area":3.33,"unit":"m2"}"# );
+        assert_eq!( body( "/api/area" ), r#"{"area":3.333333,"unit":"m2"}"# );
+        assert_eq!( body( "/api/area?precision=12" ), r#"{"area":3.333333333333,"unit":"m2"}"# );
+
+        // The other routes round their numbers too.
+        let points = r#"[{"x": 0, "y": 0}, {"x": 0.1, "y": 0}, {"x": 0, "y": 0.2}, {"x": 0.1, "y": 0.2}]"#;
+        let response = post( addr, "/convex-hull?precision=3", points );
+        assert!( response.ends_with( r#""area":0.02}"# ), "{response}" );
+
+        for precision in [ "13", "-1", "two", "" ] {
+            let response = post( addr, &format!("/api/area?precision={precision}"), triangle );
+            assert!( response.starts_with( "HTTP/1.1 400 Bad Request" ), "{precision}: {response}" );
+        }
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_area_field_errors() {
+        let mut server = start_server();
+        let addr = server.local_addr().unwrap();
+
+        let triangle = |corner: &str| format!(r#"{{"corners": [{{"x": 0, "y": 0}}, {{"x": 4, "y": 0}}, {corner}]}}"#);
+
+        // Each malformed payload gets a 422 with the field and a message for it.
+        let cases = [
+            ( "[]".to_string(), "", "Expected an object with the 'corners' of the shape." ),
+            ( "{}".to_string(), "corners", "The 'corners' member is missing." ),
+            ( r#"{"corners": 3}"#.to_string(), "corners", "Expected an array of points." ),
+            ( triangle( "[4, 3]" ), "corners[2]", "Expected a point with 'x' and 'y'." ),
+            ( triangle( r#"{"y": 3}"# ), "corners[2].x", "The 'x' coordinate is missing." ),
+            ( triangle( r#"{"x": 4}"# ), "corners[2].y", "The 'y' coordinate is missing." ),
+            ( triangle( r#"{"x": "4", "y": 3}"# ), "corners[2].x", "The 'x' coordinate is not a number." ),
+            ( triangle( r#"{"x": 8, "y": 0}"# ), "corners", "The corners do not make a valid shape." ),
+        ];
+        for (body, field, message) in cases {
+            let response = post( addr, "/api/area", &body );
+            assert!( response.starts_with( "HTTP/1.1 422 Unprocessable Entity" ), "{body}: {response}" );
+            let json = body_json( &response );
+            let expected = JsonValue::Array( vec![ JsonValue::Object( vec![
+                ("field".to_string(), JsonValue::from( field )),
+                ("message".to_string(), JsonValue::from( message )),
+            ] ) ] );
+            assert_eq!( json.get( "errors" ), Some( &expected ), "{body}" );
+            assert_eq!( json.get( "error" ).and_then( JsonValue::as_str ), Some( message ), "{body}" );
+        }
+
+        // All the fields with errors are listed.
+        let json = body_json( &post( addr, "/api/area", r#"{"corners": [{"x": null}, {"x": 1, "y": 1}, {}]}"# ) );
+        let fields: Vec<&str> = json.get( "errors" ).and_then( JsonValue::as_array ).unwrap().iter()
+            .filter_map( |error| error.get( "field" ).and_then( JsonValue::as_str ) )
+            .collect();
+        assert_eq!( fields, vec![ "corners[0].x", "corners[0].y", "corners[2].x", "corners[2].y" ] );
+
+        server.stop();
+    }
+}
@@ -0,0 +1,268 @@
+use std::io::{BufRead, Read};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The default number of buffers kept in a pool.
+pub const DEFAULT_POOL_BUFFERS: usize = 16;
+
+/// The largest buffer capacity in bytes that is returned to a pool, larger buffers are dropped
+/// so that one huge request does not keep its memory forever.
+pub const MAX_POOLED_CAPACITY: usize = 64 * 1024;
+
+/// The size of the buffer a `PooledReader` reads the connection into, the same as `BufReader` uses.
+pub const READ_BUFFER_BYTES: usize = 8 * 1024;
+
+/// A buffer that can be kept in a pool and reused.
+pub trait Reusable: Default {
+
+    /// Empties the buffer for the next user, keeping its memory.
+    fn reset( &mut self );
+
+    /// Gets the number of bytes of memory the buffer keeps.
+    fn capacity_bytes( &self ) -> usize;
+}
+
+/// Byte buffers, like the buffer for reading a line.
+impl Reusable for Vec<u8> {
+    fn reset( &mut self ) {
+        self.clear();
+    }
+
+    fn capacity_bytes( &self ) -> usize {
+        self.capacity()
+    }
+}
+
+/// The lines of a request head. The strings are emptied but kept, so that the lines of the next
+/// request can be written into them. `read_head` removes the lines the next request does not have.
+impl Reusable for Vec<String> {
+    fn reset( &mut self ) {
+        self.iter_mut().for_each( String::clear );
+    }
+
+    fn capacity_bytes( &self ) -> usize {
+        self.iter().map( String::capacity ).sum()
+    }
+}
+
+/// A pool of buffers that are reused between connections,
+/// so that reading a request does not allocate new buffers every time.
+/// Cloning gives another handle to the same pool.
+pub struct BufferPool<T: Reusable = Vec<u8>> {
+    buffers: Arc<Mutex<Vec<T>>>,
+    max_buffers: usize,
+}
+
+/// Cloning a pool shares its buffers, so the buffers do not need to be cloneable.
+impl<T: Reusable> Clone for BufferPool<T> {
+    fn clone( &self ) -> Self {
+        BufferPool { buffers: self.buffers.clone(), max_buffers: self.max_buffers }
+    }
+}
+
+/// A buffer taken from a pool. The buffer is reset and returned to the pool when dropped.
+pub struct PooledBuffer<T: Reusable = Vec<u8>> {
+    buffer: T,
+    pool: BufferPool<T>,
+}
+
+/// Implement the BufferPool struct.
+impl<T: Reusable> BufferPool<T> {
+
+    /// Creates a new empty BufferPool.
+    ///
+    /// # Arguments
+    /// * `max_buffers` - The maximum number of unused buffers kept in the pool.
+    pub fn new( max_buffers: usize ) -> BufferPool<T> {
+        BufferPool {
+            buffers: Arc::new( Mutex::new( Vec::new() ) ),
+            max_buffers,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or a new one if the pool is empty.
+    pub fn take( &self ) -> PooledBuffer<T> {
+        let buffer = self.lock().pop().unwrap_or_default();
+        PooledBuffer { buffer, pool: self.clone() }
+    }
+
+    /// Gets the number of unused buffers in the pool.
+    pub fn len( &self ) -> usize {
+        self.lock().len()
+    }
+
+    /// Checks if the pool has no unused buffers.
+    pub fn is_empty( &self ) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Lock the buffers. A buffer is only added or removed as a whole, so a poisoned lock is still usable.
+    fn lock( &self ) -> std::sync::MutexGuard<'_, Vec<T>> {
+        self.buffers.lock().unwrap_or_else( PoisonError::into_inner )
+    }
+}
+
+/// Return the buffer to the pool, unless the pool is full or the buffer has grown too large.
+impl<T: Reusable> Drop for PooledBuffer<T> {
+    fn drop( &mut self ) {
+        let mut buffer = std::mem::take( &mut self.buffer );
+        if buffer.capacity_bytes() == 0 || buffer.capacity_bytes() > MAX_POOLED_CAPACITY {
+            return;
+        }
+        buffer.reset();
+        let mut buffers = self.pool.lock();
+        if buffers.len() < self.pool.max_buffers {
+            buffers.push( buffer );
+        }
+    }
+}
+
+impl<T: Reusable> Deref for PooledBuffer<T> {
+    type Target = T;
+
+    fn deref( &self ) -> &T {
+        &self.buffer
+    }
+}
+
+impl<T: Reusable> DerefMut for PooledBuffer<T> {
+    fn deref_mut( &mut self ) -> &mut T {
+        &mut self.buffer
+    }
+}
+
+/// A buffered reader, like `BufReader`, that reads into a buffer from a pool.
+/// The buffer goes back to the pool when the reader is dropped.
+pub struct PooledReader<R: Read> {
+    inner: R,
+    buffer: PooledBuffer,
+    position: usize,
+}
+
+/// Implement the PooledReader struct.
+impl<R: Read> PooledReader<R> {
+
+    /// Creates a new PooledReader with a buffer from the pool.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader to read from.
+    /// * `pool` - The pool to take the buffer from.
+    pub fn new( inner: R, pool: &BufferPool ) -> PooledReader<R> {
+        PooledReader { inner, buffer: pool.take(), position: 0 }
+    }
+}
+
+impl<R: Read> Read for PooledReader<R> {
+    fn read( &mut self, out: &mut [u8] ) -> std::io::Result<usize> {
+
+        // Large reads skip the buffer when it is empty, like BufReader does.
+        if self.position >= self.buffer.len() && out.len() >= READ_BUFFER_BYTES {
+            return self.inner.read( out );
+        }
+        let read = self.fill_buf()?.read( out )?;
+        self.consume( read );
+        Ok( read )
+    }
+}
+
+impl<R: Read> BufRead for PooledReader<R> {
+    fn fill_buf( &mut self ) -> std::io::Result<&[u8]> {
+        if self.position >= self.buffer.len() {
+            self.buffer.resize( READ_BUFFER_BYTES, 0 );
+            self.position = 0;
+            match self.inner.read( &mut self.buffer ) {
+                Ok( read ) => self.buffer.truncate( read ),
+                Err( e ) => {
+                    self.buffer.clear();
+                    return Err( e );
+                }
+            }
+        }
+        Ok( &self.buffer[self.position..] )
+    }
+
+    fn consume( &mut self, amount: usize ) {
+        self.position = (self.position + amount).min( self.buffer.len() );
+    }
+}
+
+
+// Unit tests for BufferPool.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_reused() {
+        let pool: BufferPool = BufferPool::new( 2 );
+        let mut buffer = pool.take();
+        buffer.extend_from_slice( b"GET / HTTP/1.1" );
+        let address = buffer.as_ptr();
+        drop( buffer );
+        assert_eq!( pool.len(), 1 );
+
+        // The same allocation comes back empty.
+        let buffer = pool.take();
+        assert!( buffer.is_empty() );
+        assert!( buffer.capacity() >= 14 );
+        assert_eq!( buffer.as_ptr(), address );
+        assert!( pool.is_empty() );
+    }
+
+    #[test]
+    fn test_pool_limits() {
+        let pool: BufferPool = BufferPool::new( 1 );
+        let mut first = pool.take();
+        let mut second = pool.take();
+        first.push( 1 );
+        second.push( 2 );
+        drop( first );
+        drop( second );
+        assert_eq!( pool.len(), 1 );
+
+        // Buffers that were never used or have grown too large are not kept.
+        let pool: BufferPool = BufferPool::new( 4 );
+        drop( pool.take() );
+        let mut large = pool.take();
+        large.resize( MAX_POOLED_CAPACITY + 1, 0 );
+        drop( large );
+        assert!( pool.is_empty() );
+    }
+
+    #[test]
+    fn test_lines_reused() {
+        let pool: BufferPool<Vec<String>> = BufferPool::new( 1 );
+        let mut lines = pool.take();
+        lines.push( "GET / HTTP/1.1".to_string() );
+        lines.push( "Host: localhost".to_string() );
+        let address = lines[1].as_ptr();
+        drop( lines );
+
+        // The strings come back empty, with their memory.
+        let lines = pool.take();
+        assert_eq!( lines.len(), 2 );
+        assert!( lines.iter().all( String::is_empty ) );
+        assert_eq!( lines[1].as_ptr(), address );
+    }
+
+    #[test]
+    fn test_pooled_reader() {
+        let pool: BufferPool = BufferPool::new( 1 );
+        let text = format!( "GET / HTTP/1.1\r\n\r\n{}", "a".repeat( 3 * READ_BUFFER_BYTES ) );
+        let mut reader = PooledReader::new( text.as_bytes(), &pool );
+        let mut line = String::new();
+        reader.read_line( &mut line ).unwrap();
+        assert_eq!( line, "GET / HTTP/1.1\r\n" );
+        let mut rest = String::new();
+        reader.read_to_string( &mut rest ).unwrap();
+        assert_eq!( rest.len(), 3 * READ_BUFFER_BYTES + 2 );
+        assert!( pool.is_empty() );
+
+        // The read buffer goes back to the pool and is used by the next reader.
+        drop( reader );
+        assert_eq!( pool.len(), 1 );
+        let mut reader = PooledReader::new( &b"next"[..], &pool );
+        assert!( pool.is_empty() );
+        assert_eq!( reader.fill_buf().unwrap(), b"next" );
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use super::response::Response;
+
+/// The default number of responses kept in a cache.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// A cached response and when it was stored and last used.
+struct CacheEntry {
+    response: Response,
+    expires: Instant,
+    last_used: u64,
+}
+
+/// The entries of a cache, keyed by the request path and, for responses that vary on it,
+/// the `Accept` header of the request.
+struct CacheEntries {
+    entries: HashMap<(String, Option<String>), CacheEntry>,
+    capacity: usize,
+    clock: u64,
+}
+
+/// An in-memory cache of rendered responses.
+/// The least recently used response is dropped when the cache is full.
+/// Cloning gives another handle to the same cache, so handlers can invalidate it.
+#[derive(Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<CacheEntries>>,
+}
+
+/// Implement the ResponseCache struct.
+impl ResponseCache {
+
+    /// Creates a new empty ResponseCache.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of responses kept in the cache.
+    pub fn new( capacity: usize ) -> ResponseCache {
+        ResponseCache {
+            entries: Arc::new( Mutex::new( CacheEntries {
+                entries: HashMap::new(),
+                capacity,
+                clock: 0,
+            } ) ),
+        }
+    }
+
+    /// Gets a copy of the cached response for a request, if there is one that has not expired.
+    ///
+    /// # Arguments
+    /// * `path` - The request target as the client sent it, the path including the query.
+    /// * `accept` - The value of the `Accept` header, if the request had one.
+    pub fn get( &self, path: &str, accept: Option<&str> ) -> Option<Response> {
+        let mut cache = self.lock();
+        cache.clock += 1;
+        let clock = cache.clock;
+        let now = Instant::now();
+
+        // Responses that do not vary on Accept are shared by all requests for the path.
+        let keys = [
+            (path.to_string(), None),
+            (path.to_string(), Some( accept.unwrap_or("").to_string() )),
+        ];
+        for key in keys {
+            match cache.entries.get_mut( &key ) {
+                Some( entry ) if entry.expires > now => {
+                    entry.last_used = clock;
+                    return Some( entry.response.clone() );
+                }
+                Some( _ ) => {
+                    cache.entries.remove( &key );
+                }
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// Stores a response for a request.
+    /// A response with a `Vary` header naming `Accept` is only used for requests with the same `Accept` header.
+    ///
+    /// # Arguments
+    /// * `path` - The request target as the client sent it, the path including the query.
+    /// * `accept` - The value of the `Accept` header, if the request had one.
+    /// * `response` - The response to store.
+    /// * `ttl` - How long the response can be used.
+    pub fn insert( &self, path: &str, accept: Option<&str>, response: &Response, ttl: Duration ) {
+        let varies_on_accept = response.headers.iter().any( |(name, value)| {
+            name.eq_ignore_ascii_case("Vary") &&
+                value.split(',').any( |field| field.trim().eq_ignore_ascii_case("Accept") || field.trim() == "*" )
+        });
+        let key = match varies_on_accept {
+            true => (path.to_string(), Some( accept.unwrap_or("").to_string() )),
+            false => (path.to_string(), None),
+        };
+
+        let mut cache = self.lock();
+        if cache.capacity == 0 {
+            return;
+        }
+        cache.clock += 1;
+        let clock = cache.clock;
+
+        // Make room by dropping the least recently used response.
+        if !cache.entries.contains_key( &key ) && cache.entries.len() >= cache.capacity {
+            let oldest = cache.entries.iter()
+                .min_by_key( |(_, entry)| entry.last_used )
+                .map( |(key, _)| key.clone() );
+            if let Some( oldest ) = oldest {
+                cache.entries.remove( &oldest );
+            }
+        }
+        cache.entries.insert( key, CacheEntry {
+            response: response.clone(),
+            expires: Instant::now() + ttl,
+            last_used: clock,
+        });
+    }
+
+    /// Removes the cached responses of all paths that start with the prefix.
+    ///
+    /// # Arguments
+    /// * `prefix` - The start of the paths to remove, an empty prefix removes everything.
+    pub fn invalidate( &self, prefix: &str ) {
+        self.lock().entries.retain( |(path, _), _| !path.starts_with( prefix ) );
+    }
+
+    /// Gets the number of responses in the cache, including expired ones not yet removed.
+    pub fn len( &self ) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Checks if the cache is empty.
+    pub fn is_empty( &self ) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// Lock the entries. The entries are only changed as a whole, so a poisoned lock is still usable.
+    fn lock( &self ) -> std::sync::MutexGuard<'_, CacheEntries> {
+        self.entries.lock().unwrap_or_else( PoisonError::into_inner )
+    }
+}
+
+
+// Unit tests for ResponseCache.
+#[cfg(test)]
+mod tests {
+    use super::super::http_status::HttpStatus;
+    use super::*;
+
+    /// Create a response with the body and headers.
+    fn response( body: &str, headers: &[(&str, &str)] ) -> Response {
+        Response::new(
+            HttpStatus::Ok,
+            body.to_string(),
+            headers.iter().map( |(name, value)| (name.to_string(), value.to_string()) ).collect()
+        )
+    }
+
+    #[test]
+    fn test_get_and_invalidate() {
+        let cache = ResponseCache::new( 10 );
+        cache.insert( "/api/shapes/1", None, &response( "one", &[] ), Duration::from_secs( 60 ) );
+        cache.insert( "/api/shapes/2", None, &response( "two", &[] ), Duration::from_secs( 60 ) );
+        cache.insert( "/version", None, &response( "1", &[] ), Duration::from_secs( 60 ) );
+
+        assert_eq!( cache.get( "/api/shapes/1", Some("text/html") ).unwrap().body, "one" );
+        assert!( cache.get( "/api/shapes/1?svg=true", None ).is_none() );
+
+        cache.invalidate( "/api/shapes" );
+        assert!( cache.get( "/api/shapes/1", None ).is_none() );
+        assert!( cache.get( "/api/shapes/2", None ).is_none() );
+        assert_eq!( cache.get( "/version", None ).unwrap().body, "1" );
+    }
+
+    #[test]
+    fn test_vary_accept() {
+        let cache = ResponseCache::new( 10 );
+        let vary = [ ("Vary", "Accept") ];
+        cache.insert( "/shape", Some("application/json"), &response( "{}", &vary ), Duration::from_secs( 60 ) );
+        cache.insert( "/shape", Some("text/html"), &response( "<p></p>", &vary ), Duration::from_secs( 60 ) );
+
+        assert_eq!( cache.get( "/shape", Some("application/json") ).unwrap().body, "{}" );
+        assert_eq!( cache.get( "/shape", Some("text/html") ).unwrap().body, "<p></p>" );
+        assert!( cache.get( "/shape", Some("text/plain") ).is_none() );
+        assert!( cache.get( "/shape", None ).is_none() );
+    }
+
+    #[test]
+    fn test_expiry() {
+        let cache = ResponseCache::new( 10 );
+        cache.insert( "/shape", None, &response( "old", &[] ), Duration::from_millis( 20 ) );
+        assert!( cache.get( "/shape", None ).is_some() );
+        std::thread::sleep( Duration::from_millis( 40 ) );
+        assert!( cache.get( "/shape", None ).is_none() );
+        assert_eq!( cache.len(), 0 );
+    }
+
+    #[test]
+    fn test_least_recently_used_dropped() {
+        let cache = ResponseCache::new( 2 );
+        cache.insert( "/a", None, &response( "a", &[] ), Duration::from_secs( 60 ) );
+        cache.insert( "/b", None, &response( "b", &[] ), Duration::from_secs( 60 ) );
+        assert!( cache.get( "/a", None ).is_some() );
+        cache.insert( "/c", None, &response( "c", &[] ), Duration::from_secs( 60 ) );
+
+        assert_eq!( cache.len(), 2 );
+        assert!( cache.get( "/a", None ).is_some() );
+        assert!( cache.get( "/b", None ).is_none() );
+        assert!( cache.get( "/c", None ).is_some() );
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// The stream of a connection the web server serves, over TCP or a Unix domain socket.
+/// Requests are read and responses written the same way for both.
+pub enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+/// Implement the Connection enum.
+impl Connection {
+
+    /// Get the address of the client for the log, like `127.0.0.1:50000`.
+    /// Clients of a Unix domain socket usually have no address, and are shown as `unix`.
+    pub fn peer(&self) -> String {
+        match self {
+            Connection::Tcp(stream) => stream.peer_addr().map( |addr| addr.to_string() ).unwrap_or_default(),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.peer_addr().ok()
+                .and_then( |addr| addr.as_pathname().map( |path| path.display().to_string() ) )
+                .unwrap_or_else( || "unix".to_string() ),
+        }
+    }
+
+    /// Check if the client is on the same machine: a TCP client from a loopback address like
+    /// `127.0.0.1` or `::1`, or any client of a Unix domain socket.
+    pub fn is_local(&self) -> bool {
+        match self {
+            Connection::Tcp(stream) => stream.peer_addr().is_ok_and( |addr| addr.ip().is_loopback() ),
+            #[cfg(unix)]
+            Connection::Unix(_) => true,
+        }
+    }
+
+    /// Move the stream into or out of nonblocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Connection::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    /// Shut down the writing half of the stream, so the client sees the end of the response.
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        match self {
+            Connection::Tcp(stream) => stream.shutdown(Shutdown::Write),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.shutdown(Shutdown::Write),
+        }
+    }
+}
+
+/// Read from a shared reference, like `&TcpStream`, so the request can be read through a
+/// buffered reader while the response is written to the same stream.
+impl Read for &Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Connection::Tcp(stream) => (&*stream).read(buf),
+            #[cfg(unix)]
+            Connection::Unix(stream) => (&*stream).read(buf),
+        }
+    }
+}
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+/// Write to the stream of the connection.
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Wrap a TCP stream in a connection.
+impl From<TcpStream> for Connection {
+    fn from(stream: TcpStream) -> Self {
+        Connection::Tcp(stream)
+    }
+}
+
+/// The listener the web server accepts connections from, on a TCP address or a Unix domain socket.
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Implement the Listener enum.
+impl Listener {
+
+    /// Wait for the next connection.
+    pub fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Listener::Tcp(listener) => listener.accept().map( |(stream, _)| Connection::Tcp(stream) ),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener.accept().map( |(stream, _)| Connection::Unix(stream) ),
+        }
+    }
+
+    /// Get the TCP address the listener is bound to.
+    /// # Returns
+    /// The address, or `None` for a Unix domain socket.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(_) => None,
+        }
+    }
+}
+
+/// Implement the Display trait for Listener to show where it listens, like `127.0.0.1:8080`
+/// or the path of the socket.
+impl fmt::Display for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Listener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "{addr}"),
+                Err(_) => write!(f, "an unknown address"),
+            },
+            #[cfg(unix)]
+            Listener::Unix(listener) => match listener.local_addr().ok().and_then( |addr| addr.as_pathname().map( |path| path.display().to_string() ) ) {
+                Some(path) => write!(f, "{path}"),
+                None => write!(f, "an unnamed socket"),
+            },
+        }
+    }
+}
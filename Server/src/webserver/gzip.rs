@@ -0,0 +1,365 @@
+/// The media types that are worth compressing. Other types, like images and archives,
+/// are usually compressed already, so compressing them again only wastes time.
+pub const COMPRESSIBLE_CONTENT_TYPES: [&str; 4] = ["application/json", "application/javascript", "image/svg+xml", "text/*"];
+
+/// Bodies smaller than this are not compressed, because the gzip header and trailer would make them larger.
+pub const MIN_COMPRESSED_BYTES: usize = 64;
+
+/// Bodies larger than this are not compressed. The compressor keeps a position for every byte
+/// of the body, so a larger body would need too much memory.
+pub const MAX_COMPRESSED_BYTES: usize = 1024 * 1024;
+
+/// The size of the window the compressor looks for repeated bytes in.
+const WINDOW_SIZE: usize = 32 * 1024;
+
+/// The shortest and the longest repeat the compressor encodes as a back-reference.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// How many earlier positions with the same hash are tried when looking for a repeat.
+const MAX_CHAIN: usize = 64;
+
+/// The number of bits in the hash of the next three bytes.
+const HASH_BITS: u32 = 15;
+
+/// The base lengths of the length codes 257-285 and their numbers of extra bits.
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+/// The base distances of the distance codes 0-29 and their numbers of extra bits.
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Checks if a body of the media type is worth compressing.
+/// Parameters like `charset` are ignored, and `text/*` matches every text type.
+///
+/// # Arguments
+/// * `content_type` - The value of the `Content-Type` header.
+pub fn is_compressible( content_type: &str ) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    COMPRESSIBLE_CONTENT_TYPES.iter().any( |allowed| match allowed.strip_suffix('*') {
+        Some( prefix ) => media_type.starts_with( prefix ),
+        None => media_type == *allowed,
+    })
+}
+
+/// Checks if the client accepts gzip encoded bodies.
+///
+/// # Arguments
+/// * `accept_encoding` - The value of the `Accept-Encoding` header, if the request had one.
+pub fn accepts_gzip( accept_encoding: Option<&str> ) -> bool {
+    accept_encoding
+        .unwrap_or("")
+        .split(',')
+        .any( |coding| {
+            let mut parts = coding.split(';');
+            let name = parts.next().unwrap_or("").trim();
+            let quality = parts
+                .filter_map( |parameter| parameter.trim().strip_prefix("q=") )
+                .find_map( |q| q.trim().parse::<f64>().ok() )
+                .unwrap_or(1.0);
+            quality > 0.0 && (name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip"))
+        })
+}
+
+/// Compresses the bytes into the gzip format.
+/// The data is compressed into a single deflate block with the fixed Huffman codes.
+/// The memory used grows with the data, so callers should not pass more than `MAX_COMPRESSED_BYTES`.
+///
+/// # Arguments
+/// * `data` - The bytes to compress.
+pub fn compress( data: &[u8] ) -> Vec<u8> {
+
+    // The header: the magic bytes, deflate, no flags, no time, no extra flags and an unknown OS.
+    let mut bits = BitWriter { bytes: vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255], current: 0, count: 0 };
+
+    // The only block is the last one and uses the fixed codes.
+    bits.write( 1, 1 );
+    bits.write( 1, 2 );
+    deflate( data, &mut bits );
+    write_literal( &mut bits, 256 );
+    let mut bytes = bits.finish();
+
+    // The trailer: the CRC-32 and the size of the data.
+    bytes.extend_from_slice( &crc32( data ).to_le_bytes() );
+    bytes.extend_from_slice( &(data.len() as u32).to_le_bytes() );
+    bytes
+}
+
+/// Writes bits to bytes, the least significant bit first, like deflate expects.
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    count: u32,
+}
+
+impl BitWriter {
+
+    /// Write the lowest `count` bits of the value.
+    fn write( &mut self, value: u32, count: u32 ) {
+        self.current |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push( self.current as u8 );
+            self.current >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which is stored the most significant bit first.
+    fn write_code( &mut self, code: u32, count: u32 ) {
+        self.write( code.reverse_bits() >> (32 - count), count );
+    }
+
+    /// Write the remaining bits and get the bytes.
+    fn finish( mut self ) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push( self.current as u8 );
+        }
+        self.bytes
+    }
+}
+
+/// Write a literal or length symbol with the fixed Huffman code.
+fn write_literal( bits: &mut BitWriter, symbol: u32 ) {
+    match symbol {
+        0..=143 => bits.write_code( 0x30 + symbol, 8 ),
+        144..=255 => bits.write_code( 0x190 + symbol - 144, 9 ),
+        256..=279 => bits.write_code( symbol - 256, 7 ),
+        _ => bits.write_code( 0xc0 + symbol - 280, 8 ),
+    }
+}
+
+/// Write a back-reference of the length to the bytes the distance back.
+fn write_match( bits: &mut BitWriter, length: usize, distance: usize ) {
+    let code = LENGTH_BASES.iter().rposition( |&base| base as usize <= length ).unwrap_or(0);
+    write_literal( bits, 257 + code as u32 );
+    bits.write( (length - LENGTH_BASES[code] as usize) as u32, LENGTH_EXTRA_BITS[code] as u32 );
+
+    let code = DISTANCE_BASES.iter().rposition( |&base| base as usize <= distance ).unwrap_or(0);
+    bits.write_code( code as u32, 5 );
+    bits.write( (distance - DISTANCE_BASES[code] as usize) as u32, DISTANCE_EXTRA_BITS[code] as u32 );
+}
+
+/// Hash the three bytes starting at the position.
+fn hash( data: &[u8], position: usize ) -> usize {
+    let value = (data[position] as u32) << 16 | (data[position + 1] as u32) << 8 | data[position + 2] as u32;
+    (value.wrapping_mul( 0x9e37_79b1 ) >> (32 - HASH_BITS)) as usize
+}
+
+/// Encode the data as literals and back-references to repeated bytes.
+/// Earlier positions are found through chains of positions with the same hash.
+fn deflate( data: &[u8], bits: &mut BitWriter ) {
+    let mut head: Vec<usize> = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous: Vec<usize> = vec![usize::MAX; data.len()];
+
+    let mut position = 0;
+    while position < data.len() {
+
+        // Find the longest repeat of the bytes at the position.
+        let mut best_length = 0;
+        let mut best_distance = 0;
+        if position + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min( data.len() - position );
+            let mut candidate = head[hash( data, position )];
+            let mut chain = 0;
+            while candidate != usize::MAX && position - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let length = data[candidate..].iter()
+                    .zip( &data[position..position + max_length] )
+                    .take_while( |(a, b)| a == b )
+                    .count();
+                if length > best_length {
+                    best_length = length;
+                    best_distance = position - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+                chain += 1;
+            }
+        }
+
+        // Write the repeat, or the byte if there is no long enough repeat.
+        if best_length >= MIN_MATCH {
+            write_match( bits, best_length, best_distance );
+            for skipped in position..position + best_length {
+                insert( data, skipped, &mut head, &mut previous );
+            }
+            position += best_length;
+        }
+        else {
+            write_literal( bits, data[position] as u32 );
+            insert( data, position, &mut head, &mut previous );
+            position += 1;
+        }
+    }
+}
+
+/// Add the position to the chain of positions with the hash of its bytes.
+fn insert( data: &[u8], position: usize, head: &mut [usize], previous: &mut [usize] ) {
+    if position + MIN_MATCH <= data.len() {
+        let key = hash( data, position );
+        previous[position] = head[key];
+        head[key] = position;
+    }
+}
+
+/// Calculate the CRC-32 of the data, as used by gzip.
+fn crc32( data: &[u8] ) -> u32 {
+    let mut crc: u32 = !0;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+
+// Unit tests for gzip compression.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_compressible() {
+        assert!( is_compressible( "application/json" ) );
+        assert!( is_compressible( "text/html; charset=utf-8" ) );
+        assert!( is_compressible( "Image/SVG+XML" ) );
+        assert!( !is_compressible( "image/png" ) );
+        assert!( !is_compressible( "application/zip" ) );
+        assert!( !is_compressible( "" ) );
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!( accepts_gzip( Some( "gzip" ) ) );
+        assert!( accepts_gzip( Some( "deflate, GZIP;q=0.5" ) ) );
+        assert!( !accepts_gzip( Some( "gzip;q=0" ) ) );
+        assert!( !accepts_gzip( Some( "br, deflate" ) ) );
+        assert!( !accepts_gzip( None ) );
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!( crc32( b"" ), 0 );
+        assert_eq!( crc32( b"123456789" ), 0xcbf4_3926 );
+    }
+
+    #[test]
+    fn test_compress() {
+        let data = "{\"area\": 12.5, \"unit\": \"m2\"}, ".repeat( 100 );
+        let compressed = compress( data.as_bytes() );
+
+        // The header, and the trailer with the CRC-32 and the size.
+        assert_eq!( &compressed[..3], &[0x1f, 0x8b, 8] );
+        let trailer = &compressed[compressed.len() - 8..];
+        assert_eq!( trailer[..4], crc32( data.as_bytes() ).to_le_bytes() );
+        assert_eq!( trailer[4..], (data.len() as u32).to_le_bytes() );
+
+        // Repeated data is much smaller.
+        assert!( compressed.len() < data.len() / 10 );
+    }
+
+    /// Reads bits from bytes, the least significant bit first, like `BitWriter` writes them.
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        position: usize,
+    }
+
+    impl BitReader<'_> {
+
+        /// Read `count` bits as a number, the first bit lowest.
+        fn read( &mut self, count: u32 ) -> u32 {
+            let mut value = 0;
+            for i in 0..count {
+                let bit = self.bytes[self.position / 8] >> (self.position % 8) & 1;
+                value |= (bit as u32) << i;
+                self.position += 1;
+            }
+            value
+        }
+
+        /// Read a Huffman code of `count` bits, which is stored the most significant bit first.
+        fn read_code( &mut self, code: u32, count: u32 ) -> u32 {
+            (0..count).fold( code, |code, _| code << 1 | self.read( 1 ) )
+        }
+
+        /// Read a literal or length symbol with the fixed codes of RFC 1951, section 3.2.6.
+        fn read_symbol( &mut self ) -> u32 {
+            let code = self.read_code( 0, 7 );
+            if code <= 0b001_0111 {
+                return 256 + code;
+            }
+            let code = self.read_code( code, 1 );
+            match code {
+                0b0011_0000..=0b1011_1111 => code - 0b0011_0000,
+                0b1100_0000..=0b1100_0111 => 280 + code - 0b1100_0000,
+                _ => 144 + self.read_code( code, 1 ) - 0b1_1001_0000,
+            }
+        }
+    }
+
+    /// Decompress gzip data whose blocks use the fixed Huffman codes, like `compress` writes.
+    /// Panics on anything else.
+    fn decompress( gzip: &[u8] ) -> Vec<u8> {
+        assert_eq!( &gzip[..4], &[0x1f, 0x8b, 8, 0] );
+        let mut bits = BitReader { bytes: &gzip[10..gzip.len() - 8], position: 0 };
+        let mut data: Vec<u8> = Vec::new();
+        loop {
+            let last = bits.read( 1 ) == 1;
+            assert_eq!( bits.read( 2 ), 1, "not a block with the fixed codes" );
+            loop {
+                let symbol = bits.read_symbol() as usize;
+                if symbol < 256 {
+                    data.push( symbol as u8 );
+                    continue;
+                }
+                if symbol == 256 {
+                    break;
+                }
+                let index = symbol - 257;
+                let length = LENGTH_BASES[index] as usize + bits.read( LENGTH_EXTRA_BITS[index] as u32 ) as usize;
+                let index = bits.read_code( 0, 5 ) as usize;
+                let distance = DISTANCE_BASES[index] as usize + bits.read( DISTANCE_EXTRA_BITS[index] as u32 ) as usize;
+                assert!( distance <= data.len(), "distance {distance} before the start" );
+                for _ in 0..length {
+                    data.push( data[data.len() - distance] );
+                }
+            }
+            if last {
+                break;
+            }
+        }
+
+        // The trailer must match the decompressed data.
+        let trailer = &gzip[gzip.len() - 8..];
+        assert_eq!( trailer[..4], crc32( &data ).to_le_bytes() );
+        assert_eq!( trailer[4..], (data.len() as u32).to_le_bytes() );
+        data
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+
+        // Every byte value, so all the code lengths are used, pseudo random bytes that do not repeat,
+        // long runs for the longest matches, and repeats further back than most of the window.
+        let mut random: u32 = 1;
+        let noise: Vec<u8> = (0..20_000).map( |_| {
+            random = random.wrapping_mul( 1_103_515_245 ).wrapping_add( 12_345 );
+            (random >> 16) as u8
+        }).collect();
+        let json = "{\"area\": 12.5, \"unit\": \"m2\"}, ".repeat( 100 );
+        let mut mixed: Vec<u8> = (0..=255).collect();
+        mixed.extend_from_slice( &noise );
+        mixed.extend( std::iter::repeat_n( b'a', 1000 ) );
+        mixed.extend_from_slice( &noise[..5000] );
+        mixed.extend_from_slice( json.as_bytes() );
+
+        for data in [ &b""[..], b"a", b"abcabcabc", json.as_bytes(), &noise, &mixed ] {
+            assert_eq!( decompress( &compress( data ) ), data );
+        }
+    }
+}
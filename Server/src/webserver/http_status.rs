@@ -1,10 +1,22 @@
 use std::fmt;
 
 /// This module defines the HTTP status codes used in the web server.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum HttpStatus {
     Ok,
+    PartialContent,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
     NotFound,
+    PayloadTooLarge,
+    UriTooLong,
+    UnsupportedMediaType,
+    RangeNotSatisfiable,
+    UnprocessableEntity,
     InternalServerError,
+    ServiceUnavailable,
+    GatewayTimeout,
 }
 
 /// Implement the Display trait for HttpStatus to allow easy printing.
@@ -12,8 +24,136 @@ impl fmt::Display for HttpStatus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             HttpStatus::Ok => write!(f, "200 OK"),
+            HttpStatus::PartialContent => write!(f, "206 Partial Content"),
+            HttpStatus::BadRequest => write!(f, "400 Bad Request"),
+            HttpStatus::Unauthorized => write!(f, "401 Unauthorized"),
+            HttpStatus::Forbidden => write!(f, "403 Forbidden"),
             HttpStatus::NotFound => write!(f, "404 Not Found"),
+            HttpStatus::PayloadTooLarge => write!(f, "413 Payload Too Large"),
+            HttpStatus::UriTooLong => write!(f, "414 URI Too Long"),
+            HttpStatus::UnsupportedMediaType => write!(f, "415 Unsupported Media Type"),
+            HttpStatus::RangeNotSatisfiable => write!(f, "416 Range Not Satisfiable"),
+            HttpStatus::UnprocessableEntity => write!(f, "422 Unprocessable Entity"),
             HttpStatus::InternalServerError => write!(f, "500 Internal Server Error"),
+            HttpStatus::ServiceUnavailable => write!(f, "503 Service Unavailable"),
+            HttpStatus::GatewayTimeout => write!(f, "504 Gateway Timeout"),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Implement the HttpStatus enum.
+impl HttpStatus {
+
+    /// Get all the statuses the web server uses, in the order of their codes.
+    pub fn all() -> &'static [HttpStatus] {
+        &ALL_STATUSES
+    }
+
+    /// Get the status with the next larger code, or `None` for the last status.
+    /// The match names every status, so a new status can not be left out of `all`.
+    const fn next(&self) -> Option<HttpStatus> {
+        match self {
+            HttpStatus::Ok => Some(HttpStatus::PartialContent),
+            HttpStatus::PartialContent => Some(HttpStatus::BadRequest),
+            HttpStatus::BadRequest => Some(HttpStatus::Unauthorized),
+            HttpStatus::Unauthorized => Some(HttpStatus::Forbidden),
+            HttpStatus::Forbidden => Some(HttpStatus::NotFound),
+            HttpStatus::NotFound => Some(HttpStatus::PayloadTooLarge),
+            HttpStatus::PayloadTooLarge => Some(HttpStatus::UriTooLong),
+            HttpStatus::UriTooLong => Some(HttpStatus::UnsupportedMediaType),
+            HttpStatus::UnsupportedMediaType => Some(HttpStatus::RangeNotSatisfiable),
+            HttpStatus::RangeNotSatisfiable => Some(HttpStatus::UnprocessableEntity),
+            HttpStatus::UnprocessableEntity => Some(HttpStatus::InternalServerError),
+            HttpStatus::InternalServerError => Some(HttpStatus::ServiceUnavailable),
+            HttpStatus::ServiceUnavailable => Some(HttpStatus::GatewayTimeout),
+            HttpStatus::GatewayTimeout => None,
+        }
+    }
+
+    /// Get the numeric code of the status, like 404 for `NotFound`.
+    pub const fn code(&self) -> u16 {
+        match self {
+            HttpStatus::Ok => 200,
+            HttpStatus::PartialContent => 206,
+            HttpStatus::BadRequest => 400,
+            HttpStatus::Unauthorized => 401,
+            HttpStatus::Forbidden => 403,
+            HttpStatus::NotFound => 404,
+            HttpStatus::PayloadTooLarge => 413,
+            HttpStatus::UriTooLong => 414,
+            HttpStatus::UnsupportedMediaType => 415,
+            HttpStatus::RangeNotSatisfiable => 416,
+            HttpStatus::UnprocessableEntity => 422,
+            HttpStatus::InternalServerError => 500,
+            HttpStatus::ServiceUnavailable => 503,
+            HttpStatus::GatewayTimeout => 504,
+        }
+    }
+
+    /// Get the status for a numeric code.
+    ///
+    /// # Arguments
+    /// * `code` - The numeric code, like 404.
+    ///
+    /// # Returns
+    /// The status, or `None` if the web server does not use the code.
+    pub fn from_u16(code: u16) -> Option<HttpStatus> {
+        HttpStatus::all().iter().copied().find( |status| status.code() == code )
+    }
+}
+
+/// The number of statuses, counted by following `HttpStatus::next` from the first status.
+/// The codes must grow along the way, so a status can not be visited twice.
+const STATUS_COUNT: usize = {
+    let mut count = 1;
+    let mut status = HttpStatus::Ok;
+    while let Some(next) = status.next() {
+        assert!(next.code() > status.code(), "HttpStatus::next must go to a larger code");
+        count += 1;
+        status = next;
+    }
+    count
+};
+
+/// All the statuses, in the order of their codes.
+const ALL_STATUSES: [HttpStatus; STATUS_COUNT] = {
+    let mut all = [HttpStatus::Ok; STATUS_COUNT];
+    let mut i = 1;
+    while i < STATUS_COUNT {
+        all[i] = match all[i - 1].next() {
+            Some(next) => next,
+            None => panic!("HttpStatus::next ended early"),
+        };
+        i += 1;
+    }
+    all
+};
+
+/// Convert a numeric code to a status. Fails for codes the web server does not use.
+impl TryFrom<u16> for HttpStatus {
+    type Error = ();
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        HttpStatus::from_u16(code).ok_or(())
+    }
+}
+
+
+// Unit tests for HttpStatus.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_display_parity() {
+        for status in HttpStatus::all() {
+            assert_eq!( HttpStatus::from_u16( status.code() ), Some( *status ) );
+            assert_eq!( HttpStatus::try_from( status.code() ), Ok( *status ) );
+            assert!( status.to_string().starts_with( &format!("{} ", status.code()) ), "{status}" );
+        }
+        assert_eq!( HttpStatus::all().len(), 14 );
+        assert_eq!( HttpStatus::all().last(), Some( &HttpStatus::GatewayTimeout ) );
+        assert_eq!( HttpStatus::from_u16( 418 ), None );
+        assert_eq!( HttpStatus::try_from( 0 ), Err( () ) );
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// This enum represents the HTTP protocol versions the web server speaks.
+/// Responses use the version of the request, so that HTTP/1.0 clients get HTTP/1.0 responses.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum HttpVersion {
+    Http10,
+    #[default]
+    Http11,
+}
+
+/// Implement the Display trait for HttpVersion to write it like in a request or status line.
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HttpVersion::Http10 => write!(f, "HTTP/1.0"),
+            HttpVersion::Http11 => write!(f, "HTTP/1.1"),
+        }
+    }
+}
+
+/// Parse the version from a request line, like `HTTP/1.0`.
+impl FromStr for HttpVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HTTP/1.0" => Ok(HttpVersion::Http10),
+            "HTTP/1.1" => Ok(HttpVersion::Http11),
+            _ => Err(())
+        }
+    }
+}
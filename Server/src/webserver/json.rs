@@ -0,0 +1,510 @@
+use std::error::Error;
+use std::fmt;
+
+/// The maximum nesting depth of arrays and objects accepted by the parser.
+const MAX_DEPTH: usize = 128;
+
+/// This enum represents a JSON value.
+/// Objects keep their members in the order they were parsed or added.
+#[derive(Clone, PartialEq, Debug)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// Represents an error in parsing JSON text.
+/// It contains the byte position of the error and a description of what was wrong.
+#[derive(Debug, PartialEq)]
+pub struct JsonError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// Implement the Display trait for JsonError to allow easy printing.
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid JSON at position {}: {}", self.position, self.message)
+    }
+}
+impl Error for JsonError {}
+
+/// Implement the JsonValue enum.
+impl JsonValue {
+
+    /// Parses a JSON value from text.
+    ///
+    /// # Arguments
+    /// * `text` - The JSON text. Whitespace around the value is allowed, anything else is an error.
+    ///
+    /// # Returns
+    /// The parsed value, or an error describing where the text is not valid JSON.
+    pub fn parse( text: &str ) -> Result<JsonValue, JsonError> {
+        let mut parser = Parser { bytes: text.as_bytes(), position: 0 };
+        let value = parser.parse_value( 0 )?;
+        parser.skip_whitespace();
+        if parser.position != parser.bytes.len() {
+            return Err( parser.error( "unexpected text after the value" ) );
+        }
+        Ok( value )
+    }
+
+    /// Gets a member of an object.
+    ///
+    /// # Returns
+    /// The value of the member, or `None` if this is not an object or there is no such member.
+    pub fn get( &self, key: &str ) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object( members ) => members.iter().find( |(k, _)| k == key ).map( |(_, v)| v ),
+            _ => None,
+        }
+    }
+
+    /// Gets the value as a string slice, if it is a string.
+    pub fn as_str( &self ) -> Option<&str> {
+        match self {
+            JsonValue::String( s ) => Some( s ),
+            _ => None,
+        }
+    }
+
+    /// Gets the value as a number, if it is a number.
+    pub fn as_f64( &self ) -> Option<f64> {
+        match self {
+            JsonValue::Number( n ) => Some( *n ),
+            _ => None,
+        }
+    }
+
+    /// Gets the items of the value, if it is an array.
+    pub fn as_array( &self ) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array( items ) => Some( items ),
+            _ => None,
+        }
+    }
+
+    /// Writes the value as compact JSON text, like `to_string`, with the numbers rounded to the
+    /// decimal places. Zeros at the end of the decimals are left out, so 2.50 is written as 2.5.
+    /// The value itself is not changed, only its text.
+    ///
+    /// # Arguments
+    /// * `decimals` - The number of decimal places to round the numbers to.
+    pub fn to_string_rounded( &self, decimals: usize ) -> String {
+        Rounded { value: self, decimals: Some( decimals ) }.to_string()
+    }
+}
+
+/// Convert strings to JSON strings.
+impl From<&str> for JsonValue {
+    fn from( value: &str ) -> Self {
+        JsonValue::String( value.to_string() )
+    }
+}
+impl From<String> for JsonValue {
+    fn from( value: String ) -> Self {
+        JsonValue::String( value )
+    }
+}
+
+/// Convert numbers to JSON numbers.
+impl From<f64> for JsonValue {
+    fn from( value: f64 ) -> Self {
+        JsonValue::Number( value )
+    }
+}
+impl From<usize> for JsonValue {
+    fn from( value: usize ) -> Self {
+        JsonValue::Number( value as f64 )
+    }
+}
+
+/// Convert booleans to JSON booleans.
+impl From<bool> for JsonValue {
+    fn from( value: bool ) -> Self {
+        JsonValue::Bool( value )
+    }
+}
+
+/// Implement the Display trait for JsonValue to write it as compact JSON text.
+/// Numbers that are not finite have no JSON representation and are written as null.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Rounded { value: self, decimals: None }.fmt( f )
+    }
+}
+
+/// A JSON value written with its numbers rounded to the decimal places, or as they are
+/// if there are no decimal places.
+struct Rounded<'a> {
+    value: &'a JsonValue,
+    decimals: Option<usize>,
+}
+
+/// Implement the Display trait for Rounded to write the value as compact JSON text.
+impl fmt::Display for Rounded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool( b ) => write!(f, "{}", b),
+            JsonValue::Number( n ) if n.is_finite() => match self.decimals {
+                Some( decimals ) => write_rounded( f, *n, decimals ),
+                None => write!(f, "{}", n),
+            },
+            JsonValue::Number( _ ) => write!(f, "null"),
+            JsonValue::String( s ) => write_string( f, s ),
+            JsonValue::Array( items ) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", Rounded { value: item, decimals: self.decimals })?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object( members ) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string( f, key )?;
+                    write!(f, ":{}", Rounded { value, decimals: self.decimals })?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Write a number rounded to the decimal places, without zeros at the end of the decimals.
+fn write_rounded( f: &mut fmt::Formatter, n: f64, decimals: usize ) -> fmt::Result {
+    let text = format!("{:.*}", decimals, n);
+    let text = match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.'),
+        false => text.as_str(),
+    };
+
+    // A small negative number rounds to -0, which is written as 0.
+    match text {
+        "-0" => write!(f, "0"),
+        text => write!(f, "{}", text),
+    }
+}
+
+/// Write a string as a quoted and escaped JSON string.
+fn write_string( f: &mut fmt::Formatter, s: &str ) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// A recursive descent parser over the bytes of JSON text.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+/// Implement the Parser struct.
+impl Parser<'_> {
+
+    /// Create an error at the current position.
+    fn error( &self, message: &str ) -> JsonError {
+        JsonError { position: self.position, message: message.to_string() }
+    }
+
+    /// Get the next byte without consuming it.
+    fn peek( &self ) -> Option<u8> {
+        self.bytes.get( self.position ).copied()
+    }
+
+    /// Skip whitespace between tokens.
+    fn skip_whitespace( &mut self ) {
+        while let Some( b' ' | b'\t' | b'\n' | b'\r' ) = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Consume the expected literal text, like `true` or `null`.
+    fn expect_literal( &mut self, literal: &str ) -> Result<(), JsonError> {
+        if self.bytes[self.position..].starts_with( literal.as_bytes() ) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err( self.error( &format!("expected '{}'", literal) ) )
+        }
+    }
+
+    /// Parse any value.
+    fn parse_value( &mut self, depth: usize ) -> Result<JsonValue, JsonError> {
+
+        // Deeply nested input could overflow the stack.
+        if depth > MAX_DEPTH {
+            return Err( self.error( "nesting is too deep" ) );
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some( b'n' ) => self.expect_literal( "null" ).map( |_| JsonValue::Null ),
+            Some( b't' ) => self.expect_literal( "true" ).map( |_| JsonValue::Bool( true ) ),
+            Some( b'f' ) => self.expect_literal( "false" ).map( |_| JsonValue::Bool( false ) ),
+            Some( b'"' ) => self.parse_string().map( JsonValue::String ),
+            Some( b'[' ) => self.parse_array( depth ),
+            Some( b'{' ) => self.parse_object( depth ),
+            Some( b'-' | b'0'..=b'9' ) => self.parse_number(),
+            Some( _ ) => Err( self.error( "expected a value" ) ),
+            None => Err( self.error( "unexpected end of text" ) ),
+        }
+    }
+
+    /// Parse a number. The JSON number grammar is checked before converting the text.
+    fn parse_number( &mut self ) -> Result<JsonValue, JsonError> {
+        let start = self.position;
+        let digits = |parser: &mut Parser| -> usize {
+            let digits_start = parser.position;
+            while let Some( b'0'..=b'9' ) = parser.peek() {
+                parser.position += 1;
+            }
+            parser.position - digits_start
+        };
+
+        // Sign and integer part. A leading zero can not be followed by more digits.
+        if self.peek() == Some( b'-' ) {
+            self.position += 1;
+        }
+        if self.peek() == Some( b'0' ) {
+            self.position += 1;
+        } else if digits( self ) == 0 {
+            return Err( self.error( "expected a digit" ) );
+        }
+
+        // Fraction part.
+        if self.peek() == Some( b'.' ) {
+            self.position += 1;
+            if digits( self ) == 0 {
+                return Err( self.error( "expected a digit after the decimal point" ) );
+            }
+        }
+
+        // Exponent part.
+        if let Some( b'e' | b'E' ) = self.peek() {
+            self.position += 1;
+            if let Some( b'+' | b'-' ) = self.peek() {
+                self.position += 1;
+            }
+            if digits( self ) == 0 {
+                return Err( self.error( "expected a digit in the exponent" ) );
+            }
+        }
+
+        // The checked text is valid for the standard float parser.
+        let text = std::str::from_utf8( &self.bytes[start..self.position] ).unwrap();
+        text.parse::<f64>()
+            .map( JsonValue::Number )
+            .map_err( |_| JsonError { position: start, message: "invalid number".to_string() } )
+    }
+
+    /// Parse four hex digits of a unicode escape.
+    fn parse_hex4( &mut self ) -> Result<u32, JsonError> {
+        let hex = self.bytes.get( self.position..self.position + 4 )
+            .and_then( |hex| std::str::from_utf8( hex ).ok() )
+            .and_then( |hex| u32::from_str_radix( hex, 16 ).ok() )
+            .ok_or_else( || self.error( "expected four hex digits" ) )?;
+        self.position += 4;
+        Ok( hex )
+    }
+
+    /// Parse a string, including the quotes around it.
+    fn parse_string( &mut self ) -> Result<String, JsonError> {
+        self.position += 1;
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err( self.error( "unterminated string" ) ),
+                Some( b'"' ) => {
+                    self.position += 1;
+                    break;
+                }
+                Some( b'\\' ) => {
+                    self.position += 1;
+                    let escaped = self.peek().ok_or_else( || self.error( "unterminated string" ) )?;
+                    self.position += 1;
+                    let c: char = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+
+                            // Characters outside the basic plane are escaped as surrogate pairs.
+                            let mut code = self.parse_hex4()?;
+                            if (0xD800..0xDC00).contains( &code ) && self.bytes[self.position..].starts_with( b"\\u" ) {
+                                self.position += 2;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains( &low ) {
+                                    return Err( self.error( "invalid surrogate pair" ) );
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32( code ).ok_or_else( || self.error( "invalid unicode escape" ) )?
+                        }
+                        _ => return Err( self.error( "invalid escape" ) ),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice( c.encode_utf8( &mut buffer ).as_bytes() );
+                }
+                Some( b ) if b < 0x20 => return Err( self.error( "control character in string" ) ),
+                Some( b ) => {
+                    bytes.push( b );
+                    self.position += 1;
+                }
+            }
+        }
+
+        // The input was a str, so unescaped bytes are valid UTF-8.
+        Ok( String::from_utf8( bytes ).unwrap() )
+    }
+
+    /// Parse an array.
+    fn parse_array( &mut self, depth: usize ) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut items: Vec<JsonValue> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some( b']' ) {
+            self.position += 1;
+            return Ok( JsonValue::Array( items ) );
+        }
+        loop {
+            items.push( self.parse_value( depth + 1 )? );
+            self.skip_whitespace();
+            match self.peek() {
+                Some( b',' ) => self.position += 1,
+                Some( b']' ) => {
+                    self.position += 1;
+                    return Ok( JsonValue::Array( items ) );
+                }
+                _ => return Err( self.error( "expected ',' or ']'" ) ),
+            }
+        }
+    }
+
+    /// Parse an object.
+    fn parse_object( &mut self, depth: usize ) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut members: Vec<(String, JsonValue)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some( b'}' ) {
+            self.position += 1;
+            return Ok( JsonValue::Object( members ) );
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some( b'"' ) {
+                return Err( self.error( "expected a member name" ) );
+            }
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if self.peek() != Some( b':' ) {
+                return Err( self.error( "expected ':'" ) );
+            }
+            self.position += 1;
+            members.push( (key, self.parse_value( depth + 1 )?) );
+            self.skip_whitespace();
+            match self.peek() {
+                Some( b',' ) => self.position += 1,
+                Some( b'}' ) => {
+                    self.position += 1;
+                    return Ok( JsonValue::Object( members ) );
+                }
+                _ => return Err( self.error( "expected ',' or '}'" ) ),
+            }
+        }
+    }
+}
+
+
+// Unit tests for JsonValue.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object() {
+        let value = JsonValue::parse( r#" { "level": "debug", "count": -1.5e2, "ok": true, "none": null, "list": [1, 2] } "# ).unwrap();
+        assert_eq!( value.get("level").and_then( JsonValue::as_str ), Some("debug") );
+        assert_eq!( value.get("count").and_then( JsonValue::as_f64 ), Some(-150.0) );
+        assert_eq!( value.get("ok"), Some( &JsonValue::Bool( true ) ) );
+        assert_eq!( value.get("none"), Some( &JsonValue::Null ) );
+        assert_eq!( value.get("list").and_then( JsonValue::as_array ).map( |items| items.len() ), Some(2) );
+        assert_eq!( value.get("missing"), None );
+    }
+
+    #[test]
+    fn test_parse_string_escapes() {
+        let value = JsonValue::parse( r#""a\"b\\c\ndé😀""# ).unwrap();
+        assert_eq!( value.as_str(), Some("a\"b\\c\nd\u{e9}\u{1F600}") );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!( JsonValue::parse( "" ).is_err() );
+        assert!( JsonValue::parse( "{\"a\" 1}" ).is_err() );
+        assert!( JsonValue::parse( "[1, 2" ).is_err() );
+        assert!( JsonValue::parse( "01" ).is_err() );
+        assert!( JsonValue::parse( "1." ).is_err() );
+        assert!( JsonValue::parse( "\"abc" ).is_err() );
+        assert!( JsonValue::parse( "true false" ).is_err() );
+        assert!( JsonValue::parse( &"[".repeat( 1000 ) ).is_err() );
+        assert_eq!( JsonValue::parse( "[1,]" ).unwrap_err().position, 3 );
+    }
+
+    #[test]
+    fn test_to_string() {
+        let value = JsonValue::Object( vec![
+            ("name".to_string(), JsonValue::from( "a \"quoted\"\tname" )),
+            ("area".to_string(), JsonValue::from( 12.5 )),
+            ("count".to_string(), JsonValue::from( 3usize )),
+            ("items".to_string(), JsonValue::Array( vec![ JsonValue::Null, JsonValue::from( false ) ] )),
+            ("nan".to_string(), JsonValue::from( f64::NAN )),
+        ] );
+        assert_eq!( value.to_string(), r#"{"name":"a \"quoted\"\tname","area":12.5,"count":3,"items":[null,false],"nan":null}"# );
+    }
+
+    #[test]
+    fn test_to_string_rounded() {
+        let value = JsonValue::Object( vec![
+            ("area".to_string(), JsonValue::from( 1.9999999999999998 )),
+            ("items".to_string(), JsonValue::Array( vec![ JsonValue::from( 2.346 ), JsonValue::from( -0.0004 ), JsonValue::from( 1500.0 ) ] )),
+            ("name".to_string(), JsonValue::from( "1.23456" )),
+        ] );
+        assert_eq!( value.to_string_rounded( 2 ), r#"{"area":2,"items":[2.35,0,1500],"name":"1.23456"}"# );
+        assert_eq!( value.to_string_rounded( 0 ), r#"{"area":2,"items":[2,0,1500],"name":"1.23456"}"# );
+        assert_eq!( JsonValue::from( 0.1 + 0.2 ).to_string_rounded( 12 ), "0.3" );
+        assert_eq!( JsonValue::from( f64::INFINITY ).to_string_rounded( 2 ), "null" );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = r#"{"corners":[{"x":0,"y":0.25},{"x":-1e-7,"y":2}],"name":"\u0001"}"#;
+        let value = JsonValue::parse( text ).unwrap();
+        assert_eq!( JsonValue::parse( &value.to_string() ).unwrap(), value );
+    }
+}
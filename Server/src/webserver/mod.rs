@@ -1,7 +1,16 @@
-
 pub mod webserver;
+pub mod connection;
 pub mod routehandler;
+pub mod router;
 pub mod http_method;
 pub mod http_status;
+pub mod http_version;
 pub mod request;
-pub mod response;
\ No newline at end of file
+pub mod response;
+pub mod json;
+pub mod static_files;
+pub mod cache;
+pub mod unicode;
+pub mod buffer_pool;
+pub mod recording;
+pub mod gzip;
\ No newline at end of file
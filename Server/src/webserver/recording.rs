@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::error;
+
+use super::json::JsonValue;
+use super::request::Request;
+use super::response::Response;
+
+/// The default number of recent requests kept in memory.
+pub const DEFAULT_RECORDING_CAPACITY: usize = 100;
+
+/// The default number of bytes of a request or response body that are recorded.
+pub const DEFAULT_RECORDED_BODY_BYTES: usize = 4096;
+
+/// The headers whose values are not recorded by default.
+pub const DEFAULT_REDACTED_HEADERS: [&str; 3] = ["Authorization", "Cookie", "Set-Cookie"];
+
+/// The value recorded instead of the value of a redacted header.
+const REDACTED: &str = "[redacted]";
+
+/// The settings and the recent requests of a recorder.
+struct RecorderState {
+    enabled: AtomicBool,
+    file: Mutex<Option<PathBuf>>,
+    recent: Mutex<VecDeque<JsonValue>>,
+    capacity: usize,
+    max_body_bytes: usize,
+    redacted_headers: Vec<String>,
+}
+
+/// Records requests and their responses for debugging, so that the traffic a user saw can be replayed.
+/// Each request is recorded as a JSON object with the method, the target, the headers, the bodies
+/// up to a size limit, the status and the duration. The most recent ones are kept in memory, and
+/// all of them are appended as JSON lines to a file if one is set.
+/// Recording is off until it is enabled. Cloning gives another handle to the same recorder.
+#[derive(Clone)]
+pub struct RequestRecorder {
+    state: Arc<RecorderState>,
+}
+
+/// Implement the RequestRecorder struct.
+impl RequestRecorder {
+
+    /// Creates a new disabled RequestRecorder without a file, which redacts the `DEFAULT_REDACTED_HEADERS`.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of recent requests kept in memory.
+    pub fn new( capacity: usize ) -> RequestRecorder {
+        RequestRecorder::with_settings( capacity, DEFAULT_RECORDED_BODY_BYTES, &DEFAULT_REDACTED_HEADERS )
+    }
+
+    /// Creates a new disabled RequestRecorder without a file.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of recent requests kept in memory.
+    /// * `max_body_bytes` - The number of bytes of each body that are recorded.
+    /// * `redacted_headers` - The headers whose values are not recorded. Names are case-insensitive.
+    pub fn with_settings( capacity: usize, max_body_bytes: usize, redacted_headers: &[&str] ) -> RequestRecorder {
+        RequestRecorder {
+            state: Arc::new( RecorderState {
+                enabled: AtomicBool::new( false ),
+                file: Mutex::new( None ),
+                recent: Mutex::new( VecDeque::new() ),
+                capacity,
+                max_body_bytes,
+                redacted_headers: redacted_headers.iter().map( |name| name.to_string() ).collect(),
+            } ),
+        }
+    }
+
+    /// Turns recording on or off.
+    pub fn set_enabled( &self, enabled: bool ) {
+        self.state.enabled.store( enabled, Ordering::Relaxed );
+    }
+
+    /// Checks if recording is on.
+    pub fn is_enabled( &self ) -> bool {
+        self.state.enabled.load( Ordering::Relaxed )
+    }
+
+    /// Sets the file the requests are appended to, or `None` to only keep them in memory.
+    pub fn set_file( &self, file: Option<PathBuf> ) {
+        *self.state.file.lock().unwrap_or_else( PoisonError::into_inner ) = file;
+    }
+
+    /// Records a request and its response, if recording is on.
+    ///
+    /// # Arguments
+    /// * `request` - The request, with its body.
+    /// * `response` - The response sent for the request.
+    /// * `duration` - How long handling the request took.
+    pub fn record( &self, request: &Request, response: &Response, duration: Duration ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let record = self.to_json( request, response, duration );
+
+        // Append the record to the file.
+        let file = self.state.file.lock().unwrap_or_else( PoisonError::into_inner ).clone();
+        if let Some( path ) = file {
+            let written = OpenOptions::new()
+                .create( true )
+                .append( true )
+                .open( &path )
+                .and_then( |mut file| file.write_all( format!("{record}\n").as_bytes() ) );
+            if let Err( e ) = written {
+                error!("Could not record the request to '{}': {}", path.display(), e);
+            }
+        }
+
+        // Keep the record in memory, dropping the oldest one if there are too many.
+        if self.state.capacity == 0 {
+            return;
+        }
+        let mut recent = self.state.recent.lock().unwrap_or_else( PoisonError::into_inner );
+        if recent.len() >= self.state.capacity {
+            recent.pop_front();
+        }
+        recent.push_back( record );
+    }
+
+    /// Gets the recent records in memory, the oldest first.
+    pub fn recent( &self ) -> Vec<JsonValue> {
+        self.state.recent.lock().unwrap_or_else( PoisonError::into_inner ).iter().cloned().collect()
+    }
+
+    /// Create the record of a request and its response.
+    fn to_json( &self, request: &Request, response: &Response, duration: Duration ) -> JsonValue {
+        let timestamp = SystemTime::now().duration_since( UNIX_EPOCH ).unwrap_or_default();
+        let response_body = match &response.binary_body {
+            Some( bytes ) => bytes.as_slice(),
+            None => response.body.as_bytes(),
+        };
+        JsonValue::Object( vec![
+            ("timestamp".to_string(), JsonValue::from( timestamp.as_secs_f64() )),
+            ("method".to_string(), JsonValue::from( request.method.to_string() )),
+            ("target".to_string(), JsonValue::from( request.raw_target.as_str() )),
+            ("request_headers".to_string(), self.headers_json( &request.headers )),
+            ("request_body".to_string(), self.body_json( request.body.as_bytes() )),
+            ("status".to_string(), JsonValue::from( response.status.to_string() )),
+            ("response_headers".to_string(), self.headers_json( &response.headers )),
+            ("response_body".to_string(), self.body_json( response_body )),
+            ("duration_ms".to_string(), JsonValue::from( duration.as_secs_f64() * 1000.0 )),
+        ] )
+    }
+
+    /// Create a JSON object of the headers with the values of the redacted ones replaced.
+    fn headers_json( &self, headers: &[(String, String)] ) -> JsonValue {
+        JsonValue::Object( headers.iter()
+            .map( |(name, value)| {
+                let redacted = self.state.redacted_headers.iter().any( |redacted| redacted.eq_ignore_ascii_case( name ) );
+                (name.clone(), JsonValue::from( if redacted { REDACTED } else { value.as_str() } ))
+            })
+            .collect() )
+    }
+
+    /// Create a JSON string of the start of a body. Bytes that are not valid UTF-8 are replaced.
+    fn body_json( &self, body: &[u8] ) -> JsonValue {
+        let length = body.len().min( self.state.max_body_bytes );
+        let mut text = String::from_utf8_lossy( &body[..length] ).into_owned();
+        if length < body.len() {
+            text.push_str( &format!("... ({} bytes)", body.len()) );
+        }
+        JsonValue::from( text )
+    }
+}
+
+
+// Unit tests for RequestRecorder.
+#[cfg(test)]
+mod tests {
+    use super::super::http_status::HttpStatus;
+    use super::*;
+
+    /// Create a request with the headers and body.
+    fn request( lines: &[&str], body: &str ) -> Request {
+        let mut request = Request::new( &lines.iter().map( |line| line.to_string() ).collect() );
+        request.body = body.to_string();
+        request
+    }
+
+    #[test]
+    fn test_record() {
+        let recorder = RequestRecorder::with_settings( 2, 8, &DEFAULT_REDACTED_HEADERS );
+        let request = request( &["POST /area?unit=m2 HTTP/1.1", "authorization: Basic c2VjcmV0", "Host: localhost"], "{\"corners\": []}" );
+        let response = Response::new( HttpStatus::Ok, "12.5".to_string(), vec![] );
+
+        // Nothing is recorded until recording is on.
+        recorder.record( &request, &response, Duration::from_millis( 3 ) );
+        assert!( recorder.recent().is_empty() );
+
+        recorder.set_enabled( true );
+        recorder.record( &request, &response, Duration::from_millis( 3 ) );
+        let recent = recorder.recent();
+        assert_eq!( recent.len(), 1 );
+        let record = &recent[0];
+        assert_eq!( record.get( "method" ).and_then( JsonValue::as_str ), Some( "POST" ) );
+        assert_eq!( record.get( "target" ).and_then( JsonValue::as_str ), Some( "/area?unit=m2" ) );
+        assert_eq!( record.get( "status" ).and_then( JsonValue::as_str ), Some( "200 OK" ) );
+        assert_eq!( record.get( "duration_ms" ).and_then( JsonValue::as_f64 ), Some( 3.0 ) );
+        let headers = record.get( "request_headers" ).unwrap();
+        assert_eq!( headers.get( "authorization" ).and_then( JsonValue::as_str ), Some( REDACTED ) );
+        assert_eq!( headers.get( "Host" ).and_then( JsonValue::as_str ), Some( "localhost" ) );
+        assert_eq!( record.get( "request_body" ).and_then( JsonValue::as_str ), Some( "{\"corner... (15 bytes)" ) );
+        assert_eq!( record.get( "response_body" ).and_then( JsonValue::as_str ), Some( "12.5" ) );
+    }
+
+    #[test]
+    fn test_recent_capacity_and_file() {
+        let path = std::env::temp_dir().join( format!("area-calculator-recording-{}.jsonl", std::process::id()) );
+        let _ = std::fs::remove_file( &path );
+        let recorder = RequestRecorder::new( 2 );
+        recorder.set_enabled( true );
+        recorder.set_file( Some( path.clone() ) );
+
+        let response = Response::new( HttpStatus::NotFound, String::new(), vec![] );
+        for target in ["/a", "/b", "/c"] {
+            recorder.record( &request( &[&format!("GET {target} HTTP/1.1")], "" ), &response, Duration::ZERO );
+        }
+
+        // Only the newest records are kept in memory, but all of them are in the file.
+        let targets: Vec<String> = recorder.recent().iter()
+            .map( |record| record.get( "target" ).and_then( JsonValue::as_str ).unwrap().to_string() )
+            .collect();
+        assert_eq!( targets, vec![ "/b", "/c" ] );
+        let lines: Vec<JsonValue> = std::fs::read_to_string( &path ).unwrap()
+            .lines()
+            .map( |line| JsonValue::parse( line ).unwrap() )
+            .collect();
+        assert_eq!( lines.len(), 3 );
+        assert_eq!( lines[0].get( "target" ).and_then( JsonValue::as_str ), Some( "/a" ) );
+        let _ = std::fs::remove_file( &path );
+    }
+}
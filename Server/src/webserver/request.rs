@@ -1,58 +1,557 @@
-use std::{fmt, str::FromStr};
+use std::{error::Error, fmt, io::BufRead, str::FromStr};
 
 use tracing::info;
 
 use super::http_method::HttpMethod;
+use super::http_version::HttpVersion;
+use super::unicode::normalize;
+
+/// The default maximum length of a request target in bytes.
+pub const DEFAULT_MAX_TARGET_BYTES: usize = 8 * 1024;
 
 /// Represents an HTTP request.
+/// The path is percent-decoded and without the query, which is parsed into name and value pairs.
+/// The path is also normalized with `unicode::normalize`, so both ways of writing an accented
+/// letter match the same route. The request target is kept exactly as the client sent it.
+#[derive(Clone)]
 pub struct Request {
     pub method: HttpMethod,
     pub path: String,
+    pub query: Vec<(String, String)>,
+    pub raw_target: String,
+    pub version: HttpVersion,
     pub headers: Vec<(String, String)>,
     pub body: String
 }
 
+/// Errors in the request line of a request.
+#[derive(Debug)]
+pub enum RequestError {
+    InvalidUtf8,
+    TargetTooLong { max_target_bytes: usize },
+}
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RequestError::InvalidUtf8 => write!(f, "The request target is not valid UTF-8."),
+            RequestError::TargetTooLong { max_target_bytes } => write!(f, "The request target is longer than {} bytes.", max_target_bytes),
+        }
+    }
+}
+impl Error for RequestError {}
+
+/// Errors that can happen while reading the body of a request.
+#[derive(Debug)]
+pub enum BodyError {
+    TooLarge { max_body_bytes: usize },
+    Malformed( String ),
+    Io( std::io::Error ),
+}
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BodyError::TooLarge { max_body_bytes } => write!(f, "The request body is larger than {} bytes.", max_body_bytes),
+            BodyError::Malformed( reason ) => write!(f, "The request body is malformed: {}", reason),
+            BodyError::Io( error ) => write!(f, "Could not read the request body: {}", error),
+        }
+    }
+}
+impl Error for BodyError {}
+impl From<std::io::Error> for BodyError {
+    fn from( error: std::io::Error ) -> Self {
+        BodyError::Io( error )
+    }
+}
+
 /// This struct represents an HTTP request.
 /// It contains the method, path, headers, and body of the request.
 /// It can be parsed from a raw request string.
 impl Request {
 
     /// Creates a new Request from a raw request string.
+    /// Percent-encoded bytes that are not valid UTF-8 are replaced with U+FFFD, use `try_new` to reject them.
+    /// The decoded path and query are cut to `DEFAULT_MAX_TARGET_BYTES`.
     /// 
     /// # Arguments
     /// * `raw_request` - A vector of strings representing the raw request, where the first element is the request line and the subsequent elements are headers.
     pub fn new( raw_request: &Vec<String> ) -> Request {
+        Request::parse( raw_request, false, DEFAULT_MAX_TARGET_BYTES ).unwrap()
+    }
+
+    /// Creates a new Request from a raw request string, like `new`.
+    ///
+    /// # Arguments
+    /// * `raw_request` - The request line and the headers.
+    ///
+    /// # Returns
+    /// An error if the decoded path or query is not valid UTF-8,
+    /// or the request target is longer than `DEFAULT_MAX_TARGET_BYTES`.
+    pub fn try_new( raw_request: &Vec<String> ) -> Result<Request, RequestError> {
+        Request::parse( raw_request, true, DEFAULT_MAX_TARGET_BYTES )
+    }
+
+    /// Creates a new Request from a raw request string, like `try_new`, with a maximum length for the request target.
+    ///
+    /// # Arguments
+    /// * `raw_request` - The request line and the headers.
+    /// * `max_target_bytes` - The maximum length of the request target in bytes.
+    ///
+    /// # Returns
+    /// An error if the decoded path or query is not valid UTF-8, or the request target is too long.
+    pub fn try_new_with_max_target( raw_request: &Vec<String>, max_target_bytes: usize ) -> Result<Request, RequestError> {
+        Request::parse( raw_request, true, max_target_bytes )
+    }
+
+    /// Parse the request line and headers.
+    /// A strict parse fails if the decoded path or query is not valid UTF-8 or the target is too long,
+    /// otherwise invalid bytes are replaced and the decoded path and query are cut to the maximum length.
+    fn parse( raw_request: &Vec<String>, strict: bool, max_target_bytes: usize ) -> Result<Request, RequestError> {
 
         // Parse the path and method.
         let first_line = raw_request[0].split_whitespace().collect::<Vec<_>>();
         let method = first_line[0];
-        let path = first_line[1];
+        let target = first_line[1];
+        if strict && target.len() > max_target_bytes {
+            return Err( RequestError::TargetTooLong { max_target_bytes } );
+        }
+        let (raw_path, raw_query) = target.split_once('?').unwrap_or( (target, "") );
 
         // Parse the method.
         let http_method = HttpMethod::from_str(method).unwrap();
 
+        // Parse the version, requests without a known version are treated as HTTP/1.1.
+        let version = first_line.get(2).and_then( |version| HttpVersion::from_str(version).ok() ).unwrap_or_default();
+
         // Parse headers.
         let mut headers = Vec::new();
         for header in raw_request.iter().skip(1) {
-            let header_parts = header.split(":").collect::<Vec<_>>();
-            headers.push( (header_parts[0].to_string(), header_parts[1].to_string()) );
+            if let Some( (name, value) ) = header.split_once(':') {
+                headers.push( (name.trim().to_string(), value.trim().to_string()) );
+            }
         }
 
-        // TODO: Parse the body.
+        // Decode the path and the query.
+        let decode = |text: &str, plus_as_space: bool| match String::from_utf8( percent_decode( text, plus_as_space, max_target_bytes ) ) {
+            Ok( decoded ) => Ok( decoded ),
+            Err( _ ) if strict => Err( RequestError::InvalidUtf8 ),
+            Err( e ) => Ok( String::from_utf8_lossy( e.as_bytes() ).into_owned() ),
+        };
+        let path = normalize( &decode( raw_path, false )? );
+        let mut query = Vec::new();
+        for parameter in raw_query.split('&').filter( |parameter| !parameter.is_empty() ) {
+            let (name, value) = parameter.split_once('=').unwrap_or( (parameter, "") );
+            query.push( (decode( name, true )?, decode( value, true )?) );
+        }
 
+        // The body is read separately with read_body.
         // Create and return the Request object.
-        Request {
+        Ok( Request {
             method: http_method,
-            path: path.to_string(),
+            path,
+            query,
+            raw_target: target.to_string(),
+            version,
             headers: headers,
             body: String::new()
+        } )
+    }
+
+    /// Get the path as the client sent it, without decoding and without the query.
+    pub fn raw_path( &self ) -> &str {
+        self.raw_target.split('?').next().unwrap_or("")
+    }
+
+    /// Get the segments of the decoded path, without empty segments, so both `/a/b/` and `/a//b`
+    /// give `["a", "b"]` and `/` gives no segments.
+    /// A percent-encoded slash, `%2F`, is decoded in the path and separates segments too.
+    pub fn path_segments( &self ) -> Vec<&str> {
+        self.path
+            .split('/')
+            .filter( |segment| !segment.is_empty() )
+            .collect()
+    }
+
+    /// Get the value of a query parameter.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the parameter.
+    ///
+    /// # Returns
+    /// The decoded value of the first parameter with the name, or `None` if there is no such parameter.
+    pub fn query_param( &self, name: &str ) -> Option<&str> {
+        self.query
+            .iter()
+            .find( |(key, _)| key == name )
+            .map( |(_, value)| value.as_str() )
+    }
+
+    /// Get the value of a header. Header names are case-insensitive.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the header.
+    ///
+    /// # Returns
+    /// The value of the first header with the name, or `None` if there is no such header.
+    pub fn header( &self, name: &str ) -> Option<&str> {
+        self.headers
+            .iter()
+            .find( |(key, _)| key.eq_ignore_ascii_case(name) )
+            .map( |(_, value)| value.as_str() )
+    }
+
+    /// Get the values of all headers with a name, in the order they were sent.
+    /// Header names are case-insensitive.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the header.
+    ///
+    /// # Returns
+    /// The values, or an empty vector if there is no such header.
+    pub fn headers_all( &self, name: &str ) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter( |(key, _)| key.eq_ignore_ascii_case(name) )
+            .map( |(_, value)| value.as_str() )
+            .collect()
+    }
+
+    /// Get the values of all headers with a name joined with commas, which is how a header
+    /// that is sent many times is read, like `Accept-Encoding`.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the header.
+    ///
+    /// # Returns
+    /// The joined values, or `None` if there is no such header.
+    pub fn header_joined( &self, name: &str ) -> Option<String> {
+        let values = self.headers_all( name );
+        if values.is_empty() {
+            return None;
         }
+        Some( values.join(", ") )
+    }
+
+    /// Get the length of the body from the `Content-Length` header.
+    ///
+    /// # Returns
+    /// The length in bytes, or `None` if there is no such header or it is not a number.
+    pub fn content_length( &self ) -> Option<usize> {
+        self.header("Content-Length").and_then( |value| value.parse().ok() )
+    }
+
+    /// Get the media type of the body from the `Content-Type` header, like `application/json`.
+    ///
+    /// # Returns
+    /// The value of the header without parameters such as `charset`, or `None` if there is no such header.
+    pub fn content_type( &self ) -> Option<&str> {
+        self.header("Content-Type").map( |value| value.split(';').next().unwrap_or("").trim() )
+    }
+
+    /// Read the body of the request from the reader, which should be positioned right after the headers.
+    /// Bodies sent with `Transfer-Encoding: chunked` are assembled from their chunks, otherwise the
+    /// `Content-Length` header tells how much to read. Requests with neither have an empty body.
+    ///
+    /// # Arguments
+    /// * `reader` - The reader to read the body from.
+    /// * `max_body_bytes` - The maximum size of the body in bytes.
+    ///
+    /// # Returns
+    /// An error if the body is too large, malformed, or could not be read.
+    pub fn read_body<R: BufRead>( &mut self, reader: &mut R, max_body_bytes: usize ) -> Result<(), BodyError> {
+
+        // Read the raw bytes of the body.
+        let chunked = self.header("Transfer-Encoding")
+            .is_some_and( |value| value.eq_ignore_ascii_case("chunked") );
+        let bytes = if chunked {
+            read_chunked_body( reader, max_body_bytes )?
+        } else {
+            read_sized_body( reader, self.header("Content-Length"), max_body_bytes )?
+        };
+
+        // The body is handled as text.
+        self.body = String::from_utf8( bytes )
+            .map_err( |_| BodyError::Malformed( "body is not valid UTF-8".to_string() ) )?;
+        Ok(())
     }
 }
 
+/// Read a body whose size is given by the `Content-Length` header.
+fn read_sized_body<R: BufRead>( reader: &mut R, content_length: Option<&str>, max_body_bytes: usize ) -> Result<Vec<u8>, BodyError> {
+
+    // No Content-Length means there is no body.
+    let Some( content_length ) = content_length else {
+        return Ok( Vec::new() );
+    };
+    let length: usize = content_length.parse()
+        .map_err( |_| BodyError::Malformed( format!("invalid Content-Length '{}'", content_length) ) )?;
+    if length > max_body_bytes {
+        return Err( BodyError::TooLarge { max_body_bytes } );
+    }
+
+    // Read exactly the announced amount of bytes.
+    let mut body = vec![0; length];
+    reader.read_exact( &mut body )?;
+    Ok( body )
+}
+
+/// Read a body sent with `Transfer-Encoding: chunked`.
+/// Each chunk starts with its size in hex on its own line and is followed by a line break.
+/// The body ends with a chunk of size zero, optionally followed by trailer headers and an empty line.
+fn read_chunked_body<R: BufRead>( reader: &mut R, max_body_bytes: usize ) -> Result<Vec<u8>, BodyError> {
+    let mut body: Vec<u8> = Vec::new();
+    loop {
+
+        // Parse the chunk size, ignoring any chunk extensions after ';'.
+        let mut size_line = String::new();
+        reader.read_line( &mut size_line )?;
+        let size_text = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix( size_text, 16 )
+            .map_err( |_| BodyError::Malformed( format!("invalid chunk size '{}'", size_text) ) )?;
+
+        // The last chunk is empty. Skip the trailer headers until the empty line.
+        if size == 0 {
+            loop {
+                let mut trailer = String::new();
+                if reader.read_line( &mut trailer )? == 0 || trailer.trim().is_empty() {
+                    return Ok( body );
+                }
+            }
+        }
+
+        // Check the size before reading the chunk. The size comes from the client,
+        // so it is compared to the room left instead of added to the body length.
+        if size > max_body_bytes.saturating_sub( body.len() ) {
+            return Err( BodyError::TooLarge { max_body_bytes } );
+        }
+
+        // Read the chunk and the line break after it.
+        let start = body.len();
+        body.resize( start + size, 0 );
+        reader.read_exact( &mut body[start..] )?;
+        let mut line_break = [0; 2];
+        reader.read_exact( &mut line_break )?;
+        if &line_break != b"\r\n" {
+            return Err( BodyError::Malformed( "chunk is not followed by a line break".to_string() ) );
+        }
+    }
+}
+
+/// Decode the `%XX` escapes in a path or query. Invalid escapes are kept as they are.
+///
+/// # Arguments
+/// * `text` - The text to decode.
+/// * `plus_as_space` - Whether `+` means a space, as it does in queries.
+/// * `max_bytes` - The maximum length of the decoded text, the rest of the text is left out.
+fn percent_decode( text: &str, plus_as_space: bool, max_bytes: usize ) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity( bytes.len().min( max_bytes ) );
+    let mut i = 0;
+    while i < bytes.len() && decoded.len() < max_bytes {
+        let escaped = bytes.get( i + 1..i + 3 )
+            .filter( |_| bytes[i] == b'%' )
+            .and_then( |hex| std::str::from_utf8( hex ).ok() )
+            .and_then( |hex| u8::from_str_radix( hex, 16 ).ok() );
+        match escaped {
+            Some( byte ) => {
+                decoded.push( byte );
+                i += 3;
+            }
+            None => {
+                decoded.push( if plus_as_space && bytes[i] == b'+' { b' ' } else { bytes[i] } );
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
 /// Implement the Display trait for Request to allow easy printing.
+/// The request target is shown as the client sent it.
 impl fmt::Display for Request {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{method} {path}", method=self.method, path=self.path)
+        write!(f, "{method} {target}", method=self.method, target=self.raw_target)
     }
-}
\ No newline at end of file
+}
+
+
+// Unit tests for Request.
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Create a request from the request line and headers.
+    fn request( lines: &[&str] ) -> Request {
+        Request::new( &lines.iter().map( |line| line.to_string() ).collect() )
+    }
+
+    #[test]
+    fn test_raw_target() {
+        let request = request( &["GET /shapes/my%20shape?name=a%20b&unit=m2&flag HTTP/1.1"] );
+        assert_eq!( request.raw_target, "/shapes/my%20shape?name=a%20b&unit=m2&flag" );
+        assert_eq!( request.raw_path(), "/shapes/my%20shape" );
+        assert_eq!( request.path, "/shapes/my shape" );
+        assert_eq!( request.query_param("name"), Some("a b") );
+        assert_eq!( request.query_param("unit"), Some("m2") );
+        assert_eq!( request.query_param("flag"), Some("") );
+        assert_eq!( request.query_param("missing"), None );
+        assert_eq!( request.to_string(), "GET /shapes/my%20shape?name=a%20b&unit=m2&flag" );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!( percent_decode( "a%2Fb%2fc", false, usize::MAX ), b"a/b/c" );
+        assert_eq!( percent_decode( "caf%C3%A9", false, usize::MAX ), "café".as_bytes() );
+        assert_eq!( percent_decode( "100%", false, usize::MAX ), b"100%" );
+        assert_eq!( percent_decode( "%zz%4", false, usize::MAX ), b"%zz%4" );
+        assert_eq!( percent_decode( "a+b", false, usize::MAX ), b"a+b" );
+        assert_eq!( percent_decode( "a+b", true, usize::MAX ), b"a b" );
+        assert_eq!( percent_decode( "%E9", false, usize::MAX ), [ 0xe9 ] );
+        assert_eq!( percent_decode( "%41%42%43", false, 2 ), b"AB" );
+    }
+
+    #[test]
+    fn test_unicode_path() {
+        let composed = request( &["GET /caf%C3%A9 HTTP/1.1"] );
+        let decomposed = request( &["GET /cafe%CC%81 HTTP/1.1"] );
+        assert_eq!( composed.path, "/caf\u{00E9}" );
+        assert_eq!( decomposed.path, "/caf\u{00E9}" );
+        assert_eq!( decomposed.raw_path(), "/cafe%CC%81" );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let lines = vec![ "GET /caf%E9?q=1 HTTP/1.1".to_string() ];
+        assert!( matches!( Request::try_new( &lines ), Err( RequestError::InvalidUtf8 ) ) );
+        assert_eq!( Request::new( &lines ).path, "/caf\u{FFFD}" );
+
+        let lines = vec![ "GET /?name=%FF HTTP/1.1".to_string() ];
+        assert!( Request::try_new( &lines ).is_err() );
+    }
+
+    #[test]
+    fn test_target_too_long() {
+        let lines = vec![ format!("GET /{}?q=1 HTTP/1.1", "a".repeat( 20 )) ];
+        assert!( matches!(
+            Request::try_new_with_max_target( &lines, 16 ),
+            Err( RequestError::TargetTooLong { max_target_bytes: 16 } )
+        ) );
+        assert!( Request::try_new_with_max_target( &lines, 32 ).is_ok() );
+
+        let lines = vec![ format!("GET /{} HTTP/1.1", "%41".repeat( DEFAULT_MAX_TARGET_BYTES )) ];
+        assert!( Request::try_new( &lines ).is_err() );
+        assert_eq!( Request::new( &lines ).path.len(), DEFAULT_MAX_TARGET_BYTES );
+    }
+
+    #[test]
+    fn test_header_case_insensitive() {
+        let request = request( &["GET / HTTP/1.1", "Host: localhost:8080", "content-type: text/plain"] );
+        assert_eq!( request.header("host"), Some("localhost:8080") );
+        assert_eq!( request.header("Content-Type"), Some("text/plain") );
+        assert_eq!( request.header("Accept"), None );
+    }
+
+    #[test]
+    fn test_content_length() {
+        assert_eq!( request( &["POST /area HTTP/1.1", "content-length: 42"] ).content_length(), Some( 42 ) );
+        assert_eq!( request( &["POST /area HTTP/1.1"] ).content_length(), None );
+        assert_eq!( request( &["POST /area HTTP/1.1", "Content-Length: 4x"] ).content_length(), None );
+        assert_eq!( request( &["POST /area HTTP/1.1", "Content-Length: -1"] ).content_length(), None );
+    }
+
+    #[test]
+    fn test_content_type() {
+        let request_with = |value: &str| request( &["POST /area HTTP/1.1", &format!("Content-Type: {value}")] );
+        assert_eq!( request_with( "application/json" ).content_type(), Some( "application/json" ) );
+        assert_eq!( request_with( "text/plain ; charset=utf-8" ).content_type(), Some( "text/plain" ) );
+        assert_eq!( request( &["POST /area HTTP/1.1"] ).content_type(), None );
+    }
+
+    #[test]
+    fn test_read_body_content_length() {
+        let mut request = request( &["POST /area HTTP/1.1", "Content-Length: 5"] );
+        let mut reader = Cursor::new( "hello, and more" );
+        request.read_body( &mut reader, 1024 ).unwrap();
+        assert_eq!( request.body, "hello" );
+    }
+
+    #[test]
+    fn test_read_body_none() {
+        let mut request = request( &["GET / HTTP/1.1"] );
+        let mut reader = Cursor::new( "" );
+        request.read_body( &mut reader, 1024 ).unwrap();
+        assert_eq!( request.body, "" );
+    }
+
+    #[test]
+    fn test_read_body_chunked() {
+        let mut request = request( &["POST /area HTTP/1.1", "Transfer-Encoding: chunked"] );
+        let mut reader = Cursor::new( "4\r\nWiki\r\n7;name=value\r\npedia i\r\nB\r\nn \r\nchunks.\r\n0\r\nExpires: never\r\n\r\n" );
+        request.read_body( &mut reader, 1024 ).unwrap();
+        assert_eq!( request.body, "Wikipedia in \r\nchunks." );
+    }
+
+    #[test]
+    fn test_read_body_chunked_too_large() {
+        let mut request = request( &["POST /area HTTP/1.1", "Transfer-Encoding: chunked"] );
+        let mut reader = Cursor::new( "4\r\nWiki\r\n4\r\npedi\r\n0\r\n\r\n" );
+        let result = request.read_body( &mut reader, 6 );
+        assert!( matches!( result, Err( BodyError::TooLarge { max_body_bytes: 6 } ) ) );
+    }
+
+    #[test]
+    fn test_read_body_chunked_huge_size() {
+        // A huge chunk size does not overflow, even after a chunk has been read.
+        let mut huge = request( &["POST /area HTTP/1.1", "Transfer-Encoding: chunked"] );
+        let mut reader = Cursor::new( "4\r\nWiki\r\nffffffffffffffff\r\npedi\r\n0\r\n\r\n" );
+        let result = huge.read_body( &mut reader, usize::MAX );
+        assert!( matches!( result, Err( BodyError::TooLarge { max_body_bytes: usize::MAX } ) ) );
+        let mut reader = Cursor::new( "ffffffffffffffff\r\npedi\r\n0\r\n\r\n" );
+        assert!( matches!( huge.read_body( &mut reader, 1024 ), Err( BodyError::TooLarge { .. } ) ) );
+    }
+
+    #[test]
+    fn test_read_body_chunked_malformed() {
+        let mut request = request( &["POST /area HTTP/1.1", "Transfer-Encoding: chunked"] );
+        let mut reader = Cursor::new( "nope\r\nWiki\r\n0\r\n\r\n" );
+        let result = request.read_body( &mut reader, 1024 );
+        assert!( matches!( result, Err( BodyError::Malformed( _ ) ) ) );
+    }
+
+    #[test]
+    fn test_read_body_content_length_too_large() {
+        let mut request = request( &["POST /area HTTP/1.1", "Content-Length: 2048"] );
+        let mut reader = Cursor::new( "" );
+        let result = request.read_body( &mut reader, 1024 );
+        assert!( matches!( result, Err( BodyError::TooLarge { max_body_bytes: 1024 } ) ) );
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!( request( &["GET / HTTP/1.0"] ).version, HttpVersion::Http10 );
+        assert_eq!( request( &["GET / HTTP/1.1"] ).version, HttpVersion::Http11 );
+        assert_eq!( request( &["GET / HTTP/2"] ).version, HttpVersion::Http11 );
+        assert_eq!( request( &["GET /"] ).version, HttpVersion::Http11 );
+    }
+
+    #[test]
+    fn test_headers_all() {
+        let request = request( &["GET / HTTP/1.1", "Accept-Encoding: br", "Via: 1.1 proxy", "accept-encoding: gzip;q=0.5"] );
+        assert_eq!( request.header("Accept-Encoding"), Some("br") );
+        assert_eq!( request.headers_all("Accept-Encoding"), vec!["br", "gzip;q=0.5"] );
+        assert_eq!( request.header_joined("Accept-Encoding").as_deref(), Some("br, gzip;q=0.5") );
+        assert_eq!( request.headers_all("Via"), vec!["1.1 proxy"] );
+        assert!( request.headers_all("Accept").is_empty() );
+        assert_eq!( request.header_joined("Accept"), None );
+    }
+
+    #[test]
+    fn test_path_segments() {
+        assert!( request( &["GET / HTTP/1.1"] ).path_segments().is_empty() );
+        assert_eq!( request( &["GET /a/b HTTP/1.1"] ).path_segments(), vec!["a", "b"] );
+        assert_eq!( request( &["GET /a//b/ HTTP/1.1"] ).path_segments(), vec!["a", "b"] );
+        assert_eq!( request( &["GET /shapes/my%20shape?unit=m2 HTTP/1.1"] ).path_segments(), vec!["shapes", "my shape"] );
+        assert_eq!( request( &["GET /a%2Fb HTTP/1.1"] ).path_segments(), vec!["a", "b"] );
+    }
+}
@@ -1,12 +1,71 @@
+use std::error::Error;
 use std::fmt;
 
+use super::gzip;
 use super::http_status::HttpStatus;
+use super::http_version::HttpVersion;
+use super::json::JsonValue;
+
+/// The formats a message can be rendered in for the client.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BodyFormat {
+    Html,
+    Json,
+    Text,
+}
+
+/// Implement the BodyFormat enum.
+impl BodyFormat {
+
+    /// Picks the format the client prefers from the value of an `Accept` header.
+    /// Media types are tried in order of their quality value, and HTML is used if none of them is supported.
+    ///
+    /// # Arguments
+    /// * `accept` - The value of the `Accept` header, if the request had one.
+    pub fn negotiate( accept: Option<&str> ) -> BodyFormat {
+
+        // Parse the media types and their quality values.
+        let mut media_types: Vec<(&str, f64)> = accept
+            .unwrap_or("")
+            .split(',')
+            .map( |media_type| {
+                let mut parts = media_type.split(';');
+                let name = parts.next().unwrap_or("").trim();
+                let quality = parts
+                    .filter_map( |parameter| parameter.trim().strip_prefix("q=") )
+                    .find_map( |q| q.trim().parse::<f64>().ok() )
+                    .unwrap_or(1.0);
+                (name, quality)
+            })
+            .collect();
+
+        // Try the preferred types first. The sort is stable, so equal qualities keep their order.
+        media_types.sort_by( |a, b| b.1.total_cmp( &a.1 ) );
+        for (name, quality) in media_types {
+            if quality <= 0.0 {
+                continue;
+            }
+            match name.to_ascii_lowercase().as_str() {
+                "text/html" | "text/*" | "*/*" => return BodyFormat::Html,
+                "application/json" => return BodyFormat::Json,
+                "text/plain" => return BodyFormat::Text,
+                _ => {}
+            }
+        }
+        BodyFormat::Html
+    }
+}
 
 /// Represents an HTTP response.
+/// A binary body, like an image, is sent instead of the text body when there is one.
+/// The version is written in the status line and is HTTP/1.1 unless it is set to the version of the request.
+#[derive(Clone)]
 pub struct Response {
     pub headers: Vec<(String, String)>,
     pub status: HttpStatus,
-    pub body: String
+    pub version: HttpVersion,
+    pub body: String,
+    pub binary_body: Option<Vec<u8>>
 }
 
 /// This struct represents an HTTP response.
@@ -27,18 +86,277 @@ impl Response {
         Response {
             headers,
             status,
-            body
+            version: HttpVersion::default(),
+            body,
+            binary_body: None
         }
     }
 
+    /// Creates a new Response with a binary body, like an image.
+    /// The `Content-Type` and `Content-Length` headers are set for the body.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `bytes` - The body of the response.
+    /// * `content_type` - The media type of the body, like `image/png`.
+    pub fn binary( status: HttpStatus, bytes: Vec<u8>, content_type: &str ) -> Response {
+        let length = bytes.len();
+        let mut response = Response::new(
+            status,
+            String::new(),
+            vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                ("Content-Length".to_string(), length.to_string()),
+            ]
+        );
+        response.binary_body = Some( bytes );
+        response
+    }
+
+    /// Creates a new 206 Partial Content Response with a range of the bytes of a resource.
+    /// The `Content-Type`, `Content-Length` and `Content-Range` headers are set for the range.
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes of the range.
+    /// * `start` - The position of the first byte of the range in the whole resource.
+    /// * `total_length` - The length of the whole resource.
+    /// * `content_type` - The media type of the resource.
+    pub fn partial_content( bytes: Vec<u8>, start: usize, total_length: usize, content_type: &str ) -> Response {
+        let end = start + bytes.len().saturating_sub(1);
+        let mut response = Response::binary( HttpStatus::PartialContent, bytes, content_type );
+        response.headers.push( ("Content-Range".to_string(), format!("bytes {start}-{end}/{total_length}")) );
+        response
+    }
+
+    /// Creates a new 416 Range Not Satisfiable Response for a range outside of a resource.
+    /// The `Content-Range` header tells the length of the resource.
+    ///
+    /// # Arguments
+    /// * `total_length` - The length of the whole resource.
+    pub fn range_not_satisfiable( total_length: usize ) -> Response {
+        let mut response = Response::from_message(
+            HttpStatus::RangeNotSatisfiable,
+            "The requested range is outside of the resource.",
+            BodyFormat::Text
+        );
+        response.headers.push( ("Content-Range".to_string(), format!("bytes */{total_length}")) );
+        response
+    }
+
+    /// Creates a new Response with a JSON body.
+    /// The `Content-Type` and `Content-Length` headers are set for the body.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `value` - The JSON value to send as the body.
+    pub fn json( status: HttpStatus, value: &JsonValue ) -> Response {
+        Response::json_body( status, value.to_string() )
+    }
+
+    /// Creates a new Response with a JSON body, like `json`, with the numbers in the body rounded
+    /// to the decimal places. Only the text of the body is rounded, not the value.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `value` - The JSON value to send as the body.
+    /// * `decimals` - The number of decimal places to round the numbers to.
+    pub fn json_rounded( status: HttpStatus, value: &JsonValue, decimals: usize ) -> Response {
+        Response::json_body( status, value.to_string_rounded( decimals ) )
+    }
+
+    /// Creates a new Response with JSON text as the body.
+    fn json_body( status: HttpStatus, body: String ) -> Response {
+        let length = body.len();
+        Response::new(
+            status,
+            body,
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Content-Length".to_string(), length.to_string()),
+            ]
+        )
+    }
+
+    /// Creates a new 401 Unauthorized Response asking the client to authenticate.
+    ///
+    /// # Arguments
+    /// * `www_authenticate` - The value of the `WWW-Authenticate` header, like `Basic realm="admin"`.
+    pub fn unauthorized( www_authenticate: &str ) -> Response {
+        let mut response = Response::from_message(
+            HttpStatus::Unauthorized,
+            "Authentication is required to access this page.",
+            BodyFormat::Html
+        );
+        response.headers.push( ("WWW-Authenticate".to_string(), www_authenticate.to_string()) );
+        response
+    }
+
+    /// Creates a new 403 Forbidden Response for a client that is not allowed to access a page.
+    pub fn forbidden() -> Response {
+        Response::from_message(
+            HttpStatus::Forbidden,
+            "You do not have permission to access this page.",
+            BodyFormat::Html
+        )
+    }
+
+    /// Creates a new Response describing an error, rendered as an HTML page.
+    ///
+    /// # Arguments
+    /// * `err` - The error, its `Display` text is shown to the client.
+    /// * `status` - The HTTP status of the response.
+    pub fn from_error( err: &dyn Error, status: HttpStatus ) -> Response {
+        Response::from_error_as( err, status, BodyFormat::Html )
+    }
+
+    /// Creates a new Response describing an error in the given format.
+    /// Use `BodyFormat::negotiate` with the `Accept` header of the request to pick the format.
+    ///
+    /// # Arguments
+    /// * `err` - The error, its `Display` text is shown to the client.
+    /// * `status` - The HTTP status of the response.
+    /// * `format` - The format of the body.
+    pub fn from_error_as( err: &dyn Error, status: HttpStatus, format: BodyFormat ) -> Response {
+        Response::from_message( status, &err.to_string(), format )
+    }
+
+    /// Creates a new Response with a message for the client, like the description of an error.
+    /// HTML messages use the same page layout as the built-in error pages.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `message` - The message shown to the client.
+    /// * `format` - The format of the body.
+    pub fn from_message( status: HttpStatus, message: &str, format: BodyFormat ) -> Response {
+        let (body, content_type) = match format {
+            BodyFormat::Html => ( html_page( status, message ), "text/html; charset=utf-8" ),
+            BodyFormat::Json => {
+                let json = JsonValue::Object( vec![
+                    ("status".to_string(), JsonValue::from( status.to_string() )),
+                    ("error".to_string(), JsonValue::from( message )),
+                ] );
+                ( json.to_string(), "application/json" )
+            }
+            BodyFormat::Text => ( format!("{status}\n{message}\n"), "text/plain; charset=utf-8" ),
+        };
+        let length = body.len();
+        Response::new(
+            status,
+            body,
+            vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                ("Content-Length".to_string(), length.to_string()),
+            ]
+        )
+    }
+
+    /// Replaces the body of the response, including a binary body.
+    /// An existing `Content-Length` header is updated to the length of the new body.
+    ///
+    /// # Arguments
+    /// * `body` - The new body of the response.
+    pub fn set_body( &mut self, body: String ) {
+        let length = body.len().to_string();
+        for (key, value) in self.headers.iter_mut() {
+            if key.eq_ignore_ascii_case("Content-Length") {
+                *value = length.clone();
+            }
+        }
+        self.body = body;
+        self.binary_body = None;
+    }
+
+    /// Appends text to the body of the response, so a handler can build the body piece by piece.
+    /// A binary body gets the bytes of the text. An existing `Content-Length` header is updated
+    /// to the length of the whole body, like in `set_body`.
+    ///
+    /// # Arguments
+    /// * `s` - The text to append.
+    pub fn append_body( &mut self, s: &str ) {
+        let length = match &mut self.binary_body {
+            Some( body ) => {
+                body.extend_from_slice( s.as_bytes() );
+                body.len()
+            }
+            None => {
+                self.body.push_str( s );
+                self.body.len()
+            }
+        };
+        for (key, value) in self.headers.iter_mut() {
+            if key.eq_ignore_ascii_case("Content-Length") {
+                *value = length.to_string();
+            }
+        }
+    }
+
+    /// Compresses the body with gzip if the client accepts it and the body is worth compressing.
+    /// Only the media types in `gzip::COMPRESSIBLE_CONTENT_TYPES` are compressed, others like images
+    /// are left as they are. The `Content-Encoding` and `Content-Length` headers are set for the
+    /// compressed body, and `Accept-Encoding` is added to the `Vary` header of responses that could be
+    /// compressed. Bodies larger than `gzip::MAX_COMPRESSED_BYTES` are sent as they are.
+    /// Ranges are never compressed, because their `Content-Range` counts the bytes of the body as it is.
+    ///
+    /// # Arguments
+    /// * `accept_encoding` - The value of the `Accept-Encoding` header of the request, if it had one.
+    /// # Returns
+    /// True if the body was compressed.
+    pub fn compress_for( &mut self, accept_encoding: Option<&str> ) -> bool {
+        if matches!( self.status, HttpStatus::PartialContent | HttpStatus::RangeNotSatisfiable ) {
+            return false;
+        }
+        let header = |name: &str| self.headers.iter().find( |(key, _)| key.eq_ignore_ascii_case( name ) ).map( |(_, value)| value.as_str() );
+        if !header("Content-Type").is_some_and( gzip::is_compressible ) || header("Content-Encoding").is_some() {
+            return false;
+        }
+        match self.headers.iter_mut().find( |(key, _)| key.eq_ignore_ascii_case("Vary") ) {
+            Some( (_, value) ) => {
+                let listed = value.split(',').any( |name| name.trim() == "*" || name.trim().eq_ignore_ascii_case("Accept-Encoding") );
+                if !listed {
+                    value.push_str( ", Accept-Encoding" );
+                }
+            }
+            None => self.headers.push( ("Vary".to_string(), "Accept-Encoding".to_string()) ),
+        }
+
+        let body = match &self.binary_body {
+            Some( bytes ) => bytes.as_slice(),
+            None => self.body.as_bytes(),
+        };
+        if body.len() < gzip::MIN_COMPRESSED_BYTES || body.len() > gzip::MAX_COMPRESSED_BYTES || !gzip::accepts_gzip( accept_encoding ) {
+            return false;
+        }
+        let compressed = gzip::compress( body );
+        let length = compressed.len().to_string();
+        self.headers.retain( |(key, _)| !key.eq_ignore_ascii_case("Content-Length") );
+        self.headers.push( ("Content-Encoding".to_string(), "gzip".to_string()) );
+        self.headers.push( ("Content-Length".to_string(), length) );
+        self.body = String::new();
+        self.binary_body = Some( compressed );
+        true
+    }
+
     /// Converts the response to a string representation.
-    /// This string can be sent over the network as an HTTP response.
+    /// This string can be sent over the network as an HTTP response, unless the body is binary.
+    /// A binary body that is not valid UTF-8 is shown with replacement characters.
     /// # Returns
     /// A string representation of the HTTP response.
     pub fn to_string(&self) -> String {
-        let status_line = format!("HTTP/1.1 {status}", status=self.status);
+        String::from_utf8_lossy( &self.to_bytes() ).into_owned()
+    }
+
+    /// Converts the response to the bytes sent over the network.
+    /// # Returns
+    /// The status line, headers and body of the HTTP response.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let status_line = format!("{version} {status}", version=self.version, status=self.status);
         let headers = self.headers.iter().map(|(key, value)| format!("{key}: {value}", key=key, value=value)).collect::<Vec<_>>().join("\r\n");
-        format!("{status_line}\r\n{headers}\r\n\r\n{body}", status_line=status_line, headers=headers, body=self.body)
+        let mut bytes = format!("{status_line}\r\n{headers}\r\n\r\n", status_line=status_line, headers=headers).into_bytes();
+        match &self.binary_body {
+            Some(body) => bytes.extend_from_slice(body),
+            None => bytes.extend_from_slice(self.body.as_bytes()),
+        }
+        bytes
     }
 }
 
@@ -47,4 +365,233 @@ impl fmt::Display for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{status}", status=self.status)
     }
-}
\ No newline at end of file
+}
+
+/// Create an HTML page showing a status and a message.
+fn html_page( status: HttpStatus, message: &str ) -> String {
+    let status = escape_html( &status.to_string() );
+    let message = escape_html( message );
+    format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>{status}</title>
+    <style>
+        body {{ font-family: sans-serif; background: #f8f8f8; color: #333; text-align: center; margin-top: 10%; }}
+        h1 {{ font-size: 3em; margin-bottom: 0.2em; }}
+        p {{ font-size: 1.2em; }}
+    </style>
+</head>
+<body>
+    <h1>{status}</h1>
+    <p>{message}</p>
+</body>
+</html>
+"#)
+}
+
+/// Escape the characters that have a special meaning in HTML.
+pub(super) fn escape_html( text: &str ) -> String {
+    let mut escaped = String::with_capacity( text.len() );
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+
+// Unit tests for Response.
+#[cfg(test)]
+mod tests {
+    use crate::geometry::angular_shape::ShapeError;
+
+    use super::*;
+
+    #[test]
+    fn test_from_error_json() {
+        let error = ShapeError::IndexOutOfBounds { index: 7, corner_count: 4 };
+        let response = Response::from_error_as( &error, HttpStatus::BadRequest, BodyFormat::Json );
+        assert_eq!( response.status, HttpStatus::BadRequest );
+        assert_eq!( response.body, r#"{"status":"400 Bad Request","error":"Corner index 7 is out of bounds, the shape has 4 corners."}"# );
+        assert!( response.headers.contains( &("Content-Type".to_string(), "application/json".to_string()) ) );
+        assert!( response.headers.contains( &("Content-Length".to_string(), response.body.len().to_string()) ) );
+    }
+
+    #[test]
+    fn test_from_error_html() {
+        let error = ShapeError::NotValidShape( "Lines <1> & <2> intersect.".into() );
+        let response = Response::from_error( &error, HttpStatus::BadRequest );
+        assert!( response.body.starts_with( "<!DOCTYPE html>" ) );
+        assert!( response.body.contains( "<h1>400 Bad Request</h1>" ) );
+        assert!( response.body.contains( "<p>The shape is not valid. Lines &lt;1&gt; &amp; &lt;2&gt; intersect.</p>" ) );
+        assert!( response.headers.contains( &("Content-Type".to_string(), "text/html; charset=utf-8".to_string()) ) );
+    }
+
+    #[test]
+    fn test_from_error_text() {
+        let error = ShapeError::InvalidDiagonal { from: 0, to: 1 };
+        let response = Response::from_error_as( &error, HttpStatus::BadRequest, BodyFormat::Text );
+        assert_eq!( response.body, "400 Bad Request\nThere is no diagonal from corner 0 to corner 1 inside the shape.\n" );
+    }
+
+    #[test]
+    fn test_set_body() {
+        let mut response = Response::json( HttpStatus::Ok, &JsonValue::from( "short" ) );
+        response.set_body( "a longer body".to_string() );
+        assert_eq!( response.body, "a longer body" );
+        assert!( response.headers.contains( &("Content-Length".to_string(), "13".to_string()) ) );
+
+        // No Content-Length header is added if there was none.
+        let mut response = Response::new( HttpStatus::Ok, String::new(), vec![] );
+        response.set_body( "body".to_string() );
+        assert!( response.headers.is_empty() );
+    }
+
+    #[test]
+    fn test_append_body() {
+        let mut response = Response::new( HttpStatus::Ok, String::new(), vec![ ("Content-Length".to_string(), "0".to_string()) ] );
+        for fragment in [ "<ul>", "<li>square</li>", "</ul>" ] {
+            response.append_body( fragment );
+        }
+        assert_eq!( response.body, "<ul><li>square</li></ul>" );
+        assert_eq!( response.headers, vec![ ("Content-Length".to_string(), "24".to_string()) ] );
+        assert!( response.to_string().ends_with( "\r\nContent-Length: 24\r\n\r\n<ul><li>square</li></ul>" ) );
+
+        // The header is updated for a binary body too, and not added if there was none.
+        let mut response = Response::binary( HttpStatus::Ok, vec![ 0xff ], "application/octet-stream" );
+        response.append_body( "ab" );
+        assert_eq!( response.binary_body, Some( vec![ 0xff, b'a', b'b' ] ) );
+        assert!( response.headers.contains( &("Content-Length".to_string(), "3".to_string()) ) );
+        assert_eq!( response.headers.len(), 2 );
+        let mut response = Response::new( HttpStatus::Ok, String::new(), vec![] );
+        response.append_body( "body" );
+        assert!( response.headers.is_empty() );
+    }
+
+    #[test]
+    fn test_binary() {
+        let bytes = vec![ 0x00, 0xff, 0x89, 0x50 ];
+        let response = Response::binary( HttpStatus::Ok, bytes.clone(), "image/png" );
+        assert!( response.headers.contains( &("Content-Type".to_string(), "image/png".to_string()) ) );
+        assert!( response.headers.contains( &("Content-Length".to_string(), "4".to_string()) ) );
+        assert!( response.to_bytes().ends_with( b"\r\n\r\n\x00\xff\x89\x50" ) );
+
+        // Setting a text body replaces the binary body.
+        let mut response = response;
+        response.set_body( "text".to_string() );
+        assert!( response.binary_body.is_none() );
+        assert!( response.to_bytes().ends_with( b"\r\n\r\ntext" ) );
+    }
+
+    #[test]
+    fn test_unauthorized() {
+        let response = Response::unauthorized( r#"Basic realm="admin""# );
+        assert!( response.to_string().starts_with( "HTTP/1.1 401 Unauthorized\r\n" ) );
+        assert!( response.headers.contains( &("WWW-Authenticate".to_string(), r#"Basic realm="admin""#.to_string()) ) );
+    }
+
+    #[test]
+    fn test_forbidden() {
+        let response = Response::forbidden();
+        assert!( response.to_string().starts_with( "HTTP/1.1 403 Forbidden\r\n" ) );
+        assert!( response.body.contains( "<h1>403 Forbidden</h1>" ) );
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!( BodyFormat::negotiate( None ), BodyFormat::Html );
+        assert_eq!( BodyFormat::negotiate( Some("application/json") ), BodyFormat::Json );
+        assert_eq!( BodyFormat::negotiate( Some("text/plain, application/json") ), BodyFormat::Text );
+        assert_eq!( BodyFormat::negotiate( Some("text/html;q=0.5, application/json;q=0.9") ), BodyFormat::Json );
+        assert_eq!( BodyFormat::negotiate( Some("image/png, */*;q=0.1") ), BodyFormat::Html );
+        assert_eq!( BodyFormat::negotiate( Some("image/png") ), BodyFormat::Html );
+        assert_eq!( BodyFormat::negotiate( Some("application/json;q=0") ), BodyFormat::Html );
+    }
+
+    #[test]
+    fn test_compress_for() {
+        let value = JsonValue::Array( vec![ JsonValue::from( "square" ); 20 ] );
+        let mut json = Response::json( HttpStatus::Ok, &value );
+        let original = json.body.clone();
+        assert!( json.compress_for( Some("gzip, deflate") ) );
+        let compressed = json.binary_body.clone().unwrap();
+        assert_eq!( &compressed[..2], &[0x1f, 0x8b] );
+        assert!( compressed.len() < original.len() );
+        assert!( json.headers.contains( &("Content-Encoding".to_string(), "gzip".to_string()) ) );
+        assert!( json.headers.contains( &("Content-Length".to_string(), compressed.len().to_string()) ) );
+        assert!( json.headers.contains( &("Vary".to_string(), "Accept-Encoding".to_string()) ) );
+
+        // An image is left as it is, even if the client accepts gzip.
+        let mut png = Response::binary( HttpStatus::Ok, [0x89, b'P', b'N', b'G'].repeat( 100 ), "image/png" );
+        assert!( !png.compress_for( Some("gzip") ) );
+        assert_eq!( png.binary_body.as_ref().unwrap().len(), 400 );
+        assert!( !png.headers.iter().any( |(key, _)| key == "Content-Encoding" || key == "Vary" ) );
+
+        // Nothing is compressed for clients that do not accept gzip.
+        let mut json = Response::json( HttpStatus::Ok, &value );
+        assert!( !json.compress_for( None ) );
+        assert_eq!( json.body, original );
+
+        // Accept-Encoding is added to a Vary header that is already there.
+        let mut json = Response::json( HttpStatus::Ok, &value );
+        json.headers.push( ("Vary".to_string(), "Origin".to_string()) );
+        assert!( json.compress_for( Some("gzip") ) );
+        assert!( json.headers.contains( &("Vary".to_string(), "Origin, Accept-Encoding".to_string()) ) );
+        assert_eq!( json.headers.iter().filter( |(key, _)| key == "Vary" ).count(), 1 );
+        let mut json = Response::json( HttpStatus::Ok, &value );
+        json.headers.push( ("Vary".to_string(), "accept-encoding".to_string()) );
+        json.compress_for( Some("gzip") );
+        assert!( json.headers.contains( &("Vary".to_string(), "accept-encoding".to_string()) ) );
+
+        // A body that is too large is sent as it is.
+        let mut large = Response::binary( HttpStatus::Ok, vec![b'a'; gzip::MAX_COMPRESSED_BYTES + 1], "text/plain" );
+        assert!( !large.compress_for( Some("gzip") ) );
+        assert_eq!( large.binary_body.as_ref().unwrap().len(), gzip::MAX_COMPRESSED_BYTES + 1 );
+        assert!( large.headers.contains( &("Vary".to_string(), "Accept-Encoding".to_string()) ) );
+    }
+
+    #[test]
+    fn test_partial_content() {
+        let response = Response::partial_content( b"world".to_vec(), 6, 11, "text/plain" );
+        let text = response.to_string();
+        assert!( text.starts_with( "HTTP/1.1 206 Partial Content\r\n" ), "{text}" );
+        assert!( text.contains( "Content-Range: bytes 6-10/11\r\n" ), "{text}" );
+        assert!( text.contains( "Content-Length: 5\r\n" ), "{text}" );
+        assert!( text.ends_with( "\r\n\r\nworld" ) );
+
+        let text = Response::range_not_satisfiable( 11 ).to_string();
+        assert!( text.starts_with( "HTTP/1.1 416 Range Not Satisfiable\r\n" ), "{text}" );
+        assert!( text.contains( "Content-Range: bytes */11\r\n" ), "{text}" );
+    }
+
+    #[test]
+    fn test_compress_for_range() {
+        let text = "a range of a text that is long enough to be compressed ".repeat( 4 );
+        let mut partial = Response::partial_content( text.clone().into_bytes(), 10, 1000, "text/plain" );
+        assert!( !partial.compress_for( Some("gzip") ) );
+        assert_eq!( partial.binary_body.as_deref(), Some( text.as_bytes() ) );
+        assert!( !partial.headers.iter().any( |(key, _)| key == "Content-Encoding" || key == "Vary" ) );
+
+        let mut not_satisfiable = Response::range_not_satisfiable( 1000 );
+        not_satisfiable.body = text.clone();
+        assert!( !not_satisfiable.compress_for( Some("gzip") ) );
+        assert_eq!( not_satisfiable.body, text );
+    }
+
+    #[test]
+    fn test_version() {
+        let mut response = Response::new( HttpStatus::Ok, "ok".to_string(), Vec::new() );
+        assert!( response.to_string().starts_with( "HTTP/1.1 200 OK\r\n" ) );
+
+        response.version = HttpVersion::Http10;
+        assert!( response.to_string().starts_with( "HTTP/1.0 200 OK\r\n" ) );
+        assert!( response.to_string().ends_with( "\r\n\r\nok" ) );
+    }
+}
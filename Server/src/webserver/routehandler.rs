@@ -1,9 +1,12 @@
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::http_method::HttpMethod;
+use super::json::JsonValue;
 use super::request::Request;
 use super::response::Response;
+use super::unicode::normalize;
 
 
 /// Helper trait for cloning trait objects
@@ -23,17 +26,33 @@ where
 
 /// Represents a route handler in the web server.
 /// It contains the HTTP method, path, path pattern, and the handler function.
+/// The optional name and description are only used to describe the route, for example in route listings.
+/// A route that matches by prefix also handles all paths under its path.
+/// A route that declares the content types it consumes only gets requests with one of those types.
+/// A cached route has its successful responses kept for the time to live.
+/// A route with a timeout gets 503 if its handler takes longer than the timeout.
+/// A route with its own body limit uses it instead of the limit of the server.
+/// An admin route only handles requests from the local machine or with the admin token of the server.
 pub struct RouteHandler {
     pub method: HttpMethod,
     pub path: String,
     pub path_pattern: String,
     pub handler: Arc<dyn HandlerFn>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub prefix_match: bool,
+    pub consumes: Vec<String>,
+    pub cache_ttl: Option<Duration>,
+    pub timeout: Option<Duration>,
+    pub max_body_bytes: Option<usize>,
+    pub admin_only: bool,
 }
 
 /// Implement the RouteHandler struct.
 impl RouteHandler {
 
     /// Creates a new RouteHandler with the specified HTTP method, path, and handler function.
+    /// The path is normalized like the paths of requests, see `unicode::normalize`.
     /// 
     /// # Arguments
     /// * `method` - The HTTP method (GET, POST, etc.) that this handler will respond to.
@@ -49,12 +68,140 @@ impl RouteHandler {
         // with named groups, so the handler can access the resorouce ids by name.
         RouteHandler {
             method: method,
-            path: path.to_string(),
-            path_pattern: path.to_string(),
+            path: normalize(path),
+            path_pattern: normalize(path),
             handler: handler,
+            name: None,
+            description: None,
+            prefix_match: false,
+            consumes: Vec::new(),
+            cache_ttl: None,
+            timeout: None,
+            max_body_bytes: None,
+            admin_only: false,
         }
     }
 
+    /// Makes the route handle all paths under its path, in addition to the path itself.
+    /// For example, a route for `/static` then also handles `/static/css/site.css`, but not `/statics`.
+    pub fn with_prefix_match(mut self) -> RouteHandler {
+        self.prefix_match = true;
+        self
+    }
+
+    /// Sets the media types of the request bodies the route accepts, like `application/json`.
+    /// Requests with another `Content-Type` are rejected with 415 Unsupported Media Type
+    /// before the handler is called. Routes without any types accept all requests.
+    ///
+    /// # Arguments
+    /// * `media_types` - The accepted media types, without parameters like `charset`.
+    pub fn consumes(mut self, media_types: &[&str]) -> RouteHandler {
+        self.consumes = media_types.iter().map(|media_type| media_type.to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// Checks if the route accepts a request body of the given content type.
+    /// Parameters like `charset` are ignored and the media type is compared case-insensitively.
+    ///
+    /// # Arguments
+    /// * `content_type` - The value of the `Content-Type` header, if the request had one.
+    ///
+    /// # Returns
+    /// * `true` if the route does not declare any types, or the media type is one of them.
+    pub fn accepts_content_type(&self, content_type: Option<&str>) -> bool {
+        if self.consumes.is_empty() {
+            return true;
+        }
+        let Some(content_type) = content_type else {
+            return false;
+        };
+        let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        return self.consumes.contains(&media_type);
+    }
+
+    /// Caches the successful responses of a GET route in memory, keyed by the path and query.
+    /// Cached responses are served without calling the handler until they expire or are
+    /// removed with `WebServer::invalidate_cache`. Responses with `Vary: Accept` are cached
+    /// separately for each `Accept` header.
+    ///
+    /// # Arguments
+    /// * `ttl` - How long a response is served from the cache.
+    pub fn cached(mut self, ttl: Duration) -> RouteHandler {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Limits how long the handler can take. The handler is run on its own thread, and if it
+    /// does not finish in time the client gets 503 Service Unavailable with a `Retry-After` header.
+    /// The timeout of the route is used instead of the handler timeout of the server, which answers
+    /// with 504 Gateway Timeout.
+    /// The thread is not killed: the handler keeps running and its response is discarded.
+    /// Every request to the route starts a thread. While `webserver::MAX_LATE_HANDLERS` handlers are
+    /// still running after their timeout, the route gets 503 without calling the handler.
+    ///
+    /// # Arguments
+    /// * `timeout` - How long to wait for the handler.
+    pub fn with_timeout(mut self, timeout: Duration) -> RouteHandler {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the size of the request bodies of the route, instead of the limit of the server.
+    /// Requests with a larger body get 413 Payload Too Large before the handler is called.
+    ///
+    /// # Arguments
+    /// * `max_body_bytes` - The maximum size of a request body in bytes.
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> RouteHandler {
+        self.max_body_bytes = Some(max_body_bytes);
+        self
+    }
+
+    /// Restricts the route to administrators, for routes that show or change the internals of the server.
+    /// Requests from other machines get 403 Forbidden, or 401 Unauthorized if the server has an admin
+    /// token and the request does not have it. See `WebServer::set_admin_token`.
+    pub fn with_admin_only(mut self) -> RouteHandler {
+        self.admin_only = true;
+        self
+    }
+
+    /// Sets the name of the route.
+    ///
+    /// # Arguments
+    /// * `name` - A short name for the route.
+    pub fn with_name(mut self, name: &str) -> RouteHandler {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the description of the route.
+    ///
+    /// # Arguments
+    /// * `description` - A description of what the route does.
+    pub fn with_description(mut self, description: &str) -> RouteHandler {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Describes the route as a JSON object with the method, path, path pattern, name, description
+    /// the media types it consumes and whether it is an admin route.
+    /// A missing name or description is null.
+    pub fn to_json(&self) -> JsonValue {
+        let optional = |value: &Option<String>| match value {
+            Some(value) => JsonValue::from(value.as_str()),
+            None => JsonValue::Null,
+        };
+        JsonValue::Object(vec![
+            ("method".to_string(), JsonValue::from(self.method.to_string())),
+            ("path".to_string(), JsonValue::from(self.path.as_str())),
+            ("path_pattern".to_string(), JsonValue::from(self.path_pattern.as_str())),
+            ("name".to_string(), optional(&self.name)),
+            ("description".to_string(), optional(&self.description)),
+            ("prefix_match".to_string(), JsonValue::from(self.prefix_match)),
+            ("consumes".to_string(), JsonValue::Array(self.consumes.iter().map(|media_type| JsonValue::from(media_type.as_str())).collect())),
+            ("admin_only".to_string(), JsonValue::from(self.admin_only)),
+        ])
+    }
+
     /// Checks if this route handler can handle the given HTTP method and path.
     /// 
     /// # Arguments
@@ -71,7 +218,16 @@ impl RouteHandler {
         }
 
         // Check if the path matches. TODO: Use regex and path_pattern.
-        return self.path == path;
+        if self.path == path {
+            return true;
+        }
+
+        // Check if the path is under the path of a prefix route.
+        if self.prefix_match {
+            let prefix = self.path.trim_end_matches('/');
+            return path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'));
+        }
+        return false;
     }
 }
 
@@ -95,9 +251,24 @@ impl Clone for RouteHandler {
             path: self.path.clone(),
             path_pattern: self.path_pattern.clone(),
             handler: Arc::clone(&self.handler),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            prefix_match: self.prefix_match,
+            consumes: self.consumes.clone(),
+            cache_ttl: self.cache_ttl,
+            timeout: self.timeout,
+            max_body_bytes: self.max_body_bytes,
+            admin_only: self.admin_only,
         }
     }
 }
 
+/// The function that handles the requests of a route.
+///
+/// Handlers are shared with the thread that serves the requests, so they must be `Send` and `Sync`,
+/// and any state they change must be behind a lock or an atomic. A handler that panics gets a
+/// 500 response and the server keeps serving, but a panic while holding a `Mutex` poisons it.
+/// Handlers that share state should recover a poisoned lock with `PoisonError::into_inner`
+/// when the state is still consistent, instead of panicking again on every following request.
 pub trait HandlerFn: Fn(Request) -> Response + Send + Sync {}
 impl<T> HandlerFn for T where T: Fn(Request) -> Response + Send + Sync {}
\ No newline at end of file
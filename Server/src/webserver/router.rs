@@ -0,0 +1,285 @@
+use super::http_method::HttpMethod;
+use super::routehandler::RouteHandler;
+
+/// Finds the route that handles a request.
+///
+/// A router keeps the routes of the server and decides which one handles a method and a path.
+/// All routers match the same way: a route for the exact path wins over prefix routes, and a longer
+/// prefix wins over a shorter one. They differ only in how fast they find the route.
+/// The web server checks that the routes are valid and unique before giving them to the router.
+pub trait Router: Send + Sync {
+
+    /// Find the route that handles the method and the path.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `path` - The decoded path of the request.
+    ///
+    /// # Returns
+    /// The matching route, or `None` if no route handles the request.
+    fn match_route(&self, method: HttpMethod, path: &str) -> Option<&RouteHandler>;
+
+    /// Get the routes of the router in the order they were added.
+    fn routes(&self) -> &[RouteHandler];
+
+    /// Replace all routes of the router.
+    ///
+    /// # Arguments
+    /// * `routes` - The new routes.
+    fn set_routes(&mut self, routes: Vec<RouteHandler>);
+
+    /// Add a route to the router.
+    ///
+    /// # Arguments
+    /// * `route` - The route to add.
+    fn add_route(&mut self, route: RouteHandler) {
+        let mut routes = self.routes().to_vec();
+        routes.push(route);
+        self.set_routes(routes);
+    }
+}
+
+/// Pick the best of the routes that handle a path, like the routers do.
+/// A route for the exact path wins over prefix routes, and a longer prefix wins over a shorter one.
+fn best_match<'a>(candidates: impl Iterator<Item = &'a RouteHandler>, path: &str) -> Option<&'a RouteHandler> {
+    candidates.max_by_key( |route| ( route.path == path, route.path.len() ) )
+}
+
+/// A router that tries every route in turn. Finding a route takes time in proportion
+/// to the number of routes, which is fast enough for most servers.
+#[derive(Clone, Default)]
+pub struct LinearRouter {
+    routes: Vec<RouteHandler>,
+}
+
+/// Implement the LinearRouter struct.
+impl LinearRouter {
+
+    /// Creates a new LinearRouter with the routes.
+    ///
+    /// # Arguments
+    /// * `routes` - The routes of the router.
+    pub fn new(routes: Vec<RouteHandler>) -> LinearRouter {
+        LinearRouter { routes }
+    }
+}
+
+/// Implement the Router trait for LinearRouter.
+impl Router for LinearRouter {
+    fn match_route(&self, method: HttpMethod, path: &str) -> Option<&RouteHandler> {
+        best_match( self.routes.iter().filter( |route| route.handles_path( method, path ) ), path )
+    }
+
+    fn routes(&self) -> &[RouteHandler] {
+        &self.routes
+    }
+
+    fn set_routes(&mut self, routes: Vec<RouteHandler>) {
+        self.routes = routes;
+    }
+
+    fn add_route(&mut self, route: RouteHandler) {
+        self.routes.push(route);
+    }
+}
+
+/// A node of the radix tree. The path of a node is the labels of the edges from the root to it.
+#[derive(Clone, Default)]
+struct RadixNode {
+    edges: Vec<(String, RadixNode)>,
+    exact: Vec<usize>,
+    prefix: Vec<usize>,
+}
+
+/// Implement the RadixNode struct.
+impl RadixNode {
+
+    /// Get the node for a key under this node, splitting edges and adding nodes as needed.
+    ///
+    /// # Arguments
+    /// * `key` - The rest of the path of the node, after the path of this node.
+    fn insert(&mut self, key: &str) -> &mut RadixNode {
+        if key.is_empty() {
+            return self;
+        }
+
+        // Follow the edge that shares the start of the key. The edges of a node all start
+        // with a different character, so there is at most one.
+        let shared = self.edges.iter().enumerate()
+            .map( |(i, (label, _))| (i, common_prefix_len(label, key)) )
+            .find( |(_, common)| *common > 0 );
+        let Some( (i, common) ) = shared else {
+            self.edges.push( (key.to_string(), RadixNode::default()) );
+            return &mut self.edges.last_mut().unwrap().1;
+        };
+
+        // Split the edge if the key ends or leaves it in the middle.
+        if common < self.edges[i].0.len() {
+            let (label, child) = self.edges.remove(i);
+            let middle = RadixNode {
+                edges: vec![ (label[common..].to_string(), child) ],
+                ..RadixNode::default()
+            };
+            self.edges.insert( i, (label[..common].to_string(), middle) );
+        }
+        self.edges[i].1.insert( &key[common..] )
+    }
+}
+
+/// Get the length in bytes of the common start of two strings, on a character boundary.
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip( b.chars() )
+        .take_while( |(x, y)| x == y )
+        .map( |(x, _)| x.len_utf8() )
+        .sum()
+}
+
+/// A router that keeps the paths of the routes in a radix tree, where each edge is labeled with
+/// the part of the path the routes under it share. Finding a route takes time in proportion to the
+/// length of the path instead of the number of routes, which helps servers with many routes.
+#[derive(Clone, Default)]
+pub struct RadixRouter {
+    routes: Vec<RouteHandler>,
+    root: RadixNode,
+}
+
+/// Implement the RadixRouter struct.
+impl RadixRouter {
+
+    /// Creates a new RadixRouter with the routes.
+    ///
+    /// # Arguments
+    /// * `routes` - The routes of the router.
+    pub fn new(routes: Vec<RouteHandler>) -> RadixRouter {
+        let mut router = RadixRouter::default();
+        router.set_routes(routes);
+        router
+    }
+}
+
+/// Implement the Router trait for RadixRouter.
+impl Router for RadixRouter {
+    fn match_route(&self, method: HttpMethod, path: &str) -> Option<&RouteHandler> {
+
+        // Walk down the tree along the path. A prefix route matches when the rest of the path
+        // starts a new segment, and the routes of the node at the end of the path match exactly.
+        let mut candidates: Vec<usize> = Vec::new();
+        let mut node = &self.root;
+        let mut depth = 0;
+        loop {
+            let rest = &path[depth..];
+            if rest.starts_with('/') {
+                candidates.extend( &node.prefix );
+            }
+            if rest.is_empty() {
+                candidates.extend( &node.exact );
+                break;
+            }
+            match node.edges.iter().find( |(label, _)| rest.starts_with( label.as_str() ) ) {
+                Some( (label, child) ) => {
+                    depth += label.len();
+                    node = child;
+                }
+                None => break,
+            }
+        }
+        best_match( candidates.into_iter().map( |i| &self.routes[i] ).filter( |route| route.method == method ), path )
+    }
+
+    fn routes(&self) -> &[RouteHandler] {
+        &self.routes
+    }
+
+    fn set_routes(&mut self, routes: Vec<RouteHandler>) {
+        self.routes = Vec::new();
+        self.root = RadixNode::default();
+        for route in routes {
+            self.add_route(route);
+        }
+    }
+
+    fn add_route(&mut self, route: RouteHandler) {
+        let index = self.routes.len();
+        self.root.insert( &route.path ).exact.push(index);
+        if route.prefix_match {
+            self.root.insert( route.path.trim_end_matches('/') ).prefix.push(index);
+        }
+        self.routes.push(route);
+    }
+}
+
+
+// Unit tests for the routers.
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::webserver::http_status::HttpStatus;
+    use crate::webserver::response::Response;
+
+    /// Create a route that answers with an empty 200 OK.
+    fn route( method: HttpMethod, path: &str ) -> RouteHandler {
+        RouteHandler::new( method, path, Arc::new( |_request| Response::new( HttpStatus::Ok, String::new(), Vec::new() ) ) )
+    }
+
+    /// Describe the route a router matches, like `GET /static`.
+    fn matched( router: &dyn Router, method: HttpMethod, path: &str ) -> Option<String> {
+        router.match_route( method, path ).map( RouteHandler::to_string )
+    }
+
+    #[test]
+    fn test_routers_match_the_same() {
+        let routes = vec![
+            route( HttpMethod::GET, "/" ),
+            route( HttpMethod::GET, "/area" ),
+            route( HttpMethod::POST, "/area" ),
+            route( HttpMethod::GET, "/areas" ),
+            route( HttpMethod::GET, "/api" ).with_prefix_match(),
+            route( HttpMethod::GET, "/api/shapes/" ).with_prefix_match(),
+            route( HttpMethod::GET, "/api/shapes/list" ),
+            route( HttpMethod::DELETE, "/api/shapes" ).with_prefix_match(),
+            route( HttpMethod::GET, "/static" ).with_prefix_match(),
+            route( HttpMethod::GET, "/stat" ),
+            route( HttpMethod::GET, "/café/menü" ),
+            route( HttpMethod::GET, "/cafe" ).with_prefix_match(),
+            route( HttpMethod::PUT, "/" ).with_prefix_match(),
+        ];
+        let linear = LinearRouter::new( routes.clone() );
+        let radix = RadixRouter::new( routes );
+
+        let requests = [
+            "/", "/area", "/areas", "/area/", "/are", "/api", "/api/", "/apis", "/api/shapes", "/api/shapes/",
+            "/api/shapes/1", "/api/shapes/list", "/api/shapes/list/2", "/static", "/static/css/site.css", "/staticx",
+            "/stat", "/sta", "/café/menü", "/café", "/café/", "/cafe/x", "/other", "",
+        ];
+        for method in [ HttpMethod::GET, HttpMethod::POST, HttpMethod::PUT, HttpMethod::DELETE ] {
+            for path in requests {
+                assert_eq!( matched( &radix, method, path ), matched( &linear, method, path ), "{method} {path}" );
+            }
+        }
+
+        // Spot check the expected matches.
+        assert_eq!( matched( &radix, HttpMethod::GET, "/api/shapes/1" ).as_deref(), Some( "GET /api/shapes/" ) );
+        assert_eq!( matched( &radix, HttpMethod::GET, "/api/shapes/list" ).as_deref(), Some( "GET /api/shapes/list" ) );
+        assert_eq!( matched( &radix, HttpMethod::GET, "/api/shapes" ).as_deref(), Some( "GET /api" ) );
+        assert_eq!( matched( &radix, HttpMethod::GET, "/staticx" ), None );
+        assert_eq!( matched( &radix, HttpMethod::PUT, "/anything/at/all" ).as_deref(), Some( "PUT /" ) );
+    }
+
+    #[test]
+    fn test_add_route() {
+        let mut radix = RadixRouter::default();
+        radix.add_route( route( HttpMethod::GET, "/shapes/circle" ) );
+        radix.add_route( route( HttpMethod::GET, "/shapes/square" ) );
+        radix.add_route( route( HttpMethod::GET, "/shapes" ) );
+        assert_eq!( radix.routes().len(), 3 );
+        for path in [ "/shapes/circle", "/shapes/square", "/shapes" ] {
+            assert_eq!( matched( &radix, HttpMethod::GET, path ), Some( format!("GET {path}") ) );
+        }
+        assert_eq!( matched( &radix, HttpMethod::GET, "/shapes/" ), None );
+
+        radix.set_routes( Vec::new() );
+        assert_eq!( matched( &radix, HttpMethod::GET, "/shapes" ), None );
+    }
+}
@@ -0,0 +1,358 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, error};
+
+use super::http_status::HttpStatus;
+use super::request::Request;
+use super::response::{escape_html, BodyFormat, Response};
+
+/// Represents a directory whose files are served under a URL prefix.
+pub struct StaticDirectory {
+    url_prefix: String,
+    root: PathBuf,
+    follow_symlinks: bool,
+    directory_listing: bool,
+}
+
+/// Implement the StaticDirectory struct.
+impl StaticDirectory {
+
+    /// Creates a new StaticDirectory.
+    ///
+    /// # Arguments
+    /// * `url_prefix` - The path under which the files are served, like `/static`.
+    /// * `fs_path` - The directory in the file system the files are served from.
+    /// * `follow_symlinks` - Whether symbolic links inside the directory are followed.
+    ///   Links are only followed to files and directories inside the directory.
+    /// * `directory_listing` - Whether a directory without an `index.html` is served as a list of its files.
+    pub fn new( url_prefix: &str, fs_path: &str, follow_symlinks: bool, directory_listing: bool ) -> StaticDirectory {
+        StaticDirectory {
+            url_prefix: url_prefix.trim_end_matches('/').to_string(),
+            root: PathBuf::from( fs_path ),
+            follow_symlinks,
+            directory_listing,
+        }
+    }
+
+    /// Serves the file the request points to.
+    ///
+    /// # Arguments
+    /// * `request` - A request for a path under the URL prefix.
+    ///
+    /// # Returns
+    /// The file with a `Content-Type` based on its extension, or 404 if there is no such file or
+    /// the path tries to leave the directory. A directory without an `index.html` gets 403, or a
+    /// list of its files if listing is enabled.
+    pub fn serve( &self, request: &Request ) -> Response {
+        let format = BodyFormat::negotiate( request.header_joined("Accept").as_deref() );
+        let not_found = || Response::from_message(
+            HttpStatus::NotFound,
+            "The page you requested could not be found.",
+            format
+        );
+
+        // Build the file path from the segments after the prefix.
+        let Some( relative ) = request.path.strip_prefix( &self.url_prefix ) else {
+            return not_found();
+        };
+        let mut path = self.root.clone();
+        for segment in relative.split('/') {
+            match segment {
+                "" | "." => continue,
+                ".." => {
+                    debug!("Refusing path with '..' segment: {}", request.path);
+                    return not_found();
+                }
+                segment if segment.contains('\\') => {
+                    debug!("Refusing path with '\\' in a segment: {}", request.path);
+                    return not_found();
+                }
+                segment => path.push( segment ),
+            }
+        }
+
+        // Directories are served from their index.html.
+        if path.is_dir() && path.join( "index.html" ).exists() {
+            path.push( "index.html" );
+        }
+
+        // Do not go through symbolic links unless allowed.
+        if !self.follow_symlinks && self.has_symlink( &path ) {
+            debug!("Refusing path through a symbolic link: {}", request.path);
+            return not_found();
+        }
+
+        // Resolve the symbolic links and make sure the path stays inside the root directory.
+        let resolved = match ( fs::canonicalize( &path ), fs::canonicalize( &self.root ) ) {
+            ( Ok( resolved ), Ok( root ) ) if resolved.starts_with( &root ) => resolved,
+            ( Ok( _ ), Ok( _ ) ) => {
+                debug!("Refusing path that leaves the directory: {}", request.path);
+                return not_found();
+            }
+            ( Err( e ), _ ) | ( _, Err( e ) ) if e.kind() == ErrorKind::NotFound => return not_found(),
+            ( Err( e ), _ ) | ( _, Err( e ) ) => {
+                error!("Could not resolve path '{}': {}", path.display(), e);
+                return Response::from_error_as( &e, HttpStatus::InternalServerError, format );
+            }
+        };
+
+        // Directories without an index.html are listed only if allowed.
+        if resolved.is_dir() {
+            if !self.directory_listing {
+                return Response::from_message(
+                    HttpStatus::Forbidden,
+                    "The directory has no index page.",
+                    format
+                );
+            }
+            return match directory_listing( &resolved, &request.path ) {
+                Ok( page ) => Response::binary( HttpStatus::Ok, page.into_bytes(), "text/html; charset=utf-8" ),
+                Err( e ) => {
+                    error!("Could not list directory '{}': {}", resolved.display(), e);
+                    Response::from_error_as( &e, HttpStatus::InternalServerError, format )
+                }
+            };
+        }
+
+        // Read the file.
+        let bytes = match fs::read( &resolved ) {
+            Ok( bytes ) => bytes,
+            Err( e ) if e.kind() == ErrorKind::NotFound => {
+                return not_found();
+            }
+            Err( e ) => {
+                error!("Could not read file '{}': {}", resolved.display(), e);
+                return Response::from_error_as( &e, HttpStatus::InternalServerError, format );
+            }
+        };
+
+        Response::binary( HttpStatus::Ok, bytes, content_type( &path ) )
+    }
+
+    /// Checks if any part of the path below the root directory is a symbolic link.
+    fn has_symlink( &self, path: &Path ) -> bool {
+        let Ok( relative ) = path.strip_prefix( &self.root ) else {
+            return true;
+        };
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push( component );
+            if fs::symlink_metadata( &current ).is_ok_and( |metadata| metadata.file_type().is_symlink() ) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Creates an HTML page listing the files and subdirectories of a directory.
+///
+/// # Arguments
+/// * `directory` - The directory to list.
+/// * `url_path` - The path of the directory in the request, used for the links.
+fn directory_listing( directory: &Path, url_path: &str ) -> std::io::Result<String> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir( directory )? {
+        let entry = entry?;
+        let mut name = entry.file_name().to_string_lossy().into_owned();
+        if entry.path().is_dir() {
+            name.push('/');
+        }
+        names.push( name );
+    }
+    names.sort();
+
+    let base = url_path.trim_end_matches('/');
+    let items: String = names.iter()
+        .map( |name| format!(
+            "        <li><a href=\"{}/{}\">{}</a></li>\n",
+            escape_html( &encode_path_segment( base ) ),
+            escape_html( &encode_path_segment( name ) ),
+            escape_html( name )
+        ))
+        .collect();
+    let title = escape_html( &format!("Index of {}/", base) );
+    Ok( format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>{title}</title>
+</head>
+<body>
+    <h1>{title}</h1>
+    <ul>
+{items}    </ul>
+</body>
+</html>
+"#) )
+}
+
+/// Percent-encodes the characters of a path that are not allowed in a URL path as they are.
+fn encode_path_segment( text: &str ) -> String {
+    let mut encoded = String::with_capacity( text.len() );
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => encoded.push( byte as char ),
+            byte => encoded.push_str( &format!("%{:02X}", byte) ),
+        }
+    }
+    encoded
+}
+
+/// Gets the `Content-Type` of a file from its extension.
+fn content_type( path: &Path ) -> &'static str {
+    let extension = path.extension()
+        .and_then( |extension| extension.to_str() )
+        .map( |extension| extension.to_ascii_lowercase() )
+        .unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "xml" => "application/xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+
+// Unit tests for StaticDirectory.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty temporary directory for a test.
+    fn temp_dir( name: &str ) -> PathBuf {
+        let dir = std::env::temp_dir().join( format!("area-calculator-static-{}-{}", std::process::id(), name) );
+        let _ = fs::remove_dir_all( &dir );
+        fs::create_dir_all( &dir ).unwrap();
+        dir
+    }
+
+    /// Create a GET request for the path.
+    fn get( path: &str ) -> Request {
+        Request::new( &vec![ format!("GET {path} HTTP/1.1") ] )
+    }
+
+    /// Get the value of a response header.
+    fn header<'a>( response: &'a Response, name: &str ) -> Option<&'a str> {
+        response.headers.iter().find( |(key, _)| key == name ).map( |(_, value)| value.as_str() )
+    }
+
+    #[test]
+    fn test_serve_file() {
+        let root = temp_dir( "serve" );
+        fs::create_dir_all( root.join( "css" ) ).unwrap();
+        fs::write( root.join( "css/site.css" ), "body {}" ).unwrap();
+        let directory = StaticDirectory::new( "/static/", root.to_str().unwrap(), false, false );
+
+        let response = directory.serve( &get( "/static/css/site.css" ) );
+        assert_eq!( response.status, HttpStatus::Ok );
+        assert_eq!( response.binary_body.as_deref(), Some( "body {}".as_bytes() ) );
+        assert_eq!( header( &response, "Content-Type" ), Some( "text/css; charset=utf-8" ) );
+        assert_eq!( header( &response, "Content-Length" ), Some( "7" ) );
+    }
+
+    #[test]
+    fn test_serve_index() {
+        let root = temp_dir( "index" );
+        fs::create_dir_all( root.join( "docs" ) ).unwrap();
+        fs::write( root.join( "index.html" ), "<h1>root</h1>" ).unwrap();
+        fs::write( root.join( "docs/index.html" ), "<h1>docs</h1>" ).unwrap();
+        let directory = StaticDirectory::new( "/static", root.to_str().unwrap(), false, false );
+
+        assert_eq!( directory.serve( &get( "/static" ) ).binary_body.unwrap(), b"<h1>root</h1>" );
+        assert_eq!( directory.serve( &get( "/static/docs/" ) ).binary_body.unwrap(), b"<h1>docs</h1>" );
+        assert_eq!( header( &directory.serve( &get( "/static/docs" ) ), "Content-Type" ), Some( "text/html; charset=utf-8" ) );
+    }
+
+    #[test]
+    fn test_missing_and_traversal() {
+        let root = temp_dir( "traversal" );
+        fs::create_dir_all( root.join( "public" ) ).unwrap();
+        fs::write( root.join( "secret.txt" ), "secret" ).unwrap();
+        let directory = StaticDirectory::new( "/static", root.join( "public" ).to_str().unwrap(), false, false );
+
+        assert_eq!( directory.serve( &get( "/static/missing.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( directory.serve( &get( "/static/../secret.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( directory.serve( &get( "/static/..\\secret.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( directory.serve( &get( "/static/sub/../../secret.txt" ) ).status, HttpStatus::NotFound );
+    }
+
+    #[test]
+    fn test_serve_binary() {
+        let root = temp_dir( "binary" );
+        let image: [u8; 4] = [ 0x89, 0x50, 0x4e, 0x47 ];
+        fs::write( root.join( "logo.png" ), image ).unwrap();
+        let directory = StaticDirectory::new( "/static", root.to_str().unwrap(), false, false );
+
+        let response = directory.serve( &get( "/static/logo.png" ) );
+        assert_eq!( header( &response, "Content-Type" ), Some( "image/png" ) );
+        assert_eq!( response.binary_body.unwrap(), image );
+    }
+
+    #[test]
+    fn test_encoded_traversal() {
+        let root = temp_dir( "encoded" );
+        fs::create_dir_all( root.join( "public" ) ).unwrap();
+        fs::write( root.join( "secret.txt" ), "secret" ).unwrap();
+        let directory = StaticDirectory::new( "/static", root.join( "public" ).to_str().unwrap(), false, false );
+
+        // The path is decoded before the segments are checked.
+        assert_eq!( directory.serve( &get( "/static/%2e%2e%2fsecret.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( directory.serve( &get( "/static/%2E%2E/secret.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( directory.serve( &get( "/static/%2e%2e%5csecret.txt" ) ).status, HttpStatus::NotFound );
+    }
+
+    #[test]
+    fn test_directory_without_index() {
+        let root = temp_dir( "listing" );
+        fs::create_dir_all( root.join( "docs/images" ) ).unwrap();
+        fs::write( root.join( "docs/a <b>.txt" ), "a" ).unwrap();
+
+        let strict = StaticDirectory::new( "/static", root.to_str().unwrap(), false, false );
+        assert_eq!( strict.serve( &get( "/static/docs" ) ).status, HttpStatus::Forbidden );
+
+        let listing = StaticDirectory::new( "/static", root.to_str().unwrap(), false, true );
+        let response = listing.serve( &get( "/static/docs/" ) );
+        assert_eq!( response.status, HttpStatus::Ok );
+        assert_eq!( header( &response, "Content-Type" ), Some( "text/html; charset=utf-8" ) );
+        let page = String::from_utf8( response.binary_body.unwrap() ).unwrap();
+        assert!( page.contains( "<a href=\"/static/docs/a%20%3Cb%3E.txt\">a &lt;b&gt;.txt</a>" ), "{page}" );
+        assert!( page.contains( "<a href=\"/static/docs/images/\">images/</a>" ), "{page}" );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        let root = temp_dir( "symlinks" );
+        fs::create_dir_all( root.join( "public/docs" ) ).unwrap();
+        fs::write( root.join( "outside.txt" ), "outside" ).unwrap();
+        fs::write( root.join( "public/docs/inside.txt" ), "inside" ).unwrap();
+        std::os::unix::fs::symlink( root.join( "outside.txt" ), root.join( "public/link.txt" ) ).unwrap();
+        std::os::unix::fs::symlink( &root, root.join( "public/up" ) ).unwrap();
+        std::os::unix::fs::symlink( root.join( "public/docs/inside.txt" ), root.join( "public/inside.txt" ) ).unwrap();
+        let public = root.join( "public" );
+
+        let strict = StaticDirectory::new( "/static", public.to_str().unwrap(), false, false );
+        assert_eq!( strict.serve( &get( "/static/link.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( strict.serve( &get( "/static/inside.txt" ) ).status, HttpStatus::NotFound );
+
+        // Links are followed only when they stay inside the directory.
+        let following = StaticDirectory::new( "/static", public.to_str().unwrap(), true, false );
+        assert_eq!( following.serve( &get( "/static/inside.txt" ) ).binary_body.unwrap(), b"inside" );
+        assert_eq!( following.serve( &get( "/static/link.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( following.serve( &get( "/static/up/outside.txt" ) ).status, HttpStatus::NotFound );
+        assert_eq!( following.serve( &get( "/static/up" ) ).status, HttpStatus::NotFound );
+    }
+}
@@ -1,29 +1,155 @@
 use std::{
-    env, fs, hash::Hash, io::{prelude::*, BufReader}, net::{SocketAddr, TcpListener, TcpStream}, sync::{atomic::{AtomicBool, Ordering}, Arc}
+    env, fs, hash::Hash, path::PathBuf, time::{Duration, Instant}, io::{prelude::*, BufReader}, net::{SocketAddr, TcpListener, TcpStream}, sync::{atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}, mpsc::{self, TrySendError}, Arc, Mutex, PoisonError, RwLock, Weak}
 };
 
-use tracing::{info, debug, error};
+use tracing::{debug, error, field, info, info_span, trace, Span};
 
+use super::buffer_pool::{BufferPool, PooledReader, DEFAULT_POOL_BUFFERS};
+use super::cache::{ResponseCache, DEFAULT_CACHE_CAPACITY};
+use super::connection::{Connection, Listener};
 use super::routehandler::RouteHandler;
+use super::router::{LinearRouter, Router};
+use super::static_files::StaticDirectory;
 use super::http_method::HttpMethod;
 use super::http_status::HttpStatus;
-use super::request::Request;
-use super::response::Response;
+use super::json::JsonValue;
+use super::recording::{RequestRecorder, DEFAULT_RECORDING_CAPACITY};
+use super::request::{BodyError, Request, RequestError, DEFAULT_MAX_TARGET_BYTES};
+use super::response::{BodyFormat, Response};
 
+/// The default maximum size of a request body in bytes.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// The number of bytes the request line may have in addition to the request target,
+/// for the method, the protocol version and the spaces between them.
+const REQUEST_LINE_OVERHEAD: usize = 32;
+
+/// The number of characters of an overly long request target shown in the log.
+const LOGGED_TARGET_CHARS: usize = 64;
+
+/// The number of seconds a client is asked to wait after a handler timed out or the server was overloaded.
+const RETRY_AFTER_SECONDS: u64 = 1;
+
+/// The default number of worker threads that handle connections.
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// The number of handlers that can still be running after their timeout. Each of them holds a thread,
+/// so once there are this many, routes with a timeout get 503 until some of the handlers finish.
+pub const MAX_LATE_HANDLERS: usize = 16;
+
+/// The default number of accepted connections that can wait for a free worker.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// The icon served at `/favicon.ico` unless a route for it is added.
+const FAVICON: &[u8] = include_bytes!("favicon.ico");
+
+/// A callback that observes each request with its response and how long handling it took.
+pub type RequestObserver = Arc<dyn Fn(&Request, &Response, Duration) + Send + Sync>;
+
+/// The id of the next request, shown in the log events of the request.
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The headers added to every response when security headers are enabled.
+const SECURITY_HEADERS: [(&str, &str); 3] = [
+    ("X-Content-Type-Options", "nosniff"),
+    ("X-Frame-Options", "DENY"),
+    ("Content-Security-Policy", "default-src 'self'; frame-ancestors 'none'"),
+];
 
 /// Represents a simple web server.
 /// It can handle routes and serve HTTP requests.
+///
+/// A listener thread accepts connections and queues them for a fixed number of worker threads.
+/// When the queue is full, new connections get 503 right away instead of waiting. The handler
+/// functions of the routes are shared with the workers. See `HandlerFn` for what this means for handlers.
 pub struct WebServer {
-    routes: Vec<RouteHandler>,
+    routes: Arc<RwLock<Box<dyn Router>>>,
     is_running: bool,
     should_stop: Arc<AtomicBool>,
     local_addr: Option<SocketAddr>,
     listener_handle: Option<std::thread::JoinHandle<()>>,
-    listener: Option<TcpListener>,
+    listener: Option<Listener>,
+    unix_path: Option<PathBuf>,
+    max_body_bytes: usize,
+    max_target_bytes: usize,
+    follow_symlinks: bool,
+    directory_listing: bool,
+    cache: ResponseCache,
+    security_headers: bool,
+    timeouts: Arc<AtomicU64>,
+    handler_timeout: Option<Duration>,
+    rejected: Arc<AtomicU64>,
+    workers: usize,
+    queue_capacity: usize,
+    recorder: RequestRecorder,
+    observers: Vec<RequestObserver>,
+    admin_access: AdminAccess,
     pub address: String,
     pub port: String
 }
 
+/// The state the worker threads need to handle connections.
+struct ConnectionContext {
+    routes: Arc<RwLock<Box<dyn Router>>>,
+    builtin_routes: LinearRouter,
+    cache: ResponseCache,
+    timeouts: Arc<AtomicU64>,
+    handler_timeout: Option<Duration>,
+    late_handlers: Arc<AtomicUsize>,
+    max_late_handlers: usize,
+    max_body_bytes: usize,
+    max_target_bytes: usize,
+    security_headers: bool,
+    buffers: BufferPool,
+    lines: BufferPool<Vec<String>>,
+    recorder: RequestRecorder,
+    observers: Vec<RequestObserver>,
+    admin_access: AdminAccess,
+}
+
+/// Who can use the admin routes: clients on the same machine if `local` is set, and clients
+/// with the token if there is one.
+#[derive(Clone)]
+struct AdminAccess {
+    local: bool,
+    token: Option<String>,
+}
+
+/// Implement the AdminAccess struct.
+impl AdminAccess {
+
+    /// Check if a request can use an admin route.
+    ///
+    /// # Arguments
+    /// * `request` - The request, with the token in an `Authorization: Bearer` header.
+    /// * `local_peer` - Whether the client is on the same machine.
+    ///
+    /// # Returns
+    /// `None` if the request is allowed, otherwise the response to send instead: 401 if a token
+    /// would let the client in, 403 if nothing would.
+    fn deny(&self, request: &Request, local_peer: bool) -> Option<Response> {
+        let has_token = self.token.as_deref().is_some_and( |token| {
+            request.header("Authorization")
+                .and_then( |value| value.strip_prefix("Bearer ") )
+                .is_some_and( |given| tokens_match( given.trim(), token ) )
+        });
+        if has_token || (self.local && local_peer) {
+            return None;
+        }
+        match self.token {
+            Some(_) => Some(Response::unauthorized("Bearer realm=\"admin\"")),
+            None => Some(Response::forbidden()),
+        }
+    }
+}
+
+/// Compare two tokens in a time that does not depend on where they differ, so the time of
+/// a failed attempt does not tell how much of the token was right.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given.bytes().zip( token.bytes() ).fold( 0, |difference, (a, b)| difference | (a ^ b) ) == 0
+}
+
 /// Implement the WebServer struct.
 /// This struct represents a simple web server that can handle HTTP requests.
 /// It can be started, stopped, and can handle routes.
@@ -39,16 +165,55 @@ impl WebServer {
 
         // Todo: Check if the address and port are valid.
         WebServer{
-            routes: Vec::new(),
+            routes: Arc::new(RwLock::new(Box::new(LinearRouter::default()))),
             address: url.to_string(), port: port.to_string(),
             is_running: false,
             should_stop: Arc::new(AtomicBool::new(false)),
             local_addr: None,
             listener_handle: None,
-            listener: None
+            listener: None,
+            unix_path: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_target_bytes: DEFAULT_MAX_TARGET_BYTES,
+            follow_symlinks: false,
+            directory_listing: false,
+            cache: ResponseCache::new( DEFAULT_CACHE_CAPACITY ),
+            security_headers: false,
+            timeouts: Arc::new(AtomicU64::new(0)),
+            handler_timeout: None,
+            rejected: Arc::new(AtomicU64::new(0)),
+            workers: DEFAULT_WORKERS,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            recorder: RequestRecorder::new( DEFAULT_RECORDING_CAPACITY ),
+            observers: Vec::new(),
+            admin_access: AdminAccess { local: true, token: None }
         }
     }
 
+    /// Create a new WebServer that listens on a port the operating system picks, like port "0".
+    /// The port is known after the server is started, see `local_addr`. Useful for tests, where
+    /// fixed ports can already be in use.
+    ///
+    /// # Arguments
+    /// * `url` - The URL or IP address to bind the server to (e.g., "127.0.0.1").
+    pub fn bind_random_port( url: &str ) -> WebServer {
+        WebServer::new( url, "0" )
+    }
+
+    /// Create a new WebServer that listens on a Unix domain socket instead of a TCP address,
+    /// for clients on the same machine. The socket file is created when the server is started
+    /// and removed when it is stopped. A socket file left behind by a server that did not stop is
+    /// replaced, but starting fails if another server is listening on it or the file is not a socket.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the socket file.
+    #[cfg(unix)]
+    pub fn bind_unix( path: &str ) -> WebServer {
+        let mut server = WebServer::new( "", "" );
+        server.unix_path = Some( PathBuf::from( path ) );
+        server
+    }
+
     /// Add a route to the web server.
     /// arguments
     /// * `handler` - The RouteHandler that will handle requests for this route.
@@ -67,75 +232,441 @@ impl WebServer {
             return false;
         }
 
-        // Check if the handler is already registered.
-        if self.routes.iter().any( |r| r.path == handler.path && r.method == handler.method ) {
-            error!("Cannot add route: Handler for path '{}' with method '{}' is already registered.", handler.path, handler.method);
+        // Check that the handler is valid and not already registered.
+        let mut routes = self.routes.write().unwrap_or_else(PoisonError::into_inner);
+        if !is_valid_route( routes.routes(), &handler ) {
             return false;
         }
 
-        // Validate the handler.
-        if handler.path.is_empty() {
-            error!("Cannot add route: Handler for path '{}' with method '{}' is empty.", handler.path, handler.method);
-            return false;
-        }
-        if handler.method == HttpMethod::Unknown {
-            error!("Cannot add route: Handler for path '{}' has an unknown method.", handler.path);
-            return false;
-        }
+        // Add the handler to the routes.
+        routes.add_route( handler );
+        return true;
+    }
+
+    /// Serve the files of a directory and its subdirectories.
+    /// A request for `url_prefix/some/file.css` is served from `fs_path/some/file.css`, and a request
+    /// for a directory is served from the `index.html` in it. Paths with `..` segments and paths that
+    /// lead outside the directory through symbolic links get 404. A directory without an `index.html`
+    /// gets 403 unless directory listing is enabled.
+    ///
+    /// # Arguments
+    /// * `url_prefix` - The path under which the files are served, like `/static`.
+    /// * `fs_path` - The directory in the file system the files are served from.
+    /// # Returns
+    /// A boolean indicating whether the route was added successfully.
+    pub fn add_static_directory(&mut self, url_prefix: &str, fs_path: &str) -> bool {
+        let directory = StaticDirectory::new( url_prefix, fs_path, self.follow_symlinks, self.directory_listing );
+        let handler = RouteHandler::new(
+            HttpMethod::GET,
+            url_prefix,
+            Arc::new( move |request| directory.serve( &request ) )
+        )
+        .with_prefix_match()
+        .with_name("static-directory")
+        .with_description(&format!("Serves the files in '{}'.", fs_path));
+        self.add_route(handler)
+    }
+
+    /// Set whether symbolic links are followed when serving static directories.
+    /// Symbolic links are not followed by default, and requests through them get 404.
+    /// This applies to static directories added after the call.
+    ///
+    /// # Arguments
+    /// * `follow_symlinks` - Whether symbolic links are followed.
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) {
+        self.follow_symlinks = follow_symlinks;
+    }
+
+    /// Set whether directories without an `index.html` are served as a list of their files
+    /// when serving static directories.
+    /// Directory listing is disabled by default, and requests for such directories get 403.
+    /// This applies to static directories added after the call.
+    ///
+    /// # Arguments
+    /// * `directory_listing` - Whether directories are listed.
+    pub fn set_directory_listing(&mut self, directory_listing: bool) {
+        self.directory_listing = directory_listing;
+    }
+
+    /// Remove the cached responses of all paths that start with the prefix.
+    /// Handlers that change data should invalidate the responses that show it,
+    /// using a handle from `response_cache`.
+    ///
+    /// # Arguments
+    /// * `prefix` - The start of the paths to remove, like `/api/shapes`.
+    pub fn invalidate_cache(&self, prefix: &str) {
+        self.cache.invalidate(prefix);
+    }
 
-        // Check if the path pattern is valid.
-        // For now, we only support simple paths without regex or wildcards.
-        let unsupported_wildcards = [
-            "{", "}", "*", "?", "+", "|", "^", "$", ".", "\\", "#", "&", "="
+    /// Get a handle to the cache of the responses of cached routes.
+    /// Handlers can keep the handle and call `ResponseCache::invalidate` after a change.
+    pub fn response_cache(&self) -> ResponseCache {
+        self.cache.clone()
+    }
+
+    /// Add the `GET /admin/routes` route, which lists all routes of the server as JSON, sorted by path.
+    /// The listing includes the route itself and follows any later changes to the routes.
+    /// The route is only for administrators, see `RouteHandler::with_admin_only`.
+    /// # Returns
+    /// A boolean indicating whether the route was added successfully.
+    pub fn add_route_listing(&mut self) -> bool {
+
+        // The route is stored in the same list it reads, so it only keeps a weak
+        // reference to the list to avoid a reference cycle.
+        let routes: Weak<RwLock<Box<dyn Router>>> = Arc::downgrade(&self.routes);
+        let handler = RouteHandler::new(
+            HttpMethod::GET,
+            "/admin/routes",
+            Arc::new( move |_request| {
+                let mut listing: Vec<JsonValue> = Vec::new();
+                if let Some(routes) = routes.upgrade() {
+                    let mut routes: Vec<RouteHandler> = routes.read().unwrap_or_else(PoisonError::into_inner).routes().to_vec();
+                    routes.sort_by( |a, b| a.path.cmp(&b.path).then_with( || a.method.to_string().cmp(&b.method.to_string()) ) );
+                    listing = routes.iter().map( |route| route.to_json() ).collect();
+                }
+                Response::json( HttpStatus::Ok, &JsonValue::Array(listing) )
+            })
+        )
+        .with_name("route-listing")
+        .with_description("Lists the registered routes.")
+        .with_admin_only();
+        self.add_route(handler)
+    }
+
+    /// Add a callback that is called after each request is handled, with the request, the response
+    /// and how long handling the request took. The callback is called just before the response is
+    /// sent, and it can not change the response. Requests that could not be parsed are not observed.
+    /// This applies when the server is started, so callbacks should be added before `start`.
+    ///
+    /// # Arguments
+    /// * `f` - The callback.
+    pub fn on_request(&mut self, f: RequestObserver) {
+        self.observers.push(f);
+    }
+
+    /// Turn recording of requests and responses on or off. Recording is off by default.
+    /// The recorded requests are listed by the routes added with `add_recording_routes`.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether requests are recorded.
+    /// * `file` - A file the requests are appended to as JSON lines, or `None` to only keep the recent ones in memory.
+    pub fn set_recording(&mut self, enabled: bool, file: Option<&str>) {
+        self.recorder.set_file(file.map(PathBuf::from));
+        self.recorder.set_enabled(enabled);
+    }
+
+    /// Get a handle to the recorder of requests and responses.
+    pub fn request_recorder(&self) -> RequestRecorder {
+        self.recorder.clone()
+    }
+
+    /// Add the routes for recording requests: `GET /admin/recent-requests` lists the recently
+    /// recorded requests as JSON, and `GET` and `PUT /admin/recording` report and change whether
+    /// requests are recorded, like `{"enabled": true}`. The recorded requests show what other
+    /// clients sent, so the routes are only for administrators, see `RouteHandler::with_admin_only`.
+    ///
+    /// # Returns
+    /// A boolean indicating whether the routes were added successfully.
+    pub fn add_recording_routes(&mut self) -> bool {
+        let recent = self.recorder.clone();
+        let state = self.recorder.clone();
+        let toggle = self.recorder.clone();
+        let enabled_json = |recorder: &RequestRecorder| JsonValue::Object( vec![
+            ("enabled".to_string(), JsonValue::from( recorder.is_enabled() )),
+        ] );
+        let routes = vec![
+            RouteHandler::new(
+                HttpMethod::GET,
+                "/admin/recent-requests",
+                Arc::new( move |_request| Response::json( HttpStatus::Ok, &JsonValue::Array( recent.recent() ) ) )
+            ).with_name("recent-requests").with_description("Lists the recently recorded requests and responses.").with_admin_only(),
+            RouteHandler::new(
+                HttpMethod::GET,
+                "/admin/recording",
+                Arc::new( move |_request| Response::json( HttpStatus::Ok, &enabled_json( &state ) ) )
+            ).with_name("get-recording").with_description("Reports whether requests are recorded.").with_admin_only(),
+            RouteHandler::new(
+                HttpMethod::PUT,
+                "/admin/recording",
+                Arc::new( move |request: Request| {
+                    let enabled = JsonValue::parse( &request.body ).ok().and_then( |json| match json.get( "enabled" ) {
+                        Some( JsonValue::Bool( enabled ) ) => Some( *enabled ),
+                        _ => None,
+                    });
+                    let Some( enabled ) = enabled else {
+                        return Response::json(
+                            HttpStatus::BadRequest,
+                            &JsonValue::Object( vec![ ("error".to_string(), JsonValue::from( "Expected {\"enabled\": true} or {\"enabled\": false}." )) ] )
+                        );
+                    };
+                    toggle.set_enabled( enabled );
+                    info!("Recording of requests turned {}", if enabled { "on" } else { "off" });
+                    Response::json( HttpStatus::Ok, &enabled_json( &toggle ) )
+                })
+            ).with_name("set-recording").with_description("Turns the recording of requests on or off.").with_admin_only(),
         ];
-        // Check if path pattern has any of the unsupported widlcards.
-        for wildcard in unsupported_wildcards.iter() {
-            if handler.path_pattern.contains(wildcard) {
-                error!("Cannot add route: Handler for path '{}' with method '{}' has a path pattern with unsupported wildcard '{}'.", handler.path, handler.method, wildcard);
+        routes.into_iter().all( |route| self.add_route(route) )
+    }
+
+    /// Add a `GET /debug/routes` route listing the registered routes as plain text, one
+    /// `METHOD /path` per line. Only available in debug builds, as the listing would tell
+    /// too much about a production server, and only to administrators, see `RouteHandler::with_admin_only`.
+    ///
+    /// # Returns
+    /// * `true` if the route was added.
+    #[cfg(debug_assertions)]
+    pub fn add_debug_routes(&mut self) -> bool {
+        let routes: Weak<RwLock<Box<dyn Router>>> = Arc::downgrade(&self.routes);
+        let handler = RouteHandler::new(
+            HttpMethod::GET,
+            "/debug/routes",
+            Arc::new( move |_request| {
+                let mut lines: Vec<String> = Vec::new();
+                if let Some(routes) = routes.upgrade() {
+                    lines = routes.read().unwrap_or_else(PoisonError::into_inner).routes().iter().map( |route| route.to_string() ).collect();
+                    lines.sort();
+                }
+                let body = lines.iter().map( |line| format!("{line}\n") ).collect::<String>();
+                let length = body.len();
+                Response::new(
+                    HttpStatus::Ok,
+                    body,
+                    vec![
+                        ("Content-Type".to_string(), "text/plain; charset=utf-8".to_string()),
+                        ("Content-Length".to_string(), length.to_string()),
+                    ]
+                )
+            })
+        )
+        .with_name("debug-routes")
+        .with_description("Lists the registered routes as text, only in debug builds.")
+        .with_admin_only();
+        self.add_route(handler)
+    }
+
+    /// Set the router that finds the route of each request, like a `RadixRouter` for servers with
+    /// many routes. The routes already added are moved to the new router. The default router is a
+    /// `LinearRouter`.
+    ///
+    /// # Arguments
+    /// * `router` - The new router.
+    pub fn set_router(&mut self, mut router: Box<dyn Router>) {
+        let mut routes = self.routes.write().unwrap_or_else(PoisonError::into_inner);
+        router.set_routes( routes.routes().to_vec() );
+        *routes = router;
+    }
+
+    /// Replace all routes of the web server with a new set of routes.
+    /// This can be done while the server is running: requests that are already being
+    /// handled finish with the old routes, and new requests are handled with the new routes.
+    ///
+    /// # Arguments
+    /// * `new_routes` - The RouteHandlers that replace the current routes.
+    /// # Returns
+    /// A boolean indicating whether the routes were replaced. If any of the new routes is
+    /// invalid, the current routes are kept as they are.
+    pub fn reload_routes(
+        &mut self,
+        new_routes: Vec<RouteHandler>
+    ) -> bool {
+
+        debug!("Reloading {} route handlers", new_routes.len() );
+
+        // Validate all new routes before touching the current ones.
+        let mut validated: Vec<RouteHandler> = Vec::new();
+        for handler in new_routes {
+            if !is_valid_route( &validated, &handler ) {
+                error!("Cannot reload routes: Handler '{}' is not valid.", handler);
                 return false;
             }
+            validated.push( handler );
         }
 
-        // Add the handler to the list of routes.
-        self.routes.push( handler);
+        // Swap the routes.
+        self.routes.write().unwrap_or_else(PoisonError::into_inner).set_routes( validated );
+        info!("Routes reloaded.");
         return true;
     }
 
+    /// Add default security headers to every response: `X-Content-Type-Options: nosniff`,
+    /// `X-Frame-Options: DENY` and a basic `Content-Security-Policy`. Headers a handler has
+    /// already set are kept. The headers are off by default.
+    pub fn with_security_headers(mut self) -> WebServer {
+        self.security_headers = true;
+        self
+    }
+
+    /// Get the number of requests whose handler did not finish within the timeout of its route,
+    /// or the handler timeout of the server.
+    pub fn timeout_count(&self) -> u64 {
+        self.timeouts.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of connections that got 503 because the queue of connections was full.
+    pub fn rejected_count(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Set the number of worker threads that handle connections. At least one worker is started.
+    /// The new number is used the next time the server is started.
+    ///
+    /// # Arguments
+    /// * `workers` - The number of worker threads.
+    pub fn set_workers(&mut self, workers: usize) {
+        self.workers = workers;
+    }
+
+    /// Set how long the handler of any route can take, for routes that do not have their own
+    /// timeout. A handler that does not finish in time gets 504 Gateway Timeout, and the worker
+    /// moves on to the next connection. Handlers can not be killed in safe Rust, so a handler that
+    /// never returns keeps running on its own thread: the timeout bounds the response, not the CPU.
+    /// Every request to such a route costs a thread, and while `MAX_LATE_HANDLERS` handlers are still
+    /// running after their timeout, the routes with a timeout get 503 without calling the handler.
+    /// The new timeout is used the next time the server is started.
+    ///
+    /// # Arguments
+    /// * `timeout` - How long to wait for a handler.
+    pub fn set_handler_timeout(&mut self, timeout: Duration) {
+        self.handler_timeout = Some(timeout);
+    }
+
+    /// Set a token that lets clients on any machine use the admin routes, see
+    /// `RouteHandler::with_admin_only`. The client sends it in an `Authorization: Bearer <token>`
+    /// header. Without a token, only clients on the same machine can use the admin routes.
+    /// The new token is used the next time the server is started.
+    ///
+    /// # Arguments
+    /// * `token` - The admin token.
+    pub fn set_admin_token(&mut self, token: &str) {
+        self.admin_access.token = Some(token.to_string());
+    }
+
+    /// Set whether clients on the same machine can use the admin routes without the admin token.
+    /// They can by default. Behind a reverse proxy on the same machine every client looks local,
+    /// so local access should be turned off there. The new setting is used the next time the
+    /// server is started.
+    ///
+    /// # Arguments
+    /// * `allowed` - Whether local clients can use the admin routes without the token.
+    pub fn set_admin_local_access(&mut self, allowed: bool) {
+        self.admin_access.local = allowed;
+    }
+
+    /// Set how many accepted connections can wait for a free worker. Connections that arrive when
+    /// the queue is full are answered with 503 and closed. With a capacity of 0 a connection is only
+    /// accepted if a worker is free. The new capacity is used the next time the server is started.
+    ///
+    /// # Arguments
+    /// * `queue_capacity` - The maximum number of waiting connections.
+    pub fn set_queue_capacity(&mut self, queue_capacity: usize) {
+        self.queue_capacity = queue_capacity;
+    }
+
+    /// Set the maximum size of request bodies. Requests with a larger body are answered with 413.
+    /// The new limit is used the next time the server is started.
+    ///
+    /// # Arguments
+    /// * `max_body_bytes` - The maximum size of a request body in bytes.
+    pub fn set_max_body_bytes(&mut self, max_body_bytes: usize) {
+        self.max_body_bytes = max_body_bytes;
+    }
+
+    /// Set the maximum length of a request target, the path and the query, in bytes.
+    /// Requests with a longer target get 414. The default is `DEFAULT_MAX_TARGET_BYTES`.
+    ///
+    /// # Arguments
+    /// * `max_target_bytes` - The maximum length of a request target in bytes.
+    pub fn set_max_target_bytes(&mut self, max_target_bytes: usize) {
+        self.max_target_bytes = max_target_bytes;
+    }
+
+    /// Get the address the server is listening on.
+    /// # Returns
+    /// The local address, or `None` if the server has not been started.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
     
     /// Start the web server.
-    pub fn start(&mut self) {
+    ///
+    /// # Returns
+    /// The error if the server could not listen on its address or socket file.
+    pub fn start(&mut self) -> std::io::Result<()> {
 
         // Check if the server is already running.
         if self.is_running {
             info!( "Start: Server is already running." );
-            return;
+            return Ok(());
         }
         
-        // Start listening for incoming connections. TODO: error handling
-        self.listener = Some( TcpListener::bind( format!("{}:{}", self.address, self.port) ).unwrap() );
-        let socket_addr = self.listener.as_ref().unwrap().local_addr();
-        match socket_addr {
-            Ok(addr) => {
-                self.local_addr = Some(addr);
-            }
-            Err(e) => {
-                error!("Error: {}", e);
-            }
-        }
+        // Start listening for incoming connections, on the socket file if there is one.
+        let listener = match &self.unix_path {
+            #[cfg(unix)]
+            Some(path) => Listener::Unix( bind_unix_socket( path )? ),
+            _ => Listener::Tcp( TcpListener::bind( format!("{}:{}", self.address, self.port) )? ),
+        };
+        self.local_addr = listener.local_addr();
+        debug!("Created listener on {}", listener);
+        self.listener = Some( listener );
         self.is_running = true;
         self.should_stop.store(false, Ordering::Relaxed);
-        debug!("Created listener on {}:{}", self.address, self.port);
 
         // Start handling incoming connections in a separate thread.
         // We need to take the listener out of the Option so we can move it into the thread.        
         let listener = self.listener.take().unwrap();
-        let routes = self.routes.clone();
         let should_stop = self.should_stop.clone();
+        let context = ConnectionContext {
+            routes: self.routes.clone(),
+            builtin_routes: LinearRouter::new( builtin_routes() ),
+            cache: self.cache.clone(),
+            timeouts: self.timeouts.clone(),
+            handler_timeout: self.handler_timeout,
+            late_handlers: Arc::new(AtomicUsize::new(0)),
+            max_late_handlers: MAX_LATE_HANDLERS,
+            max_body_bytes: self.max_body_bytes,
+            max_target_bytes: self.max_target_bytes,
+            security_headers: self.security_headers,
+            buffers: BufferPool::new( DEFAULT_POOL_BUFFERS ),
+            lines: BufferPool::new( DEFAULT_POOL_BUFFERS ),
+            recorder: self.recorder.clone(),
+            observers: self.observers.clone(),
+            admin_access: self.admin_access.clone(),
+        };
+        let context = Arc::new(context);
+        let rejected = self.rejected.clone();
+
+        // Start the workers. They take connections from the queue until it is closed.
+        let (sender, receiver) = mpsc::sync_channel::<Connection>(self.queue_capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers: Vec<std::thread::JoinHandle<()>> = (0..self.workers.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                let context = context.clone();
+                std::thread::spawn(move || loop {
+                    let next = receiver.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    match next {
+                        // A panic while serving one connection must not take the worker with it.
+                        Ok(stream) => {
+                            let served = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                                || handle_connection(stream, &context)
+                            ));
+                            if served.is_err() {
+                                error!("Worker panicked while serving a connection.");
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
         self.listener_handle = Some(std::thread::spawn(move || {
             
             // Loop to handle incoming connections.
-            info!("Listening for incoming connections on {}...", listener.local_addr().unwrap());
-            for stream in listener.incoming() {
+            info!("Listening for incoming connections on {}...", listener);
+            loop {
+                let stream = listener.accept();
                 
                 // Check if the server should stop.
                 if should_stop.load(Ordering::Relaxed) {
@@ -143,19 +674,29 @@ impl WebServer {
                     break;
                 }
 
-                // Handle the incoming connection.
+                // Queue the incoming connection, or reject it if the queue is full.
                 match stream {
                     Ok(stream) => {
                         info!("Request arrived.");
-                        handle_connection(stream, &routes);
+                        if let Err(TrySendError::Full(stream)) = sender.try_send(stream) {
+                            rejected.fetch_add(1, Ordering::Relaxed);
+                            reject_overloaded(stream, context.security_headers);
+                        }
                     }
                     Err(e) => {
                         error!("Error: {}", e);
                     }
                 }
             }
+
+            // Close the queue and wait for the workers to finish the queued connections.
+            drop(sender);
+            for worker in workers {
+                let _ = worker.join();
+            }
         }));
- 
+
+        Ok(())
     }
 
     /// Stop the web server.
@@ -173,8 +714,8 @@ impl WebServer {
             return;
         }
 
-        // Check if local address is set.
-        if self.local_addr.is_none() {
+        // Check if local address or the socket file is set.
+        if self.local_addr.is_none() && self.unix_path.is_none() {
             error!( "Stop: Local address is not set, cannot stop!" );
             return;
         }
@@ -183,13 +724,25 @@ impl WebServer {
         self.should_stop.store(true, Ordering::Relaxed);
 
         // Call the listner to unblock it.
-        let _ = TcpStream::connect( self.local_addr.unwrap() );
+        if let Some(addr) = self.local_addr {
+            let _ = TcpStream::connect( addr );
+        }
+        #[cfg(unix)]
+        if let Some(path) = &self.unix_path {
+            let _ = std::os::unix::net::UnixStream::connect( path );
+        }
 
         // Wait for the listener to finish.
         if let Some(handle) = self.listener_handle.take() {
             let _ = handle.join();
         }
 
+        // Remove the socket file, so the server can be started again.
+        if let Some(path) = &self.unix_path
+            && let Err(e) = fs::remove_file( path ) {
+            error!( "Stop: Could not remove the socket file {}: {}", path.display(), e );
+        }
+
         // Server is stopped.
         self.is_running = false;
     }
@@ -197,79 +750,432 @@ impl WebServer {
 
 }
 
+/// Check if a route handler can be added to a list of routes.
+/// # Arguments
+/// * `routes` - The routes the handler would be added to.
+/// * `handler` - The RouteHandler to check.
+/// # Returns
+/// A boolean indicating whether the handler is valid and not already in the routes.
+fn is_valid_route(
+    routes: &[RouteHandler],
+    handler: &RouteHandler
+) -> bool {
+
+    // Check if the handler is already registered.
+    if routes.iter().any( |r| r.path == handler.path && r.method == handler.method ) {
+        error!("Cannot add route: Handler for path '{}' with method '{}' is already registered.", handler.path, handler.method);
+        return false;
+    }
+
+    // Validate the handler.
+    if handler.path.is_empty() {
+        error!("Cannot add route: Handler for path '{}' with method '{}' is empty.", handler.path, handler.method);
+        return false;
+    }
+    if handler.method == HttpMethod::Unknown {
+        error!("Cannot add route: Handler for path '{}' has an unknown method.", handler.path);
+        return false;
+    }
+
+    // Check if the path pattern is valid.
+    // For now, we only support simple paths without regex or wildcards.
+    // Paths are matched as they are, so a dot is a plain character, like in `/favicon.ico`.
+    let unsupported_wildcards = [
+        "{", "}", "*", "?", "+", "|", "^", "$", "\\", "#", "&", "="
+    ];
+    // Check if path pattern has any of the unsupported widlcards.
+    for wildcard in unsupported_wildcards.iter() {
+        if handler.path_pattern.contains(wildcard) {
+            error!("Cannot add route: Handler for path '{}' with method '{}' has a path pattern with unsupported wildcard '{}'.", handler.path, handler.method, wildcard);
+            return false;
+        }
+    }
+
+    return true;
+}
+
+/// Find the route that handles a request.
+/// A route for the exact path wins over prefix routes, and a longer prefix wins over a shorter one.
+/// # Arguments
+/// * `router` - The router with the routes to search.
+/// * `method` - The HTTP method of the request.
+/// * `path` - The path of the request.
+/// # Returns
+/// A copy of the matching route, or `None` if no route handles the request.
+fn find_route(
+    router: &dyn Router,
+    method: HttpMethod,
+    path: &str
+) -> Option<RouteHandler> {
+    router.match_route( method, path ).cloned()
+}
+
+/// Create the routes the server has without adding them, like `GET /favicon.ico`.
+/// A route added to the server for the same method and path is used instead.
+fn builtin_routes() -> Vec<RouteHandler> {
+    vec![
+        RouteHandler::new(
+            HttpMethod::GET,
+            "/favicon.ico",
+            Arc::new( |_request| {
+                let mut response = Response::binary( HttpStatus::Ok, FAVICON.to_vec(), "image/x-icon" );
+                response.headers.push(("Cache-Control".to_string(), "public, max-age=604800".to_string()));
+                response
+            })
+        )
+        .with_name("favicon")
+        .with_description("Serves the built-in icon."),
+    ]
+}
+
+/// Listen on a Unix domain socket file. A socket file that no server answers on is left behind by
+/// a server that did not stop, so it is removed and the socket is bound again.
+///
+/// # Arguments
+/// * `path` - The path of the socket file.
+///
+/// # Returns
+/// The listener, or the error if the file is in use, is not a socket, or could not be bound.
+#[cfg(unix)]
+fn bind_unix_socket( path: &std::path::Path ) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::{fs::FileTypeExt, net::{UnixListener, UnixStream}};
+
+    match UnixListener::bind( path ) {
+        Err( e ) if e.kind() == std::io::ErrorKind::AddrInUse
+            && fs::symlink_metadata( path ).is_ok_and( |metadata| metadata.file_type().is_socket() )
+            && UnixStream::connect( path ).is_err() => {
+            info!( "Removing the stale socket file {}", path.display() );
+            fs::remove_file( path )?;
+            UnixListener::bind( path )
+        }
+        result => result,
+    }
+}
+
 /// Handle an incoming connection.
-/// This function reads the request from the stream, parses it, finds the appropriate route handler,
-/// and sends the response back to the client.
-/// This function is called in a separate thread for each incoming connection.
+/// The log events of the connection are in a span with the peer address, a request id, and the
+/// method and path of the request. The last event tells the status and how long the request took.
+/// This function is called on a worker thread for each queued connection.
 fn handle_connection(
-    mut stream: std::net::TcpStream,
-    routes: &Vec<RouteHandler>,
+    mut stream: Connection,
+    context: &ConnectionContext,
 ) {
+    let peer = stream.peer();
+    let span = info_span!(
+        "request",
+        id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+        peer = %peer,
+        method = field::Empty,
+        path = field::Empty
+    );
+    let _entered = span.enter();
+    let started = Instant::now();
+
+    let status = serve_connection(&mut stream, context, &span, started);
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    match status {
+        Some(status) => info!(status = %status, duration_ms, "Request finished"),
+        None => info!(duration_ms, "Connection closed without a response"),
+    }
+}
+
+/// Answer a connection with 503 without reading its request, because there is no room for it in the queue.
+/// The request bytes that have already arrived are read first, so that closing the connection does not
+/// reset it before the client has read the response.
+/// Only one buffer is read, so a client that keeps sending can not hold the listener thread.
+fn reject_overloaded(mut stream: Connection, security_headers: bool) {
+    info!("Too many connections waiting, rejecting the connection.");
+    if stream.set_nonblocking(true).is_ok() {
+        let mut buffer = [0u8; 4096];
+        let _ = stream.read(&mut buffer);
+        let _ = stream.set_nonblocking(false);
+    }
+    let mut response = Response::from_message(
+        HttpStatus::ServiceUnavailable,
+        "The server is too busy to handle the request.",
+        BodyFormat::Html
+    );
+    response.headers.push(("Retry-After".to_string(), RETRY_AFTER_SECONDS.to_string()));
+    if let Err(e) = write_response(&mut stream, response, security_headers) {
+        debug!("Could not write the response: {}", e);
+    }
+    let _ = stream.shutdown_write();
+}
+
+/// Serve the request of a connection.
+/// This function reads the request from the stream, parses it, finds the appropriate route handler,
+/// and sends the response back to the client.
+///
+/// # Arguments
+/// * `stream` - The stream of the connection.
+/// * `context` - The routes and settings of the server.
+/// * `span` - The span of the connection, the method and path are recorded to it.
+/// * `started` - When the connection was accepted.
+///
+/// # Returns
+/// The status of the response, or `None` if no response was sent.
+fn serve_connection(
+    stream: &mut Connection,
+    context: &ConnectionContext,
+    span: &Span,
+    started: Instant,
+) -> Option<HttpStatus> {
+    let security_headers = context.security_headers;
+    let local_peer = stream.is_local();
 
     // Read the request line by line from the buffer to a vector.
-    let buf_reader = std::io::BufReader::new(&stream);
-    let http_request: Vec<_> = buf_reader
-        .lines()
-        .map( |result| result.unwrap() ) // todo: error handling
-        .take_while( |line| !line.is_empty() )
-        .collect();
+    // The read buffer, the line buffer and the lines come from pools and go back when the connection is done.
+    let mut buf_reader = PooledReader::new(&*stream, &context.buffers);
+    let mut line_buffer = context.buffers.take();
+    let mut lines = context.lines.take();
+    let head = match read_head( &mut buf_reader, context.max_target_bytes, &mut line_buffer, &mut lines ) {
+        Ok( head ) => head,
+        Err( e ) => {
+            error!("Could not read the request: {}", e);
+            return None;
+        }
+    };
+    let http_request: &Vec<String> = &lines;
+    if http_request.is_empty() {
+        debug!("Connection closed without a request.");
+        return None;
+    }
+
+    // A request with a too long target is rejected before it is parsed or logged in full.
+    if head.target_too_long {
+        info!("Request target too long: '{}'", truncate_for_log( &http_request[0] ));
+        let accept = http_request.iter().skip(1)
+            .filter_map( |line| line.split_once(':') )
+            .find( |(name, _)| name.trim().eq_ignore_ascii_case("Accept") )
+            .map( |(_, value)| value.trim() );
+        let e = RequestError::TargetTooLong { max_target_bytes: context.max_target_bytes };
+        let response = Response::from_error_as( &e, HttpStatus::UriTooLong, BodyFormat::negotiate( accept ) );
+        return sent(write_response(stream, response, security_headers));
+    }
 
-    debug!("Got request raw: {http_request:#?}");
+    trace!("Got request raw: {http_request:#?}");
 
-    // Wrap the request in a Request struct.
-    let request = Request::new( &http_request );
+    // Wrap the request in a Request struct. A request that is not valid UTF-8 is rejected.
+    let parsed = match head.is_utf8 {
+        true => Request::try_new_with_max_target( http_request, context.max_target_bytes ),
+        false => Err( RequestError::InvalidUtf8 ),
+    };
+    let request = match parsed {
+        Ok( request ) => request,
+        Err( e ) => {
+            let request = Request::new( http_request );
+            info!("Invalid request '{}': {}", request, e);
+            let status = match e {
+                RequestError::TargetTooLong { .. } => HttpStatus::UriTooLong,
+                RequestError::InvalidUtf8 => HttpStatus::BadRequest,
+            };
+            let mut response = Response::from_error_as( &e, status, BodyFormat::negotiate( request.header_joined("Accept").as_deref() ) );
+            response.version = request.version;
+            return sent(write_response(stream, response, security_headers));
+        }
+    };
+    span.record("method", field::display(&request.method));
+    span.record("path", field::display(&request.path));
     info!("Request: '{}'", request.to_string());
-    
-    // Find the route handler for the path.
-    let route_handler = routes.iter().find( 
-            |route| route.handles_path( request.method, &request.path ) );
+
+    // Find the response and write it to the stream.
+    // The request is only kept for the recorder and the observers if there are any.
+    let keep_request = context.recorder.is_enabled() || !context.observers.is_empty();
+    let accept_encoding = request.header_joined("Accept-Encoding");
+    let version = request.version;
+    let (mut response, kept) = respond( request, &mut buf_reader, context, keep_request, local_peer );
+    if let Some( request ) = kept {
+        let duration = started.elapsed();
+        context.recorder.record( &request, &response, duration );
+        for observer in &context.observers {
+            observer( &request, &response, duration );
+        }
+    }
+
+    // Answer in the version of the request and compress the body if the client accepts it.
+    response.version = version;
+    response.compress_for( accept_encoding.as_deref() );
+    sent(write_response(stream, response, security_headers))
+}
+
+/// The status of a written response, or `None` if the client went away before it could be written.
+fn sent( written: std::io::Result<HttpStatus> ) -> Option<HttpStatus> {
+    match written {
+        Ok( status ) => Some( status ),
+        Err( e ) => {
+            debug!("Could not write the response: {}", e);
+            None
+        }
+    }
+}
+
+/// Find the response to a request: read the body, find the route handler and call it.
+///
+/// # Arguments
+/// * `request` - The parsed request, without the body.
+/// * `reader` - The reader of the connection, positioned at the start of the body.
+/// * `context` - The routes and settings of the server.
+/// * `keep_request` - Whether to return the request, as the handler takes it.
+/// * `local_peer` - Whether the client is on the same machine, which lets it use the admin routes.
+///
+/// # Returns
+/// The response, and the request with its body if `keep_request` is set.
+fn respond<R: BufRead>(
+    mut request: Request,
+    reader: &mut R,
+    context: &ConnectionContext,
+    keep_request: bool,
+    local_peer: bool,
+) -> (Response, Option<Request>) {
+
+    // Error responses are rendered in the format the client prefers.
+    let format = BodyFormat::negotiate( request.header_joined("Accept").as_deref() );
+
+    // Find the route handler for the path. The handler is cloned out of the route list
+    // so that the routes are not locked while the handler is running. The route list is
+    // only replaced as a whole, so it is still usable even if a panic poisoned the lock.
+    // The built-in routes are only used if no added route handles the request.
+    let route_handler = find_route( context.routes.read().unwrap_or_else(PoisonError::into_inner).as_ref(), request.method, &request.path )
+        .or_else( || find_route( &context.builtin_routes, request.method, &request.path ) );
+
+    // Read the body of the request, with the limit of the route if it has one.
+    let max_body_bytes = route_handler.as_ref()
+        .and_then( |handler| handler.max_body_bytes )
+        .unwrap_or( context.max_body_bytes );
+    if let Err(e) = request.read_body( reader, max_body_bytes ) {
+        info!("Could not read the body of request '{}': {}", request, e);
+        let status = match e {
+            BodyError::TooLarge { .. } => HttpStatus::PayloadTooLarge,
+            BodyError::Malformed( _ ) | BodyError::Io( _ ) => HttpStatus::BadRequest,
+        };
+        let response = Response::from_error_as( &e, status, format );
+        info!("Response: {}", response.to_string());
+        return (response, keep_request.then_some(request));
+    }
 
     // If no route handler was found, return a 404. otherwise, call the handler.
-    let response: Response;
     if route_handler.is_none() {
         info!("No route handler found for request '{} {}'", request.method.to_string(), request.path);
-        response = Response::new(
-        HttpStatus::NotFound,
-        r#"<!DOCTYPE html>
-                <html lang="en">
-                <head>
-                    <meta charset="UTF-8">
-                    <title>404 Not Found</title>
-                    <style>
-                        body { font-family: sans-serif; background: #f8f8f8; color: #333; text-align: center; margin-top: 10%; }
-                        h1 { font-size: 3em; margin-bottom: 0.2em; }
-                        p { font-size: 1.2em; }
-                    </style>
-                </head>
-                <body>
-                    <h1>404 Not Found</h1>
-                    <p>The page you requested could not be found.</p>
-                </body>
-                </html>
-                "#.to_string(),
-        vec![("Content-Type".to_string(), "text/html; charset=utf-8".to_string())]
+        let response = Response::from_message(
+            HttpStatus::NotFound,
+            "The page you requested could not be found.",
+            format
         );
         info!("Response: {}", response.to_string());
         debug!("No route handler found for request '{} {}'", request.method.to_string(), request.path);
+        return (response, keep_request.then_some(request));
     }
     else {
 
         // Get the handler.
         let handler = route_handler.unwrap();
 
+        // Only administrators can use admin routes.
+        if handler.admin_only && let Some( response ) = context.admin_access.deny( &request, local_peer ) {
+            info!("Route '{}' is only for administrators, response: {}", handler.to_string(), response.to_string());
+            return (response, keep_request.then_some(request));
+        }
+
+        // Reject bodies the route does not accept before calling the handler.
+        if !handler.accepts_content_type( request.content_type() ) {
+            info!("Route '{}' does not accept Content-Type {:?}", handler.to_string(), request.header("Content-Type"));
+            let response = Response::from_message(
+                HttpStatus::UnsupportedMediaType,
+                &format!("Expected Content-Type: {}.", handler.consumes.join(", ")),
+                format
+            );
+            info!("Response: {}", response.to_string());
+            return (response, keep_request.then_some(request));
+        }
+
         info!("Using route handler '{}' for request '{} {}'", 
             handler.to_string(), 
             request.method.to_string(), 
             request.path);
         
+        // Serve a cached response if the route has one.
+        let accept = request.header_joined("Accept");
+        let cache_ttl = handler.cache_ttl.filter( |_| request.method == HttpMethod::GET );
+        if let Some( cached ) = cache_ttl.and_then( |_| context.cache.get( &request.raw_target, accept.as_deref() ) ) {
+            debug!("Serving '{}' from the cache", request.raw_target);
+            return (cached, keep_request.then_some(request));
+        }
+        let target = request.raw_target.clone();
+
+        // Keep a copy of the request if the caller needs it after the handler has taken it.
+        let kept = keep_request.then( || request.clone() );
+
         // Call the route handler.
         // We need to clone the handler because it is an Arc, and we need to move it into the closure 
         // to avoid borrowing issues.
         let handler_arc = handler.handler.clone();
-        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe( move || {
+        let run_handler = move || std::panic::catch_unwind(std::panic::AssertUnwindSafe( move || {
             (handler_arc)(request)
         }));
-        response = match result {
+
+        // A route with a timeout, or any route when the server has a handler timeout, runs the handler
+        // on its own thread and stops waiting at the deadline.
+        // The thread can not be killed, so a late handler keeps running and its response is dropped.
+        // A route that set its own timeout expects to be busy at times, so the client is told to retry;
+        // the handler timeout of the server is a gateway deadline.
+        // The late handlers are counted, and no more handlers are started while there are too many of them.
+        let result = match handler.timeout.or(context.handler_timeout) {
+            None => run_handler(),
+            Some(_) if context.late_handlers.load(Ordering::Relaxed) >= context.max_late_handlers => {
+                error!("Route '{}' not called, {} handlers are still running after their timeout",
+                    handler.name.as_deref().unwrap_or(&handler.to_string()), context.max_late_handlers);
+                let mut response = Response::from_message(
+                    HttpStatus::ServiceUnavailable,
+                    "The server is too busy to handle the request.",
+                    format
+                );
+                response.headers.push(("Retry-After".to_string(), RETRY_AFTER_SECONDS.to_string()));
+                return (response, kept);
+            }
+            Some(timeout) => {
+
+                // Whichever of the handler and the deadline comes second sees the flag set. The deadline
+                // counts the handler as late, and the handler takes itself off the count when it finishes late.
+                let (sender, receiver) = mpsc::channel();
+                let finished = Arc::new(AtomicBool::new(false));
+                let handler_finished = finished.clone();
+                let late_handlers = context.late_handlers.clone();
+                std::thread::spawn(move || {
+                    let result = run_handler();
+                    if handler_finished.swap(true, Ordering::AcqRel) {
+                        late_handlers.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    let _ = sender.send(result);
+                });
+                match receiver.recv_timeout(timeout) {
+                    Ok(result) => result,
+                    Err(_) => {
+                        context.late_handlers.fetch_add(1, Ordering::Relaxed);
+                        if finished.swap(true, Ordering::AcqRel) {
+                            context.late_handlers.fetch_sub(1, Ordering::Relaxed);
+                        }
+                        error!("Route '{}' did not finish in {:?}",
+                            handler.name.as_deref().unwrap_or(&handler.to_string()), timeout);
+                        context.timeouts.fetch_add(1, Ordering::Relaxed);
+                        let status = match handler.timeout {
+                            Some(_) => HttpStatus::ServiceUnavailable,
+                            None => HttpStatus::GatewayTimeout,
+                        };
+                        let mut response = Response::from_message(
+                            status,
+                            "The server took too long to respond.",
+                            format
+                        );
+                        if handler.timeout.is_some() {
+                            response.headers.push(("Retry-After".to_string(), RETRY_AFTER_SECONDS.to_string()));
+                        }
+                        info!("Response: {}", response.to_string());
+                        return (response, kept);
+                    }
+                }
+            }
+        };
+        let response = match result {
             Ok(resp) => resp,
             Err(panic_info) => {
                 // Try to extract the panic message
@@ -282,32 +1188,1237 @@ fn handle_connection(
                 } 
             
                 // Return a 500 Internal Server Error response.
-                Response::new(
-                HttpStatus::InternalServerError,
-                r#"<!DOCTYPE html>
-                    <html lang="en">
-                    <head>
-                        <meta charset="UTF-8">
-                        <title>500 Internal Server Error</title>
-                        <style>
-                            body { font-family: sans-serif; background: #f8f8f8; color: #333; text-align: center; margin-top: 10%; }
-                            h1 { font-size: 3em; margin-bottom: 0.2em; }
-                            p { font-size: 1.2em; }
-                        </style>
-                    </head>
-                    <body>
-                        <h1>500 Internal Server Error</h1>
-                        <p>The server encountered an unexpected condition.</p>
-                    </body>
-                    </html>
-                    "#.to_string(),
-                vec![("Content-Type".to_string(), "text/html; charset=utf-8".to_string())]
-            ) },
+                Response::from_message(
+                    HttpStatus::InternalServerError,
+                    "The server encountered an unexpected condition.",
+                    format
+                ) },
         };
         info!("Response from handler: {}", response.to_string());
+
+        // Keep successful responses of cached routes.
+        if let Some( ttl ) = cache_ttl.filter( |_| response.status == HttpStatus::Ok ) {
+            context.cache.insert( &target, accept.as_deref(), &response, ttl );
+        }
+        (response, kept)
     }
-    
-    // Write the response to the stream.
-    stream.write_all(response.to_string().as_bytes()).unwrap(); // todo: error handling
+}
+
+/// What was found when reading the request line and the headers of a request.
+struct RequestHead {
+    is_utf8: bool,
+    target_too_long: bool,
+}
 
-}
\ No newline at end of file
+/// Read the request line and the headers, up to the empty line before the body.
+/// Lines that are not valid UTF-8 are read with the invalid bytes replaced.
+/// A request line with a too long target is only read up to the limit, the rest of it is skipped.
+///
+/// # Arguments
+/// * `reader` - The reader of the connection.
+/// * `max_target_bytes` - The maximum length of the request target in bytes.
+/// * `line` - A buffer for reading the lines, reused so that each line does not need a new one.
+/// * `lines` - Where the lines are written. Its strings are reused for the lines, and the strings
+///   left over are removed.
+///
+/// # Returns
+/// Whether all the lines were valid UTF-8 and whether the request target was too long,
+/// or an error if reading failed.
+fn read_head<R: BufRead>( reader: &mut R, max_target_bytes: usize, line: &mut Vec<u8>, lines: &mut Vec<String> ) -> std::io::Result<RequestHead> {
+    let mut head = RequestHead { is_utf8: true, target_too_long: false };
+    let mut count = 0;
+    loop {
+        line.clear();
+        if count == 0 {
+
+            // Read the request line without letting a huge target in.
+            let max_line_bytes = max_target_bytes.saturating_add( REQUEST_LINE_OVERHEAD );
+            let read = reader.by_ref().take( max_line_bytes as u64 ).read_until( b'\n', line )?;
+            if read == 0 {
+                break;
+            }
+            if line.last() != Some( &b'\n' ) && read == max_line_bytes {
+                head.target_too_long = true;
+                reader.skip_until( b'\n' )?;
+            }
+        }
+        else if reader.read_until( b'\n', line )? == 0 {
+            break;
+        }
+        while line.last().is_some_and( |byte| *byte == b'\n' || *byte == b'\r' ) {
+            line.pop();
+        }
+        if line.is_empty() {
+            break;
+        }
+        let text = match std::str::from_utf8( line ) {
+            Ok( text ) => std::borrow::Cow::Borrowed( text ),
+            Err( _ ) => {
+                head.is_utf8 = false;
+                String::from_utf8_lossy( line )
+            }
+        };
+        match lines.get_mut( count ) {
+            Some( reused ) => {
+                reused.clear();
+                reused.push_str( &text );
+            }
+            None => lines.push( text.into_owned() ),
+        }
+        count += 1;
+    }
+    lines.truncate( count );
+
+    // The target is the second part of the request line.
+    let target_bytes = lines.first()
+        .and_then( |line| line.split_whitespace().nth( 1 ) )
+        .map_or( 0, str::len );
+    if target_bytes > max_target_bytes {
+        head.target_too_long = true;
+    }
+    Ok( head )
+}
+
+/// Shorten a request line for the log, keeping its start and telling how long it was.
+fn truncate_for_log( line: &str ) -> String {
+    match line.char_indices().nth( LOGGED_TARGET_CHARS ) {
+        Some( (end, _) ) => format!("{}... (truncated)", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Write the response to the stream.
+///
+/// # Arguments
+/// * `stream` - The stream of the connection.
+/// * `response` - The response to write.
+/// * `security_headers` - Whether to add the security headers the response does not already have.
+///
+/// # Returns
+/// The status of the response, or the error if the client went away before it was written.
+fn write_response( stream: &mut Connection, mut response: Response, security_headers: bool ) -> std::io::Result<HttpStatus> {
+    if security_headers {
+        for (name, value) in SECURITY_HEADERS {
+            if !response.headers.iter().any( |(key, _)| key.eq_ignore_ascii_case( name ) ) {
+                response.headers.push( (name.to_string(), value.to_string()) );
+            }
+        }
+    }
+    stream.write_all(&response.to_bytes())?;
+    Ok(response.status)
+}
+
+// Unit tests for WebServer.
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::sync::{mpsc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::webserver::buffer_pool::READ_BUFFER_BYTES;
+    use crate::webserver::router::RadixRouter;
+
+    /// Send a raw request to the server and return the raw response.
+    fn send_request( addr: SocketAddr, raw_request: &str ) -> String {
+        let mut stream = TcpStream::connect( addr ).unwrap();
+        stream.write_all( raw_request.as_bytes() ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string( &mut response ).unwrap();
+        response
+    }
+
+    /// Send a GET request for the path and return the raw response.
+    fn get( addr: SocketAddr, path: &str ) -> String {
+        send_request( addr, &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n") )
+    }
+
+    /// Create a route that responds with the given body.
+    fn text_route( path: &str, body: &'static str ) -> RouteHandler {
+        RouteHandler::new(
+            HttpMethod::GET,
+            path,
+            Arc::new( move |_request| Response::new( HttpStatus::Ok, body.to_string(), vec![] ) )
+        )
+    }
+
+    #[test]
+    fn test_reload_routes() {
+
+        // The slow route tells the test when it has started handling the request.
+        let (started_tx, started_rx) = mpsc::channel::<()>();
+        let started_tx = Mutex::new( started_tx );
+        let slow_route = RouteHandler::new(
+            HttpMethod::GET,
+            "/slow",
+            Arc::new( move |_request| {
+                started_tx.lock().unwrap().send( () ).unwrap();
+                std::thread::sleep( Duration::from_millis( 200 ) );
+                Response::new( HttpStatus::Ok, "old".to_string(), vec![] )
+            })
+        );
+
+        // Start the server with the old routes.
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( slow_route ) );
+        assert!( server.add_route( text_route( "/version", "old" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Send the slow request and reload the routes while it is being handled.
+        let slow_request = std::thread::spawn( move || get( addr, "/slow" ) );
+        started_rx.recv_timeout( Duration::from_secs( 5 ) ).unwrap();
+        assert!( server.reload_routes( vec![
+            text_route( "/slow", "new" ),
+            text_route( "/version", "new" ),
+        ] ) );
+
+        // The request in flight is finished with the old handler, new requests use the new one.
+        let slow_response = slow_request.join().unwrap();
+        assert!( slow_response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( slow_response.ends_with( "old" ) );
+        assert!( get( addr, "/version" ).ends_with( "new" ) );
+        assert!( get( addr, "/slow" ).ends_with( "new" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_client_closes_before_response() {
+
+        // The slow route answers with a body large enough that writing it fails once the client is gone.
+        let (started_tx, started_rx) = mpsc::channel::<()>();
+        let started_tx = Mutex::new( started_tx );
+        let slow_route = RouteHandler::new(
+            HttpMethod::GET,
+            "/slow",
+            Arc::new( move |_request| {
+                started_tx.lock().unwrap().send( () ).unwrap();
+                std::thread::sleep( Duration::from_millis( 100 ) );
+                Response::new( HttpStatus::Ok, "x".repeat( 4 * 1024 * 1024 ), vec![] )
+            })
+        );
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.set_workers( 1 );
+        assert!( server.add_route( slow_route ) );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Close the connection while the handler is still running.
+        let mut stream = TcpStream::connect( addr ).unwrap();
+        stream.write_all( b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n" ).unwrap();
+        started_rx.recv_timeout( Duration::from_secs( 5 ) ).unwrap();
+        drop( stream );
+
+        // The only worker is still there to answer the next request.
+        let response = get( addr, "/version" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.ends_with( "1" ) );
+
+        server.stop();
+    }
+
+    /// Create a route that responds with the body of the request.
+    fn echo_route( path: &str ) -> RouteHandler {
+        RouteHandler::new(
+            HttpMethod::POST,
+            path,
+            Arc::new( |request| Response::new( HttpStatus::Ok, request.body, vec![] ) )
+        )
+    }
+
+    #[test]
+    fn test_chunked_request_body() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( echo_route( "/area" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let response = send_request( addr,
+            "POST /area HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n\
+            a\r\n{\"corners\"\r\n5\r\n: []}\r\n0\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.ends_with( "{\"corners\": []}" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_route_consumes() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( echo_route( "/area" ).consumes( &[ "application/json" ] ) ) );
+        assert!( server.add_route( echo_route( "/echo" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Form data is rejected and the accepted types are listed.
+        let response = send_request( addr,
+            "POST /area HTTP/1.1\r\nAccept: application/json\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: 3\r\n\r\na=1" );
+        assert!( response.starts_with( "HTTP/1.1 415 Unsupported Media Type" ) );
+        assert!( response.ends_with( r#"{"status":"415 Unsupported Media Type","error":"Expected Content-Type: application/json."}"# ) );
+
+        // A missing Content-Type is rejected too.
+        let response = send_request( addr, "POST /area HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}" );
+        assert!( response.starts_with( "HTTP/1.1 415 Unsupported Media Type" ) );
+
+        // JSON passes through, with or without a charset.
+        for content_type in [ "application/json", "Application/JSON; charset=utf-8" ] {
+            let response = send_request( addr,
+                &format!("POST /area HTTP/1.1\r\nContent-Type: {content_type}\r\nContent-Length: 2\r\n\r\n{{}}") );
+            assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+            assert!( response.ends_with( "{}" ) );
+        }
+
+        // Routes without a declaration accept anything.
+        let response = send_request( addr,
+            "POST /echo HTTP/1.1\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nhi" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_cached_route() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        let cache = server.response_cache();
+
+        // The GET route counts how many times the handler runs and echoes the format, the PUT route
+        // changes the shape.
+        let hits = Arc::new( std::sync::atomic::AtomicUsize::new( 0 ) );
+        let version = Arc::new( std::sync::atomic::AtomicUsize::new( 1 ) );
+        let (get_hits, get_version, put_version) = (hits.clone(), version.clone(), version.clone());
+        assert!( server.add_route( RouteHandler::new(
+            HttpMethod::GET,
+            "/api/shapes/1",
+            Arc::new( move |request| {
+                get_hits.fetch_add( 1, Ordering::SeqCst );
+                let format = request.query_param( "format" ).unwrap_or( "json" );
+                Response::new( HttpStatus::Ok, format!( "{format} {}", get_version.load( Ordering::SeqCst ) ), vec![] )
+            })
+        ).cached( Duration::from_secs( 60 ) ) ) );
+        assert!( server.add_route( RouteHandler::new(
+            HttpMethod::PUT,
+            "/api/shapes/1",
+            Arc::new( move |_request| {
+                put_version.fetch_add( 1, Ordering::SeqCst );
+                cache.invalidate( "/api/shapes/1" );
+                Response::new( HttpStatus::Ok, String::new(), vec![] )
+            })
+        ) ) );
+        assert!( server.add_route( RouteHandler::new(
+            HttpMethod::GET,
+            "/short",
+            Arc::new( |_request| Response::new( HttpStatus::Ok, "short".to_string(), vec![] ) )
+        ).cached( Duration::from_millis( 50 ) ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // The second GET is served from the cache.
+        assert!( get( addr, "/api/shapes/1" ).ends_with( "\r\n\r\njson 1" ) );
+        assert!( get( addr, "/api/shapes/1" ).ends_with( "\r\n\r\njson 1" ) );
+        assert_eq!( hits.load( Ordering::SeqCst ), 1 );
+
+        // A different query is cached separately, and does not get the response of the path alone.
+        assert!( get( addr, "/api/shapes/1?format=svg" ).ends_with( "\r\n\r\nsvg 1" ) );
+        assert!( get( addr, "/api/shapes/1?format=svg" ).ends_with( "\r\n\r\nsvg 1" ) );
+        assert!( get( addr, "/api/shapes/1" ).ends_with( "\r\n\r\njson 1" ) );
+        assert_eq!( hits.load( Ordering::SeqCst ), 2 );
+
+        // The PUT invalidates the cached responses.
+        send_request( addr, "PUT /api/shapes/1 HTTP/1.1\r\nContent-Length: 0\r\n\r\n" );
+        assert!( get( addr, "/api/shapes/1" ).ends_with( "\r\n\r\njson 2" ) );
+        assert!( get( addr, "/api/shapes/1?format=svg" ).ends_with( "\r\n\r\nsvg 2" ) );
+        assert_eq!( hits.load( Ordering::SeqCst ), 4 );
+
+        // Responses expire after the time to live.
+        get( addr, "/short" );
+        assert!( server.response_cache().len() >= 2 );
+        std::thread::sleep( Duration::from_millis( 100 ) );
+        assert!( server.response_cache().get( "/short", None ).is_none() );
+
+        server.invalidate_cache( "" );
+        assert!( server.response_cache().is_empty() );
+        server.stop();
+    }
+
+    #[test]
+    fn test_security_headers() {
+        let framed = RouteHandler::new(
+            HttpMethod::GET,
+            "/framed",
+            Arc::new( |_request| Response::new(
+                HttpStatus::Ok,
+                String::new(),
+                vec![ ("X-Frame-Options".to_string(), "SAMEORIGIN".to_string()) ]
+            ) )
+        );
+
+        // Enabled, the headers are added unless the handler set them.
+        let mut server = WebServer::new( "127.0.0.1", "0" ).with_security_headers();
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        assert!( server.add_route( framed ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+        let response = get( addr, "/version" );
+        assert!( response.contains( "\r\nX-Content-Type-Options: nosniff\r\n" ) );
+        assert!( response.contains( "\r\nX-Frame-Options: DENY\r\n" ) );
+        assert!( response.contains( "\r\nContent-Security-Policy: " ) );
+        assert!( get( addr, "/missing" ).contains( "X-Content-Type-Options: nosniff" ) );
+        let response = get( addr, "/framed" );
+        assert!( response.contains( "X-Frame-Options: SAMEORIGIN" ) );
+        assert!( !response.contains( "X-Frame-Options: DENY" ) );
+        server.stop();
+
+        // Disabled by default.
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        server.start().unwrap();
+        let response = get( server.local_addr().unwrap(), "/version" );
+        assert!( !response.contains( "X-Content-Type-Options" ) );
+        assert!( !response.contains( "X-Frame-Options" ) );
+        assert!( !response.contains( "Content-Security-Policy" ) );
+        server.stop();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir().join( format!("area-calculator-{}.sock", std::process::id()) );
+        let _ = fs::remove_file( &path );
+        let mut server = WebServer::bind_unix( path.to_str().unwrap() );
+        assert!( server.add_route( text_route( "/", "over a socket" ) ) );
+        server.start().unwrap();
+        assert!( server.local_addr().is_none() );
+        assert!( path.exists() );
+
+        let mut stream = UnixStream::connect( &path ).unwrap();
+        stream.write_all( b"GET / HTTP/1.1\r\n\r\n" ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string( &mut response ).unwrap();
+        assert!( response.starts_with( "HTTP/1.1 200 OK\r\n" ), "{response}" );
+        assert!( response.ends_with( "\r\n\r\nover a socket" ), "{response}" );
+
+        // Stopping removes the socket file.
+        server.stop();
+        assert!( !path.exists() );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket_file_exists() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        // A socket file left behind by a listener that is gone is replaced.
+        let path = std::env::temp_dir().join( format!("area-calculator-{}-stale.sock", std::process::id()) );
+        let _ = fs::remove_file( &path );
+        drop( UnixListener::bind( &path ).unwrap() );
+        assert!( path.exists() );
+        let mut server = WebServer::bind_unix( path.to_str().unwrap() );
+        assert!( server.add_route( text_route( "/", "over a socket" ) ) );
+        server.start().unwrap();
+        let mut stream = UnixStream::connect( &path ).unwrap();
+        stream.write_all( b"GET / HTTP/1.1\r\n\r\n" ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string( &mut response ).unwrap();
+        assert!( response.ends_with( "\r\n\r\nover a socket" ), "{response}" );
+
+        // A socket file another server listens on is not taken over.
+        let mut other = WebServer::bind_unix( path.to_str().unwrap() );
+        assert_eq!( other.start().unwrap_err().kind(), std::io::ErrorKind::AddrInUse );
+        server.stop();
+
+        // A file that is not a socket is not removed.
+        fs::write( &path, "not a socket" ).unwrap();
+        assert_eq!( other.start().unwrap_err().kind(), std::io::ErrorKind::AddrInUse );
+        assert_eq!( fs::read_to_string( &path ).unwrap(), "not a socket" );
+        let _ = fs::remove_file( &path );
+    }
+
+    #[test]
+    fn test_set_router() {
+        // The routes added before the router is set are moved to it.
+        let mut server = WebServer::bind_random_port( "127.0.0.1" );
+        assert!( server.add_route( text_route( "/static", "/static" ).with_prefix_match() ) );
+        server.set_router( Box::new( RadixRouter::default() ) );
+        assert!( server.add_route( text_route( "/static/images", "/static/images" ).with_prefix_match() ) );
+        assert!( !server.add_route( text_route( "/static", "again" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        assert!( get( addr, "/static/css/site.css" ).ends_with( "\r\n\r\n/static" ) );
+        assert!( get( addr, "/static/images/a.png" ).ends_with( "\r\n\r\n/static/images" ) );
+        assert!( get( addr, "/other" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+        server.stop();
+    }
+
+    #[test]
+    fn test_bind_random_port() {
+        let mut first = WebServer::bind_random_port( "127.0.0.1" );
+        let mut second = WebServer::bind_random_port( "127.0.0.1" );
+        assert!( first.local_addr().is_none() );
+        first.start().unwrap();
+        second.start().unwrap();
+
+        // Both servers get their own port and answer on it.
+        let (first_addr, second_addr) = (first.local_addr().unwrap(), second.local_addr().unwrap());
+        assert_ne!( first_addr.port(), 0 );
+        assert_ne!( second_addr.port(), 0 );
+        assert_ne!( first_addr.port(), second_addr.port() );
+        assert!( get( first_addr, "/missing" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+        assert!( get( second_addr, "/missing" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+
+        first.stop();
+        second.stop();
+    }
+
+    #[test]
+    fn test_route_timeout() {
+        let sleeping_route = |path: &str, sleep: u64| RouteHandler::new(
+            HttpMethod::GET,
+            path,
+            Arc::new( move |_request| {
+                std::thread::sleep( Duration::from_millis( sleep ) );
+                Response::new( HttpStatus::Ok, "done".to_string(), vec![] )
+            })
+        ).with_timeout( Duration::from_millis( 50 ) );
+
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( sleeping_route( "/slow", 500 ).with_name( "slow" ) ) );
+        assert!( server.add_route( sleeping_route( "/fast", 0 ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let response = get( addr, "/slow" );
+        assert!( response.starts_with( "HTTP/1.1 503 Service Unavailable" ) );
+        assert!( response.contains( "\r\nRetry-After: 1\r\n" ) );
+        assert_eq!( server.timeout_count(), 1 );
+
+        let response = get( addr, "/fast" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.ends_with( "done" ) );
+        assert_eq!( server.timeout_count(), 1 );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_query_and_encoded_path() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( RouteHandler::new(
+            HttpMethod::GET,
+            "/my shape",
+            Arc::new( |request: Request| Response::new(
+                HttpStatus::Ok,
+                format!("{} {}", request.raw_target, request.query_param( "unit" ).unwrap_or( "" )),
+                vec![]
+            ) )
+        ) ) );
+        server.start().unwrap();
+
+        let response = get( server.local_addr().unwrap(), "/my%20shape?unit=m%C2%B2" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.ends_with( "/my%20shape?unit=m%C2%B2 m²" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_unicode_paths() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/caf\u{00E9}", "composed" ) ) );
+        assert!( server.add_route( text_route( "/nai\u{0308}ve", "decomposed" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Both forms of the letter hit the same route.
+        assert!( get( addr, "/caf%C3%A9" ).ends_with( "composed" ) );
+        assert!( get( addr, "/cafe%CC%81" ).ends_with( "composed" ) );
+        assert!( get( addr, "/na%C3%AFve" ).ends_with( "decomposed" ) );
+        assert!( get( addr, "/nai%CC%88ve" ).ends_with( "decomposed" ) );
+
+        // Invalid UTF-8, encoded or raw, is rejected.
+        assert!( get( addr, "/caf%E9" ).starts_with( "HTTP/1.1 400 Bad Request" ) );
+        let mut stream = TcpStream::connect( addr ).unwrap();
+        stream.write_all( b"GET /caf\xe9 HTTP/1.1\r\nHost: localhost\r\n\r\n" ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string( &mut response ).unwrap();
+        assert!( response.starts_with( "HTTP/1.1 400 Bad Request" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_request_body_too_large() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.set_max_body_bytes( 4 );
+        assert!( server.add_route( echo_route( "/area" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let response = send_request( addr,
+            "POST /area HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello" );
+        assert!( response.starts_with( "HTTP/1.1 413 Payload Too Large" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_route_body_limit() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.set_max_body_bytes( 8 );
+        assert!( server.add_route( echo_route( "/batch" ).with_max_body_bytes( 64 ) ) );
+        assert!( server.add_route( echo_route( "/area" ).with_max_body_bytes( 4 ) ) );
+        assert!( server.add_route( echo_route( "/echo" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // The same body is accepted by the route with a larger limit, even above the limit of the server.
+        let post = |path: &str| send_request( addr,
+            &format!("POST {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 16\r\n\r\n0123456789abcdef") );
+        assert!( post( "/batch" ).ends_with( "0123456789abcdef" ) );
+        assert!( post( "/area" ).starts_with( "HTTP/1.1 413 Payload Too Large" ) );
+        assert!( post( "/echo" ).starts_with( "HTTP/1.1 413 Payload Too Large" ) );
+
+        // A smaller limit of a route applies to bodies the server would accept.
+        let response = send_request( addr, "POST /area HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello" );
+        assert!( response.starts_with( "HTTP/1.1 413 Payload Too Large" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_not_found_negotiated() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let html = get( addr, "/missing" );
+        assert!( html.starts_with( "HTTP/1.1 404 Not Found" ) );
+        assert!( html.contains( "<h1>404 Not Found</h1>" ) );
+
+        let json = send_request( addr, "GET /missing HTTP/1.1\r\nAccept: application/json\r\n\r\n" );
+        assert!( json.starts_with( "HTTP/1.1 404 Not Found" ) );
+        assert!( json.ends_with( r#"{"status":"404 Not Found","error":"The page you requested could not be found."}"# ) );
+
+        server.stop();
+    }
+
+    /// Send a GET request for the path and return the raw response bytes.
+    fn get_bytes( addr: SocketAddr, path: &str ) -> Vec<u8> {
+        let mut stream = TcpStream::connect( addr ).unwrap();
+        stream.write_all( format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes() ).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end( &mut response ).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_route_path_with_dot() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/shapes.csv", "csv" ) ) );
+        assert!( !server.add_route( text_route( "/shapes*", "any" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // The dot only matches a dot.
+        assert!( get( addr, "/shapes.csv" ).ends_with( "\r\n\r\ncsv" ) );
+        assert!( get( addr, "/shapes_csv" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_favicon() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.start().unwrap();
+        let response = get_bytes( server.local_addr().unwrap(), "/favicon.ico" );
+        let head = String::from_utf8_lossy( &response[..response.len() - FAVICON.len()] ).into_owned();
+        assert!( head.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( head.contains( "\r\nContent-Type: image/x-icon\r\n" ) );
+        assert!( head.contains( "\r\nCache-Control: public, max-age=604800\r\n" ) );
+        assert!( response.ends_with( FAVICON ) );
+        server.stop();
+    }
+
+    #[test]
+    fn test_favicon_route_overrides_builtin() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/favicon.ico", "custom" ) ) );
+        server.start().unwrap();
+        let response = get( server.local_addr().unwrap(), "/favicon.ico" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.ends_with( "custom" ) );
+        server.stop();
+    }
+
+    #[test]
+    fn test_route_listing() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/version", "1" ).with_name( "version" ).with_description( "The version." ) ) );
+        assert!( server.add_route( echo_route( "/area" ) ) );
+        assert!( server.add_route_listing() );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let response = get( addr, "/admin/routes" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        let body = response.split( "\r\n\r\n" ).nth( 1 ).unwrap();
+        let listing = JsonValue::parse( body ).unwrap();
+        let routes = listing.as_array().unwrap();
+
+        // The routes are sorted by path and the listing includes itself.
+        let paths: Vec<&str> = routes.iter().map( |route| route.get( "path" ).unwrap().as_str().unwrap() ).collect();
+        assert_eq!( paths, vec![ "/admin/routes", "/area", "/version" ] );
+        assert_eq!( routes[0].get( "name" ).and_then( JsonValue::as_str ), Some( "route-listing" ) );
+        assert_eq!( routes[1].get( "method" ).and_then( JsonValue::as_str ), Some( "POST" ) );
+        assert_eq!( routes[1].get( "name" ), Some( &JsonValue::Null ) );
+        assert_eq!( routes[2].get( "method" ).and_then( JsonValue::as_str ), Some( "GET" ) );
+        assert_eq!( routes[2].get( "name" ).and_then( JsonValue::as_str ), Some( "version" ) );
+        assert_eq!( routes[2].get( "description" ).and_then( JsonValue::as_str ), Some( "The version." ) );
+        assert_eq!( routes[0].get( "admin_only" ), Some( &JsonValue::Bool( true ) ) );
+        assert_eq!( routes[2].get( "admin_only" ), Some( &JsonValue::Bool( false ) ) );
+
+        // Remote clients can not list the routes.
+        let context = context( server.routes.read().unwrap().routes().to_vec() );
+        assert_eq!( admin_response( &context, &[ "GET /admin/routes HTTP/1.1" ], false ).status, HttpStatus::Forbidden );
+
+        server.stop();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_debug_routes() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        assert!( server.add_route( echo_route( "/area" ) ) );
+        assert!( server.add_debug_routes() );
+        server.start().unwrap();
+
+        let response = get( server.local_addr().unwrap(), "/debug/routes" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.contains( "Content-Type: text/plain; charset=utf-8" ) );
+        let body = response.split( "\r\n\r\n" ).nth( 1 ).unwrap();
+        assert_eq!( body, "GET /debug/routes\nGET /version\nPOST /area\n" );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_concurrent_requests_to_stateful_handler() {
+        const CLIENTS: usize = 16;
+        const REQUESTS_PER_CLIENT: usize = 25;
+
+        // The handler counts the requests, and panics on every tenth request while
+        // holding the lock. The lock is recovered from the poisoning on the next request.
+        let counter = Arc::new( Mutex::new( 0usize ) );
+        let handler_counter = counter.clone();
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( RouteHandler::new(
+            HttpMethod::POST,
+            "/count",
+            Arc::new( move |_request| {
+                let mut count = handler_counter.lock().unwrap_or_else( PoisonError::into_inner );
+                *count += 1;
+                if *count % 10 == 0 {
+                    panic!( "Request {} failed on purpose.", *count );
+                }
+                Response::new( HttpStatus::Ok, count.to_string(), vec![] )
+            })
+        ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Send requests from many clients at the same time.
+        let clients: Vec<_> = (0..CLIENTS).map( |_| std::thread::spawn( move || {
+            (0..REQUESTS_PER_CLIENT)
+                .map( |_| send_request( addr, "POST /count HTTP/1.1\r\nContent-Length: 0\r\n\r\n" ) )
+                .collect::<Vec<String>>()
+        })).collect();
+        let responses: Vec<String> = clients.into_iter().flat_map( |client| client.join().unwrap() ).collect();
+        server.stop();
+
+        // Every request was counted once, and only the failed ones got a 500.
+        let total = CLIENTS * REQUESTS_PER_CLIENT;
+        assert_eq!( *counter.lock().unwrap_or_else( PoisonError::into_inner ), total );
+        let errors = responses.iter().filter( |r| r.starts_with( "HTTP/1.1 500" ) ).count();
+        let successes = responses.iter().filter( |r| r.starts_with( "HTTP/1.1 200 OK" ) ).count();
+        assert_eq!( errors, total / 10 );
+        assert_eq!( successes, total - total / 10 );
+    }
+
+    #[test]
+    fn test_static_directory() {
+        let root = std::env::temp_dir().join( format!("area-calculator-webserver-{}", std::process::id()) );
+        std::fs::create_dir_all( root.join( "public" ) ).unwrap();
+        std::fs::write( root.join( "public/hello.txt" ), "hello" ).unwrap();
+        std::fs::write( root.join( "secret.txt" ), "secret" ).unwrap();
+
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/static/version", "1" ) ) );
+        assert!( server.add_static_directory( "/static", root.join( "public" ).to_str().unwrap() ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let response = get( addr, "/static/hello.txt" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK" ) );
+        assert!( response.contains( "Content-Type: text/plain; charset=utf-8" ) );
+        assert!( response.ends_with( "hello" ) );
+        assert!( get( addr, "/static/missing.txt" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+        assert!( get( addr, "/static/../secret.txt" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+        assert!( get( addr, "/statics/hello.txt" ).starts_with( "HTTP/1.1 404 Not Found" ) );
+
+        // An exact route wins over the prefix route.
+        assert!( get( addr, "/static/version" ).ends_with( "1" ) );
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_reload_routes_invalid_keeps_old_routes() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/version", "old" ) ) );
+
+        // Duplicate routes are rejected and the old routes are kept.
+        assert!( !server.reload_routes( vec![
+            text_route( "/version", "new" ),
+            text_route( "/version", "newer" ),
+        ] ) );
+
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+        assert!( get( addr, "/version" ).ends_with( "old" ) );
+        server.stop();
+    }
+
+    /// A log writer that keeps the output in memory.
+    #[derive(Clone, Default)]
+    struct CapturedLog( Arc<Mutex<Vec<u8>>> );
+
+    impl Write for CapturedLog {
+        fn write( &mut self, buf: &[u8] ) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice( buf );
+            Ok( buf.len() )
+        }
+
+        fn flush( &mut self ) -> std::io::Result<()> {
+            Ok( () )
+        }
+    }
+
+    /// Handle one connection on this thread with a capturing subscriber.
+    /// Returns the raw response, the log output and the peer address of the client.
+    fn capture_connection( context: &ConnectionContext, raw_request: &[u8] ) -> (String, String, SocketAddr) {
+        let log = CapturedLog::default();
+        let writer = log.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer( move || writer.clone() )
+            .with_ansi( false )
+            .with_max_level( tracing::Level::INFO )
+            .finish();
+
+        // The client runs on its own thread so that a large request does not block the test.
+        let listener = TcpListener::bind( "127.0.0.1:0" ).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let raw_request = raw_request.to_vec();
+        let client = std::thread::spawn( move || {
+            let mut stream = TcpStream::connect( addr ).unwrap();
+            stream.write_all( &raw_request ).unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end( &mut response ).unwrap();
+            String::from_utf8_lossy( &response ).into_owned()
+        });
+        let (stream, peer) = listener.accept().unwrap();
+        tracing::subscriber::with_default( subscriber, || handle_connection( Connection::from( stream ), context ) );
+        let response = client.join().unwrap();
+        let output = String::from_utf8( log.0.lock().unwrap().clone() ).unwrap();
+        (response, output, peer)
+    }
+
+    /// Create a connection context with the routes and the default settings.
+    fn context( routes: Vec<RouteHandler> ) -> ConnectionContext {
+        ConnectionContext {
+            routes: Arc::new( RwLock::new( Box::new( LinearRouter::new( routes ) ) ) ),
+            builtin_routes: LinearRouter::new( builtin_routes() ),
+            cache: ResponseCache::new( 1 ),
+            timeouts: Arc::new( AtomicU64::new( 0 ) ),
+            handler_timeout: None,
+            late_handlers: Arc::new( AtomicUsize::new( 0 ) ),
+            max_late_handlers: MAX_LATE_HANDLERS,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_target_bytes: DEFAULT_MAX_TARGET_BYTES,
+            security_headers: false,
+            buffers: BufferPool::new( 2 ),
+            lines: BufferPool::new( 1 ),
+            recorder: RequestRecorder::new( DEFAULT_RECORDING_CAPACITY ),
+            observers: Vec::new(),
+            admin_access: AdminAccess { local: true, token: None },
+        }
+    }
+
+    #[test]
+    fn test_request_span() {
+        let context = context( vec![ text_route( "/version", "1" ) ] );
+        let (response, output, peer) = capture_connection( &context, b"GET /version?x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n" );
+        assert!( response.ends_with( "1" ) );
+
+        // Every event of the request carries the span fields, the last one the status and duration.
+        let lines: Vec<&str> = output.lines().collect();
+        assert!( lines.len() >= 2, "{output}" );
+        for line in lines.iter().filter( |line| line.contains( " INFO " ) ) {
+            assert!( line.contains( "request{id=" ), "{line}" );
+        }
+        let finished = lines.last().unwrap();
+        assert!( finished.contains( &format!("peer={peer} method=GET path=/version}}") ), "{finished}" );
+        assert!( finished.contains( "Request finished status=200 OK duration_ms=" ), "{finished}" );
+
+        // The raw request is only logged on the trace level.
+        assert!( !output.contains( "Got request raw" ) );
+
+        // The read buffer, the line buffer and the lines are back in the pools for the next connection.
+        assert_eq!( context.buffers.len(), 2 );
+        assert_eq!( context.lines.len(), 1 );
+        capture_connection( &context, b"GET /version HTTP/1.1\r\n\r\n" );
+        assert_eq!( context.buffers.len(), 2 );
+        assert_eq!( context.lines.len(), 1 );
+    }
+
+    #[test]
+    fn test_uri_too_long() {
+        let context = context( vec![ text_route( "/version", "1" ) ] );
+        let path = format!("/{}", "a".repeat( 100 * 1024 ));
+        let started = Instant::now();
+        let (response, output, _) = capture_connection(
+            &context,
+            format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: text/plain\r\n\r\n").as_bytes()
+        );
+        assert!( started.elapsed() < Duration::from_secs( 2 ) );
+        assert!( response.starts_with( "HTTP/1.1 414 URI Too Long" ), "{response}" );
+        assert!( response.contains( "longer than 8192 bytes" ), "{response}" );
+
+        // Only the start of the path is logged.
+        assert!( output.contains( &format!("GET /{}... (truncated)", "a".repeat( LOGGED_TARGET_CHARS - 5 )) ), "{output}" );
+        assert!( !output.contains( &path[..1024] ) );
+
+        // A target just below the limit is still routed.
+        let mut context = context;
+        context.max_target_bytes = 16;
+        let (response, _, _) = capture_connection( &context, b"GET /version?a=12345 HTTP/1.1\r\n\r\n" );
+        assert!( response.ends_with( "1" ), "{response}" );
+        let (response, _, _) = capture_connection( &context, b"GET /version?a=123456 HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 414 URI Too Long" ), "{response}" );
+    }
+
+    #[test]
+    fn test_recording() {
+        let path = std::env::temp_dir().join( format!("area-calculator-webserver-recording-{}.jsonl", std::process::id()) );
+        let _ = fs::remove_file( &path );
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.set_recording( true, path.to_str() );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        assert!( server.add_recording_routes() );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // A recorded request is in the file and in the recent requests, without the credentials.
+        send_request( addr, "GET /version HTTP/1.1\r\nAuthorization: Bearer secret\r\nCookie: id=1\r\n\r\n" );
+        let file = fs::read_to_string( &path ).unwrap();
+        assert_eq!( file.lines().count(), 1 );
+        assert!( file.contains( r#""target":"/version""# ), "{file}" );
+        assert!( file.contains( r#""Authorization":"[redacted]""# ), "{file}" );
+        assert!( !file.contains( "secret" ) && !file.contains( "id=1" ) );
+        let recent = get( addr, "/admin/recent-requests" );
+        assert!( recent.contains( r#""Authorization":"[redacted]""# ), "{recent}" );
+        assert!( recent.contains( r#""status":"200 OK""# ), "{recent}" );
+
+        // With recording off, nothing more is recorded.
+        let response = send_request( addr, "PUT /admin/recording HTTP/1.1\r\nContent-Length: 17\r\n\r\n{\"enabled\":false}" );
+        assert!( response.ends_with( r#"{"enabled":false}"# ), "{response}" );
+        let recorded = server.request_recorder().recent().len();
+        get( addr, "/version" );
+        assert_eq!( server.request_recorder().recent().len(), recorded );
+        assert_eq!( fs::read_to_string( &path ).unwrap().lines().count(), recorded );
+
+        // Remote clients can not read the recorded requests or turn recording on.
+        let context = context( server.routes.read().unwrap().routes().to_vec() );
+        assert_eq!( admin_response( &context, &[ "GET /admin/recent-requests HTTP/1.1" ], false ).status, HttpStatus::Forbidden );
+        assert_eq!( admin_response( &context, &[ "GET /admin/recording HTTP/1.1" ], false ).status, HttpStatus::Forbidden );
+        assert_eq!( admin_response( &context, &[ "PUT /admin/recording HTTP/1.1" ], false ).status, HttpStatus::Forbidden );
+        assert!( !server.request_recorder().is_enabled() );
+
+        server.stop();
+        let _ = fs::remove_file( &path );
+    }
+
+    #[test]
+    fn test_on_request() {
+        let observed: Arc<Mutex<Vec<(String, HttpStatus, Duration)>>> = Arc::new( Mutex::new( Vec::new() ) );
+        let observer = observed.clone();
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        server.on_request( Arc::new( move |request, response, duration| {
+            observer.lock().unwrap().push( (request.raw_target.clone(), response.status, duration) );
+        }));
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        assert!( get( addr, "/version?x=1" ).ends_with( "1" ) );
+        get( addr, "/missing" );
+        server.stop();
+
+        let observed = observed.lock().unwrap();
+        assert_eq!( observed.len(), 2 );
+        assert_eq!( (observed[0].0.as_str(), observed[0].1), ("/version?x=1", HttpStatus::Ok) );
+        assert_eq!( (observed[1].0.as_str(), observed[1].1), ("/missing", HttpStatus::NotFound) );
+        assert!( observed[0].2 > Duration::ZERO );
+    }
+
+    #[test]
+    fn test_gzip() {
+        let json = RouteHandler::new(
+            HttpMethod::GET,
+            "/shapes",
+            Arc::new( |_request| Response::json( HttpStatus::Ok, &JsonValue::from( "square ".repeat( 50 ) ) ) )
+        );
+        let png = RouteHandler::new(
+            HttpMethod::GET,
+            "/shape.png",
+            Arc::new( |_request| Response::binary( HttpStatus::Ok, vec![0u8; 512], "image/png" ) )
+        );
+        let log = RouteHandler::new(
+            HttpMethod::GET,
+            "/log.txt",
+            Arc::new( |_request| Response::partial_content( "line ".repeat( 40 ).into_bytes(), 0, 1000, "text/plain" ) )
+        );
+        let context = context( vec![ json, png, log ] );
+
+        let (response, _, _) = capture_connection( &context, b"GET /shapes HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n" );
+        assert!( response.contains( "Content-Encoding: gzip\r\n" ), "{response}" );
+        assert!( !response.contains( "square square" ), "{response}" );
+
+        // All the Accept-Encoding headers are considered, not only the first.
+        let (response, _, _) = capture_connection( &context, b"GET /shapes HTTP/1.1\r\nAccept-Encoding: br\r\nAccept-Encoding: gzip\r\n\r\n" );
+        assert!( response.contains( "Content-Encoding: gzip\r\n" ), "{response}" );
+
+        let (response, _, _) = capture_connection( &context, b"GET /shape.png HTTP/1.1\r\nAccept-Encoding: gzip\r\n\r\n" );
+        assert!( !response.contains( "Content-Encoding" ), "{response}" );
+        assert!( response.contains( "Content-Length: 512\r\n" ), "{response}" );
+
+        // A range is sent as it is, so its Content-Range matches the body.
+        let (response, _, _) = capture_connection( &context, b"GET /log.txt HTTP/1.1\r\nRange: bytes=0-199\r\nAccept-Encoding: gzip\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 206 Partial Content\r\n" ), "{response}" );
+        assert!( !response.contains( "Content-Encoding" ), "{response}" );
+        assert!( response.contains( "Content-Range: bytes 0-199/1000\r\n" ), "{response}" );
+        assert!( response.contains( "Content-Length: 200\r\n" ), "{response}" );
+    }
+
+    #[test]
+    fn test_handler_timeout() {
+        let slow = RouteHandler::new(
+            HttpMethod::GET,
+            "/slow",
+            Arc::new( |_request| {
+                std::thread::sleep( Duration::from_millis( 500 ) );
+                Response::new( HttpStatus::Ok, "done".to_string(), vec![] )
+            })
+        ).with_name( "slow" );
+        let mut context = context( vec![ slow ] );
+        context.handler_timeout = Some( Duration::from_millis( 50 ) );
+
+        // The server answers at the deadline and logs the route, while the handler keeps sleeping.
+        let started = std::time::Instant::now();
+        let (response, output, _) = capture_connection( &context, b"GET /slow HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 504 Gateway Timeout\r\n" ), "{response}" );
+        assert!( !response.contains( "Retry-After" ), "{response}" );
+        assert!( started.elapsed() < Duration::from_millis( 500 ) );
+        assert!( output.contains( "Route 'slow' did not finish in 50ms" ), "{output}" );
+        assert_eq!( context.timeouts.load( Ordering::Relaxed ), 1 );
+    }
+
+    #[test]
+    fn test_late_handlers_bound() {
+        let slow = RouteHandler::new(
+            HttpMethod::GET,
+            "/slow",
+            Arc::new( |_request| {
+                std::thread::sleep( Duration::from_millis( 300 ) );
+                Response::new( HttpStatus::Ok, "done".to_string(), vec![] )
+            })
+        ).with_name( "slow" );
+        let mut context = context( vec![ slow ] );
+        context.handler_timeout = Some( Duration::from_millis( 50 ) );
+        context.max_late_handlers = 1;
+
+        // The first handler is still running after its timeout, so the next one is not started.
+        let (response, _, _) = capture_connection( &context, b"GET /slow HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 504 Gateway Timeout\r\n" ), "{response}" );
+        assert_eq!( context.late_handlers.load( Ordering::Relaxed ), 1 );
+        let (response, output, _) = capture_connection( &context, b"GET /slow HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 503 Service Unavailable\r\n" ), "{response}" );
+        assert!( response.contains( "Retry-After: 1\r\n" ), "{response}" );
+        assert!( output.contains( "Route 'slow' not called, 1 handlers are still running after their timeout" ), "{output}" );
+        assert_eq!( context.timeouts.load( Ordering::Relaxed ), 1 );
+
+        // Once the late handler has finished, handlers are started again.
+        let deadline = std::time::Instant::now() + Duration::from_secs( 5 );
+        while context.late_handlers.load( Ordering::Relaxed ) > 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep( Duration::from_millis( 10 ) );
+        }
+        assert_eq!( context.late_handlers.load( Ordering::Relaxed ), 0 );
+        let (response, _, _) = capture_connection( &context, b"GET /slow HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 504 Gateway Timeout\r\n" ), "{response}" );
+    }
+
+    #[test]
+    fn test_http_version() {
+        let route = RouteHandler::new( HttpMethod::GET, "/", Arc::new( |_request| Response::new( HttpStatus::Ok, "ok".to_string(), Vec::new() ) ) );
+        let context = context( vec![ route ] );
+
+        let (response, _, _) = capture_connection( &context, b"GET / HTTP/1.0\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.0 200 OK\r\n" ), "{response}" );
+        let (response, _, _) = capture_connection( &context, b"GET /missing HTTP/1.0\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.0 404 Not Found\r\n" ), "{response}" );
+        let (response, _, _) = capture_connection( &context, b"GET / HTTP/1.1\r\n\r\n" );
+        assert!( response.starts_with( "HTTP/1.1 200 OK\r\n" ), "{response}" );
+    }
+
+    #[test]
+    fn test_overloaded_queue() {
+
+        // The slow route blocks its worker until the test releases it.
+        let (started_tx, started_rx) = mpsc::channel::<()>();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (started_tx, release_rx) = (Mutex::new( started_tx ), Mutex::new( release_rx ));
+        let slow_route = RouteHandler::new(
+            HttpMethod::GET,
+            "/slow",
+            Arc::new( move |_request| {
+                started_tx.lock().unwrap().send( () ).unwrap();
+                release_rx.lock().unwrap().recv_timeout( Duration::from_secs( 5 ) ).unwrap();
+                Response::new( HttpStatus::Ok, "slow".to_string(), vec![] )
+            })
+        );
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        server.set_workers( 1 );
+        server.set_queue_capacity( 1 );
+        assert!( server.add_route( slow_route ) );
+        assert!( server.add_route( text_route( "/version", "1" ) ) );
+        server.start().unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // The only worker is busy with the slow request, and the next connection waits in the queue.
+        let slow_request = std::thread::spawn( move || get( addr, "/slow" ) );
+        started_rx.recv_timeout( Duration::from_secs( 5 ) ).unwrap();
+        let mut queued = TcpStream::connect( addr ).unwrap();
+        queued.write_all( b"GET /version HTTP/1.1\r\n\r\n" ).unwrap();
+        let queued_request = std::thread::spawn( move || {
+            let mut response = String::new();
+            queued.read_to_string( &mut response ).unwrap();
+            response
+        });
+
+        // The queue is full, so the next connection gets 503 without waiting.
+        let started = Instant::now();
+        let response = get( addr, "/version" );
+        assert!( started.elapsed() < Duration::from_secs( 2 ) );
+        assert!( response.starts_with( "HTTP/1.1 503 Service Unavailable" ), "{response}" );
+        assert!( response.contains( "Retry-After: 1\r\n" ) );
+        assert_eq!( server.rejected_count(), 1 );
+
+        // The slow and the queued requests are still handled.
+        release_tx.send( () ).unwrap();
+        assert!( slow_request.join().unwrap().ends_with( "slow" ) );
+        assert!( queued_request.join().unwrap().ends_with( "1" ) );
+        server.stop();
+    }
+
+    /// Answer a request for an admin route from a local or a remote client.
+    fn admin_response( context: &ConnectionContext, lines: &[&str], local_peer: bool ) -> Response {
+        let request = Request::new( &lines.iter().map( |line| line.to_string() ).collect() );
+        respond( request, &mut std::io::empty(), context, false, local_peer ).0
+    }
+
+    #[test]
+    fn test_admin_only_route() {
+        let mut context = context( vec![ text_route( "/admin/secret", "secret" ).with_admin_only(), text_route( "/public", "public" ) ] );
+        let request = [ "GET /admin/secret HTTP/1.1" ];
+
+        // Without a token, only local clients can use the route.
+        assert_eq!( admin_response( &context, &request, true ).status, HttpStatus::Ok );
+        assert_eq!( admin_response( &context, &request, false ).status, HttpStatus::Forbidden );
+        assert_eq!( admin_response( &context, &[ "GET /public HTTP/1.1" ], false ).status, HttpStatus::Ok );
+        let (response, _, _) = capture_connection( &context, b"GET /admin/secret HTTP/1.1\r\n\r\n" );
+        assert!( response.ends_with( "secret" ), "{response}" );
+
+        // With a token, remote clients with the token can use it, and the others are asked for it.
+        context.admin_access.token = Some( "s3cret".to_string() );
+        assert_eq!( admin_response( &context, &[ "GET /admin/secret HTTP/1.1", "Authorization: Bearer s3cret" ], false ).status, HttpStatus::Ok );
+        let denied = admin_response( &context, &[ "GET /admin/secret HTTP/1.1", "Authorization: Bearer s3cre" ], false );
+        assert_eq!( denied.status, HttpStatus::Unauthorized );
+        assert!( denied.headers.contains( &("WWW-Authenticate".to_string(), "Bearer realm=\"admin\"".to_string()) ) );
+        assert_eq!( admin_response( &context, &request, false ).status, HttpStatus::Unauthorized );
+        assert_eq!( admin_response( &context, &request, true ).status, HttpStatus::Ok );
+
+        // Behind a proxy local clients need the token too.
+        context.admin_access.local = false;
+        assert_eq!( admin_response( &context, &request, true ).status, HttpStatus::Unauthorized );
+        assert_eq!( admin_response( &context, &[ "GET /admin/secret HTTP/1.1", "Authorization: Bearer s3cret" ], true ).status, HttpStatus::Ok );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_debug_routes_admin_only() {
+        let mut server = WebServer::new( "127.0.0.1", "0" );
+        assert!( server.add_debug_routes() );
+        let context = context( server.routes.read().unwrap().routes().to_vec() );
+        assert_eq!( admin_response( &context, &[ "GET /debug/routes HTTP/1.1" ], true ).status, HttpStatus::Ok );
+        assert_eq!( admin_response( &context, &[ "GET /debug/routes HTTP/1.1" ], false ).status, HttpStatus::Forbidden );
+    }
+
+    /// Read the heads of the requests on `threads` threads at once, each thread reading `requests`
+    /// copies of the request, with the buffers from the pools or with new buffers for every request.
+    ///
+    /// # Returns
+    /// The requests read per second.
+    fn read_heads_per_second( request: &[u8], threads: usize, requests: usize, pooled: bool ) -> f64 {
+        let buffers: BufferPool = BufferPool::new( DEFAULT_POOL_BUFFERS );
+        let lines: BufferPool<Vec<String>> = BufferPool::new( DEFAULT_POOL_BUFFERS );
+        let started = Instant::now();
+        std::thread::scope( |scope| {
+            for _ in 0..threads {
+                scope.spawn( || for _ in 0..requests {
+                    let head = match pooled {
+                        true => {
+                            let mut reader = PooledReader::new( request, &buffers );
+                            read_head( &mut reader, DEFAULT_MAX_TARGET_BYTES, &mut buffers.take(), &mut lines.take() )
+                        }
+                        false => {
+                            let mut reader = BufReader::with_capacity( READ_BUFFER_BYTES, request );
+                            read_head( &mut reader, DEFAULT_MAX_TARGET_BYTES, &mut Vec::new(), &mut Vec::new() )
+                        }
+                    };
+                    assert!( head.unwrap().is_utf8 );
+                });
+            }
+        });
+        (threads * requests) as f64 / started.elapsed().as_secs_f64()
+    }
+
+    /// Compare reading requests with pooled and with new buffers under concurrent load.
+    /// Run with `cargo test --release pooled_buffers_throughput -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn test_pooled_buffers_throughput() {
+        let request = format!( "POST /api/area?unit=ft2 HTTP/1.1\r\nHost: localhost\r\nUser-Agent: bench\r\n\
+            Accept: application/json\r\nContent-Type: application/json\r\nContent-Length: 64\r\n\r\n{}", "x".repeat( 64 ) );
+        for threads in [ 1, DEFAULT_WORKERS, 4 * DEFAULT_WORKERS ] {
+            let requests = 200_000 / threads;
+            let pooled = read_heads_per_second( request.as_bytes(), threads, requests, true );
+            let unpooled = read_heads_per_second( request.as_bytes(), threads, requests, false );
+            println!( "{threads:>2} threads: pooled {pooled:>10.0} requests/s, new buffers {unpooled:>10.0} requests/s ({:+.1}%)",
+                (pooled / unpooled - 1.0) * 100.0 );
+        }
+    }
+}